@@ -0,0 +1,284 @@
+// accounts.rs: Every account in a configuration in one table, for a final look over credentials
+//
+// Copyright (C) 2023 Andrew Rioux
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+use crate::{
+    config::{ConfigurationEditor, User},
+    machines::read_trimmed_input,
+    passwords::DEFAULT_PASSWORD,
+    state::{EditorMessage, EditorStateContext},
+};
+
+/// Where a list of accounts is kept, which decides how a change to it is saved
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum AccountOwner {
+    RedWhiteTeam(usize),
+    BlueTeam(usize),
+    Service(usize, usize),
+}
+
+impl AccountOwner {
+    /// The message that replaces the accounts of this owner, if it still exists
+    fn update(self, config: &ConfigurationEditor, users: Vec<User>) -> Option<EditorMessage> {
+        match self {
+            AccountOwner::RedWhiteTeam(i) => {
+                let mut team = config.red_white_teams.get(i)?.clone();
+                team.users = users;
+                Some(EditorMessage::EditRedWhiteTeam(i, team))
+            }
+            AccountOwner::BlueTeam(i) => {
+                let mut team = config.blue_teams.get(i)?.clone();
+                team.users = users;
+                Some(EditorMessage::EditBlueTeam(i, team))
+            }
+            AccountOwner::Service(machine_index, service_index) => {
+                let mut machine = config.machines.get(machine_index)?.clone();
+                machine.services.get_mut(service_index)?.accounts = Some(users);
+                Some(EditorMessage::UpdateMachine(machine_index, machine))
+            }
+        }
+    }
+
+    fn is_team(self) -> bool {
+        !matches!(self, AccountOwner::Service(..))
+    }
+}
+
+/// Every list of accounts in the configuration, along with who it belongs to
+fn account_groups(config: &ConfigurationEditor) -> Vec<(AccountOwner, String, &[User])> {
+    let mut groups = vec![];
+
+    for (i, team) in config.red_white_teams.iter().enumerate() {
+        groups.push((
+            AccountOwner::RedWhiteTeam(i),
+            format!("Team {}", team.name),
+            &team.users[..],
+        ));
+    }
+
+    for (i, team) in config.blue_teams.iter().enumerate() {
+        groups.push((
+            AccountOwner::BlueTeam(i),
+            format!("Blue team {}", team.name),
+            &team.users[..],
+        ));
+    }
+
+    for (machine_index, machine) in config.machines.iter().enumerate() {
+        for (service_index, service) in machine.services.iter().enumerate() {
+            if let Some(accounts) = &service.accounts {
+                groups.push((
+                    AccountOwner::Service(machine_index, service_index),
+                    format!("Service {} on machine {}", service.name, machine.name),
+                    &accounts[..],
+                ));
+            }
+        }
+    }
+
+    groups
+}
+
+fn count_usernames<'a>(users: impl Iterator<Item = &'a User>) -> BTreeMap<&'a str, usize> {
+    let mut counts = BTreeMap::new();
+    for user in users.filter(|user| !user.username.is_empty()) {
+        *counts.entry(user.username.as_str()).or_default() += 1;
+    }
+    counts
+}
+
+/// What is likely wrong with an account; repeated usernames are counted across every
+/// team, as the engine needs team users to be unique, but only within a service for
+/// service accounts
+fn account_problems(user: &User, username_counts: &BTreeMap<&str, usize>) -> Vec<&'static str> {
+    let mut problems = vec![];
+
+    if user.username.trim().is_empty() {
+        problems.push("no username");
+    } else if username_counts.get(user.username.as_str()).copied() > Some(1) {
+        problems.push("username used more than once");
+    }
+
+    if user.password.is_empty() {
+        problems.push("no password");
+    } else if user.password == DEFAULT_PASSWORD {
+        problems.push("default password");
+    }
+
+    problems
+}
+
+#[function_component]
+pub fn AccountsReview() -> Html {
+    let editor_state = use_context::<EditorStateContext>().unwrap();
+    let config = editor_state.force_init().0;
+
+    let only_problems = use_state(bool::default);
+
+    let set_only_problems = {
+        let only_problems = only_problems.clone();
+
+        Callback::from(move |e: Event| {
+            only_problems.set(e.target_unchecked_into::<HtmlInputElement>().checked());
+        })
+    };
+
+    let groups = account_groups(config);
+
+    let team_usernames = count_usernames(
+        groups
+            .iter()
+            .filter(|(owner, _, _)| owner.is_team())
+            .flat_map(|(_, _, users)| users.iter()),
+    );
+
+    // Changes the account at `i` of an owner, keeping the rest of its accounts as
+    // they are
+    let update_account = |owner: AccountOwner,
+                          users: &[User],
+                          i: usize,
+                          change: fn(&mut User, &HtmlInputElement)| {
+        let editor_state = editor_state.clone();
+        let config = config.clone();
+        let users = users.to_vec();
+
+        Callback::from(move |e: Event| {
+            let input = e.target_unchecked_into::<HtmlInputElement>();
+            let mut users = users.clone();
+            change(&mut users[i], &input);
+
+            if let Some(message) = owner.update(&config, users) {
+                editor_state.dispatch(message);
+            }
+        })
+    };
+
+    let mut problem_count = 0;
+
+    let rows = groups
+        .iter()
+        .map(|(owner, description, users)| {
+            let service_usernames;
+            let username_counts = if owner.is_team() {
+                &team_usernames
+            } else {
+                service_usernames = count_usernames(users.iter());
+                &service_usernames
+            };
+
+            let accounts = users
+                .iter()
+                .enumerate()
+                .map(|(i, user)| (i, user, account_problems(user, username_counts)))
+                .collect::<Vec<_>>();
+
+            problem_count += accounts
+                .iter()
+                .filter(|(_, _, problems)| !problems.is_empty())
+                .count();
+
+            let shown = accounts
+                .into_iter()
+                .filter(|(_, _, problems)| !*only_problems || !problems.is_empty())
+                .map(|(i, user, problems)| {
+                    html! {
+                        <tr key={i} class={classes!((!problems.is_empty()).then_some("has-problems"))}>
+                            <td>
+                                <input
+                                    value={user.username.clone()}
+                                    onchange={update_account(*owner, users, i, |user, input| {
+                                        user.username = read_trimmed_input(input);
+                                    })}
+                                />
+                            </td>
+                            <td>
+                                <input
+                                    value={user.password.clone()}
+                                    onchange={update_account(*owner, users, i, |user, input| {
+                                        user.password = input.value();
+                                    })}
+                                />
+                            </td>
+                            <td>{ problems.join(", ") }</td>
+                        </tr>
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            if shown.is_empty() && (*only_problems || !users.is_empty()) {
+                return html! {};
+            }
+
+            html! {
+                <>
+                    <tr class="accounts-group">
+                        <th colspan="3">{ description }</th>
+                    </tr>
+
+                    if users.is_empty() {
+                        <tr>
+                            <td colspan="3" class="accounts-empty">{ "No accounts yet" }</td>
+                        </tr>
+                    }
+
+                    { for shown }
+                </>
+            }
+        })
+        .collect::<Html>();
+
+    html! {
+        <main id="accounts">
+            <h3>{ "Accounts" }</h3>
+
+            <p>
+                { "Every team user and service account in the configuration. Changes are saved as soon as a field is left." }
+            </p>
+
+            <div class="accounts-summary">
+                { match problem_count {
+                    0 => "No accounts look wrong. ".to_owned(),
+                    1 => "1 account looks wrong. ".to_owned(),
+                    n => format!("{n} accounts look wrong. "),
+                } }
+
+                <label>
+                    <input type="checkbox" checked={*only_problems} onchange={set_only_problems} />
+                    { "Only show accounts that look wrong" }
+                </label>
+            </div>
+
+            <table>
+                <thead>
+                    <tr>
+                        <th>{ "Username" }</th>
+                        <th>{ "Password" }</th>
+                        <th>{ "Problems" }</th>
+                    </tr>
+                </thead>
+
+                <tbody>
+                    { rows }
+                </tbody>
+            </table>
+        </main>
+    }
+}
@@ -0,0 +1,26 @@
+// conversion_worker.rs: Entry point of the Web Worker that converts configurations
+//
+// Copyright (C) 2023 Andrew Rioux
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use gloo_worker::Registrable;
+
+use scoringengine_editor::worker;
+
+fn main() {
+    worker::ConversionWorker::registrar()
+        .encoding::<worker::JsonCodec>()
+        .register();
+}
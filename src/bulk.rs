@@ -0,0 +1,124 @@
+// bulk.rs: Changes to the port or points of every service of a type at once
+//
+// Copyright (C) 2023 Andrew Rioux
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+use crate::{
+    config::{BulkServiceChange, LockableField},
+    machines::parse_whole_number,
+    state::{EditorMessage, EditorStateContext},
+};
+
+#[function_component]
+pub fn BulkServiceEdit() -> Html {
+    let editor_state = use_context::<EditorStateContext>().unwrap();
+    let (config, _, _, _) = editor_state.force_init();
+
+    // How many services use each check, and how many of those have a locked port and
+    // locked points
+    let mut checks = BTreeMap::<&str, (usize, usize, usize)>::new();
+    for service in config.machines.iter().flat_map(|machine| &machine.services) {
+        let counts = checks.entry(service.definition.check_name()).or_default();
+        counts.0 += 1;
+        counts.1 += usize::from(service.is_locked(LockableField::Port));
+        counts.2 += usize::from(service.is_locked(LockableField::Points));
+    }
+
+    let check_ref = use_node_ref();
+    let value_ref = use_node_ref();
+    let error = use_state(Option::<String>::default);
+
+    let apply = |field: LockableField| {
+        let editor_state = editor_state.clone();
+        let check_ref = check_ref.clone();
+        let value_ref = value_ref.clone();
+        let error = error.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            let (Some(check), Some(value)) = (
+                check_ref.cast::<HtmlSelectElement>(),
+                value_ref.cast::<HtmlInputElement>(),
+            ) else {
+                return;
+            };
+
+            let change = match field {
+                LockableField::Port => parse_whole_number(&value.value(), 1, u16::MAX, "Port")
+                    .map(BulkServiceChange::Port),
+                LockableField::Points => parse_whole_number(&value.value(), 0, u16::MAX, "Points")
+                    .map(BulkServiceChange::Points),
+            };
+
+            match change {
+                Ok(change) => {
+                    error.set(None);
+                    editor_state.dispatch(EditorMessage::BulkChangeServices(check.value(), change));
+                }
+                Err(e) => error.set(Some(e)),
+            }
+        })
+    };
+    let set_port = apply(LockableField::Port);
+    let set_points = apply(LockableField::Points);
+
+    if checks.is_empty() {
+        return html! {
+            <p>{ "There are no services to change yet." }</p>
+        };
+    }
+
+    let locked = editor_state.is_locked();
+
+    html! {
+        <div class="bulk-service-edit">
+            <label>
+                { "Services: " }
+                <select ref={check_ref} disabled={locked}>
+                    { for checks.iter().map(|(check, (services, locked_ports, locked_points))| html! {
+                        <option value={check.to_string()}>
+                            { format!(
+                                "{check} ({services} services, {locked_ports} with a locked port, {locked_points} with locked points)"
+                            ) }
+                        </option>
+                    }) }
+                </select>
+            </label>
+
+            <label>
+                { "New value: " }
+                <input ref={value_ref} type="number" min="0" max="65535" step="1" disabled={locked} />
+            </label>
+
+            if !locked {
+                <a href="#" class="button" onclick={set_port}>
+                    { "Set port" }
+                </a>
+                <a href="#" class="button" onclick={set_points}>
+                    { "Set points" }
+                </a>
+            }
+
+            if let Some(error) = &*error {
+                <div class="error">{ error }</div>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,348 @@
+// compare.rs: Compares what the editor generates with what a scoring engine is running,
+// or with what was generated the last time the configuration was exported
+//
+// Copyright (C) 2023 Andrew Rioux
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+
+use web_sys::HtmlTextAreaElement;
+use yew::prelude::*;
+
+use crate::{
+    config::{convert_editor_to_final, FinalConfiguration, ServiceConfig, TeamConfig, User},
+    state::{EditorMessage, EditorStateContext, ExportSnapshot},
+};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Change {
+    OnlyInEditor,
+    OnlyInEngine,
+    Differs(Vec<&'static str>),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Difference {
+    pub subject: String,
+    pub change: Change,
+}
+
+type TeamParts<'a> = (&'a [User], &'a [ServiceConfig]);
+
+fn teams_by_name(config: &FinalConfiguration) -> BTreeMap<(&'static str, &str), TeamParts<'_>> {
+    config
+        .teams
+        .iter()
+        .map(|team| match team {
+            TeamConfig::Red { name, users } => (("red", name.as_str()), (&users[..], &[][..])),
+            TeamConfig::White { name, users } => (("white", name.as_str()), (&users[..], &[][..])),
+            TeamConfig::Blue {
+                name,
+                users,
+                services,
+            } => (("blue", name.as_str()), (&users[..], &services[..])),
+        })
+        .collect()
+}
+
+fn differing_fields(expected: &ServiceConfig, live: &ServiceConfig) -> Vec<&'static str> {
+    [
+        ("check", expected.check_name == live.check_name),
+        ("host", expected.host == live.host),
+        ("port", expected.port == live.port),
+        ("points", expected.points == live.points),
+        ("interval", expected.interval == live.interval),
+        ("timeout", expected.timeout == live.timeout),
+        ("accounts", expected.accounts == live.accounts),
+        ("checks", expected.environments == live.environments),
+    ]
+    .into_iter()
+    .filter(|(_, same)| !same)
+    .map(|(field, _)| field)
+    .collect()
+}
+
+/// Every flag that the two configurations disagree on, matched up by name
+fn compare_flags(expected: &FinalConfiguration, live: &FinalConfiguration) -> Vec<Difference> {
+    let live_flags = live
+        .flags
+        .iter()
+        .map(|flag| (flag.name.as_str(), flag))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut differences = vec![];
+
+    for flag in &expected.flags {
+        let subject = format!("flag {}", flag.name);
+
+        match live_flags.get(flag.name.as_str()) {
+            None => differences.push(Difference {
+                subject,
+                change: Change::OnlyInEditor,
+            }),
+            Some(live_flag) if live_flag.value != flag.value => differences.push(Difference {
+                subject,
+                change: Change::Differs(vec!["value"]),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    for name in live_flags.keys() {
+        if !expected.flags.iter().any(|flag| flag.name == *name) {
+            differences.push(Difference {
+                subject: format!("flag {name}"),
+                change: Change::OnlyInEngine,
+            });
+        }
+    }
+
+    differences
+}
+
+/// Every team and service that the generated configuration and the engine's disagree
+/// on, matched up by color and name
+pub fn compare(expected: &FinalConfiguration, live: &FinalConfiguration) -> Vec<Difference> {
+    let mut differences = compare_flags(expected, live);
+
+    let expected = teams_by_name(expected);
+    let live = teams_by_name(live);
+
+    for ((color, name), (users, services)) in &expected {
+        let team = format!("{color} team {name}");
+
+        let Some((live_users, live_services)) = live.get(&(color, name)) else {
+            differences.push(Difference {
+                subject: team,
+                change: Change::OnlyInEditor,
+            });
+            continue;
+        };
+
+        if users != live_users {
+            differences.push(Difference {
+                subject: team.clone(),
+                change: Change::Differs(vec!["users"]),
+            });
+        }
+
+        let live_services = live_services
+            .iter()
+            .map(|service| (service.name.as_str(), service))
+            .collect::<BTreeMap<_, _>>();
+
+        for service in services.iter() {
+            let subject = format!("service {} of {team}", service.name);
+
+            match live_services.get(service.name.as_str()) {
+                None => differences.push(Difference {
+                    subject,
+                    change: Change::OnlyInEditor,
+                }),
+                Some(live_service) => {
+                    let fields = differing_fields(service, live_service);
+                    if !fields.is_empty() {
+                        differences.push(Difference {
+                            subject,
+                            change: Change::Differs(fields),
+                        });
+                    }
+                }
+            }
+        }
+
+        for name in live_services.keys() {
+            if !services.iter().any(|service| service.name == *name) {
+                differences.push(Difference {
+                    subject: format!("service {name} of {team}"),
+                    change: Change::OnlyInEngine,
+                });
+            }
+        }
+    }
+
+    for (color, name) in live.keys() {
+        if !expected.contains_key(&(color, name)) {
+            differences.push(Difference {
+                subject: format!("{color} team {name}"),
+                change: Change::OnlyInEngine,
+            });
+        }
+    }
+
+    differences
+}
+
+#[function_component]
+pub fn LiveComparison() -> Html {
+    let editor_state = use_context::<EditorStateContext>().unwrap();
+    let config = editor_state.force_init().0;
+
+    let live_yaml = use_state(String::new);
+
+    let on_input = {
+        let live_yaml = live_yaml.clone();
+        Callback::from(move |e: InputEvent| {
+            live_yaml.set(e.target_unchecked_into::<HtmlTextAreaElement>().value());
+        })
+    };
+
+    let report = if live_yaml.trim().is_empty() {
+        html! {}
+    } else {
+        match (
+            convert_editor_to_final(config),
+            serde_yaml::from_str::<FinalConfiguration>(&live_yaml),
+        ) {
+            (Err(e), _) => html! {
+                <p class="error">{ format!("The editor can't generate a configuration to compare: {e}") }</p>
+            },
+            (_, Err(e)) => html! {
+                <p class="error">{ format!("The pasted configuration couldn't be read: {e}") }</p>
+            },
+            (Ok((expected, _)), Ok(live)) => {
+                let differences = compare(&expected, &live);
+
+                if differences.is_empty() {
+                    html! {
+                        <p>{ "The engine is running exactly what the editor generates." }</p>
+                    }
+                } else {
+                    html! {
+                        <ul class="findings">
+                            { for differences.iter().map(|difference| {
+                                let (class, label) = match &difference.change {
+                                    Change::OnlyInEditor => ("warning", "Missing from engine".to_owned()),
+                                    Change::OnlyInEngine => ("warning", "Only in engine".to_owned()),
+                                    Change::Differs(fields) => ("error", format!("Differs in {}", fields.join(", "))),
+                                };
+
+                                html! {
+                                    <li class={class}>
+                                        <strong>{ label } { ": " }</strong>
+                                        { &difference.subject }
+                                    </li>
+                                }
+                            }) }
+                        </ul>
+                    }
+                }
+            }
+        }
+    };
+
+    html! {
+        <div class="live-comparison">
+            <textarea
+                placeholder="Paste the configuration YAML the scoring engine is running"
+                value={live_yaml.to_string()}
+                oninput={on_input}
+            />
+
+            { report }
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ChangesSinceExportProps {
+    /// The configuration as the worker last generated it, if it could be
+    pub current: Option<FinalConfiguration>,
+    /// Whether a newer version of the configuration is still being generated
+    pub converting: bool,
+}
+
+#[function_component]
+pub fn ChangesSinceExport(props: &ChangesSinceExportProps) -> Html {
+    let editor_state = use_context::<EditorStateContext>().unwrap();
+    let current = props.current.clone();
+
+    let record_export = {
+        let editor_state = editor_state.clone();
+        let current = current.clone();
+        let converting = props.converting;
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            let (Some(current), false) = (&current, converting) else {
+                return;
+            };
+
+            editor_state.dispatch(EditorMessage::RecordExport(ExportSnapshot {
+                exported_at: js_sys::Date::new_0().to_iso_string().into(),
+                config: current.clone(),
+            }));
+        })
+    };
+
+    let changes = match (editor_state.last_export(), &current) {
+        (None, _) => html! {
+            <p>{ "This configuration hasn't been exported yet." }</p>
+        },
+        (Some(_), None) => html! {
+            <p>{ "Changes can be listed once the configuration converts again." }</p>
+        },
+        (Some(snapshot), Some(current)) => {
+            let differences = compare(current, &snapshot.config);
+
+            html! {
+                <>
+                    <p>{ format!("Last exported {}.", snapshot.exported_at) }</p>
+
+                    if differences.is_empty() {
+                        <p>{ "Nothing has changed since." }</p>
+                    } else {
+                        <ul class="findings">
+                            { for differences.iter().map(|difference| {
+                                let label = match &difference.change {
+                                    Change::OnlyInEditor => "Added".to_owned(),
+                                    Change::OnlyInEngine => "Removed".to_owned(),
+                                    Change::Differs(fields) => format!("Changed {}", fields.join(", ")),
+                                };
+
+                                html! {
+                                    <li class="warning">
+                                        <strong>{ label } { ": " }</strong>
+                                        { &difference.subject }
+                                    </li>
+                                }
+                            }) }
+                        </ul>
+                    }
+                </>
+            }
+        }
+    };
+
+    html! {
+        <div class="changes-since-export">
+            <h4>{ "Changes since the last export" }</h4>
+
+            { changes }
+
+            <a
+                href="#"
+                class={classes!(
+                    "button",
+                    (current.is_none() || props.converting).then_some("disabled")
+                )}
+                onclick={record_export}
+                title="Remember the configuration as it is now, to compare the next export against"
+            >
+                { "Mark as exported" }
+            </a>
+        </div>
+    }
+}
@@ -0,0 +1,136 @@
+// components.rs: Pieces of interface shared by several pages
+//
+// Copyright (C) 2023 Andrew Rioux
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use wasm_bindgen::JsCast;
+use web_sys::{window, Element, HtmlElement, KeyboardEvent};
+use yew::prelude::*;
+
+/// Everything inside a modal that can be reached with Tab
+const FOCUSABLE: &str = "a[href], button, input:not([disabled]), select:not([disabled]), textarea:not([disabled]), [tabindex]:not([tabindex=\"-1\"])";
+
+fn focusable_elements(modal: &Element) -> Vec<HtmlElement> {
+    let Ok(nodes) = modal.query_selector_all(FOCUSABLE) else {
+        return vec![];
+    };
+
+    (0..nodes.length())
+        .filter_map(|i| nodes.get(i))
+        .filter_map(|node| node.dyn_into::<HtmlElement>().ok())
+        .collect()
+}
+
+fn focused_element() -> Option<HtmlElement> {
+    window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.active_element())
+        .and_then(|element| element.dyn_into::<HtmlElement>().ok())
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ModalProps {
+    /// Called when the dialog asks to be closed, such as when Escape is pressed
+    pub on_close: Callback<()>,
+    #[prop_or_default]
+    pub class: Classes,
+    pub children: Html,
+}
+
+/// A dialog over the rest of the page, which keeps keyboard focus inside itself while
+/// open, closes on Escape, and gives focus back to whatever had it before opening
+#[function_component]
+pub fn Modal(props: &ModalProps) -> Html {
+    let modal_ref = use_node_ref();
+
+    {
+        let modal_ref = modal_ref.clone();
+
+        use_effect_with((), move |_| {
+            let previous_focus = focused_element();
+
+            if let Some(modal) = modal_ref.cast::<HtmlElement>() {
+                let first = focusable_elements(&modal).into_iter().next();
+                if let Err(e) = first.unwrap_or(modal).focus() {
+                    log::error!("Could not focus the dialog: {e:?}");
+                }
+            }
+
+            move || {
+                if let Some(element) = previous_focus {
+                    let _ = element.focus();
+                }
+            }
+        });
+    }
+
+    let onkeydown = {
+        let modal_ref = modal_ref.clone();
+        let on_close = props.on_close.clone();
+
+        Callback::from(move |e: KeyboardEvent| {
+            // Shortcuts for the page underneath shouldn't fire while the dialog is open
+            e.stop_propagation();
+
+            if e.key() == "Escape" {
+                e.prevent_default();
+                on_close.emit(());
+                return;
+            }
+
+            if e.key() != "Tab" {
+                return;
+            }
+
+            let Some(modal) = modal_ref.cast::<Element>() else {
+                return;
+            };
+            let elements = focusable_elements(&modal);
+            let (Some(first), Some(last)) = (elements.first(), elements.last()) else {
+                e.prevent_default();
+                return;
+            };
+
+            let focused = focused_element();
+            let wrap_to = if e.shift_key() && focused.as_ref() == Some(first) {
+                Some(last)
+            } else if !e.shift_key() && focused.as_ref() == Some(last) {
+                Some(first)
+            } else {
+                None
+            };
+
+            if let Some(element) = wrap_to {
+                e.prevent_default();
+                let _ = element.focus();
+            }
+        })
+    };
+
+    html! {
+        <div class="modal-backdrop">
+            <div
+                ref={modal_ref}
+                class={classes!("modal", props.class.clone())}
+                role="dialog"
+                aria-modal="true"
+                tabindex="-1"
+                {onkeydown}
+            >
+                { props.children.clone() }
+            </div>
+        </div>
+    }
+}
@@ -16,9 +16,10 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     error::Error,
     fmt::Display,
+    net::Ipv4Addr,
 };
 
 use serde::{Deserialize, Serialize};
@@ -28,7 +29,7 @@ pub enum ConversionError {
     OneTeamConfigurationWithMultipleTeams,
     XInManualIP(String),
     NoXInTemplateIP(String),
-    MultNotBigEnough(u8, u8),
+    MultNotBigEnough(usize, u8),
     OffsetNotSpecified(String),
     MissingOffset(String),
     DuplicateOffsets(Vec<String>),
@@ -40,9 +41,24 @@ pub enum ConversionError {
     MachineHasEmptyName,
     MachineHasEmptyService(String),
     DuplicateUserNameForTeams(String, Vec<String>),
+    DuplicateTeamName(String),
     DuplicateMachineNames(String),
     ServiceNotFullyConfigured(String, String, String),
     DuplicateServiceName(String, String),
+    DuplicateGeneratedServiceName(String, String),
+    EmptyHost(String),
+    ServiceNeedsAccount(String, String),
+    ServiceHasNoHost(String, String),
+    InvalidDuration(String, String, &'static str),
+    InvalidIpAddress(String, String),
+    InvalidCidr(String),
+    CidrTooSmall(String, usize, u32),
+    InvalidHostname(String, String),
+    HostnameNotPerTeam(String),
+    UnknownCheck(String, String),
+    UnrecoverableHost(String),
+    FlagHasEmptyName,
+    DuplicateFlagName(String),
 }
 
 impl Error for ConversionError {}
@@ -93,6 +109,12 @@ impl Display for ConversionError {
                     "duplicate ip address {ip} specified for machines {m1} and {m2}"
                 )
             }
+            Self::InvalidIpAddress(m, ip) => {
+                write!(
+                    f,
+                    "machine {m} would have the address {ip}, which is not a valid ipv4 address"
+                )
+            }
             Self::MissingOffset(m) => {
                 write!(f, "machine {m} is missing an offset")
             }
@@ -119,6 +141,15 @@ impl Display for ConversionError {
                     teams.join(", ")
                 )
             }
+            Self::DuplicateTeamName(name) => {
+                write!(f, "multiple teams are named '{name}'")
+            }
+            Self::DuplicateGeneratedServiceName(team, name) => {
+                write!(
+                    f,
+                    "the team {team} would have multiple services named '{name}'; rename a machine or service so they can be told apart"
+                )
+            }
             Self::MachineHasEmptyName => {
                 write!(f, "there can't be any machines with no name")
             }
@@ -134,6 +165,72 @@ impl Display for ConversionError {
                     "the service {service} on machine {machine} was not fully configured: {err}"
                 )
             }
+            Self::EmptyHost(machine) => {
+                write!(
+                    f,
+                    "machine {machine} has no ip address or template, so its services have no host"
+                )
+            }
+            Self::InvalidDuration(machine, service, field) => {
+                write!(
+                    f,
+                    "the {field} of the service {service} on machine {machine} must be at least one second"
+                )
+            }
+            Self::ServiceHasNoHost(machine, service) => {
+                write!(
+                    f,
+                    "the service {service} on machine {machine} has no host to check, as the machine has no ip address or template"
+                )
+            }
+            Self::ServiceNeedsAccount(machine, service) => {
+                write!(
+                    f,
+                    "the service {service} on machine {machine} needs at least one account to log in with"
+                )
+            }
+            Self::InvalidCidr(cidr) => {
+                write!(
+                    f,
+                    "the network {cidr} must be an IPv4 address followed by a prefix length of at most 30"
+                )
+            }
+            Self::CidrTooSmall(cidr, needed, available) => {
+                write!(
+                    f,
+                    "the network {cidr} has room for {available} hosts, but {needed} are needed to give every machine of every blue team an address"
+                )
+            }
+            Self::InvalidHostname(machine, hostname) => {
+                write!(
+                    f,
+                    "machine {machine} has the hostname {hostname}, which isn't a valid DNS name"
+                )
+            }
+            Self::HostnameNotPerTeam(machine) => {
+                write!(
+                    f,
+                    "the hostname of machine {machine} has no {{id}} or X part, so every blue team's copy of it would have the same name"
+                )
+            }
+            Self::UnknownCheck(service, check) => {
+                write!(
+                    f,
+                    "the service {service} uses the check {check}, which the editor doesn't know"
+                )
+            }
+            Self::UnrecoverableHost(machine) => {
+                write!(
+                    f,
+                    "the hosts of machine {machine} don't follow any of the editor's ip address or hostname schemes, so it can't be turned back into a machine template"
+                )
+            }
+            Self::FlagHasEmptyName => {
+                write!(f, "one of the flags has no name")
+            }
+            Self::DuplicateFlagName(name) => {
+                write!(f, "multiple flags are named '{name}'")
+            }
             Self::DuplicateServiceName(machine, service) => {
                 write!(
                     f,
@@ -163,25 +260,44 @@ impl User {
     }
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
-pub enum TeamColor {
-    White,
-    Red,
-    Blue,
+/// A flag planted for the red team to capture, written out under `flags`
+#[derive(Deserialize, Serialize, Eq, PartialEq, Debug, Clone, Default)]
+pub struct Flag {
+    pub name: String,
+    pub value: String,
 }
 
+/// A generated configuration that carries the editor configuration it came from, so
+/// the same file can be loaded by the scoring engine, which ignores `editor_info`,
+/// and opened again in the editor without losing anything
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Configuration {
     pub editor_info: ConfigurationEditor,
     pub teams: Vec<TeamConfig>,
+    #[serde(default)]
+    pub flags: Vec<Flag>,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+impl Configuration {
+    /// A generated configuration along with the editor data it was generated from
+    pub fn new(editor_info: ConfigurationEditor, final_config: FinalConfiguration) -> Self {
+        Self {
+            editor_info,
+            teams: final_config.teams,
+            flags: final_config.flags,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
 pub struct FinalConfiguration {
     pub teams: Vec<TeamConfig>,
+    /// Left out of configurations written before flags could be edited
+    #[serde(default)]
+    pub flags: Vec<Flag>,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
 #[serde(tag = "color")]
 pub enum TeamConfig {
     Red {
@@ -209,9 +325,26 @@ pub struct EnvironmentProperties {
 pub struct Environment {
     pub matching_content: String,
     pub properties: Vec<EnvironmentProperties>,
+    /// Set when the check passes on the result being absent
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub negate: bool,
+    /// Only present for services that ask for their accounts on each check
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accounts: Option<Vec<User>>,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+impl Environment {
+    /// The value of a property, or nothing if the environment doesn't have it
+    fn property(&self, name: &str) -> String {
+        self.properties
+            .iter()
+            .find(|property| property.name == name)
+            .map(|property| property.value.clone())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Deserialize, Serialize, Eq, PartialEq, Debug, Clone)]
 pub struct ServiceConfig {
     pub name: String,
     pub check_name: String,
@@ -220,6 +353,12 @@ pub struct ServiceConfig {
     pub points: u16,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub accounts: Option<Vec<User>>,
+    /// Seconds between checks, when the engine's default shouldn't be used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval: Option<u32>,
+    /// Seconds before a check is given up on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u32>,
     pub environments: Vec<Environment>,
 }
 
@@ -230,19 +369,229 @@ pub struct ServiceEditor {
     pub points: u16,
     pub definition: ServiceDefinition,
     pub accounts: Option<Vec<User>>,
+    /// Emit the accounts on every environment instead of on the service, for engines
+    /// whose checks expect their own credentials
+    #[serde(default)]
+    pub inline_accounts: bool,
+    /// IDs of the blue teams this service is not scored for
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub excluded_teams: BTreeSet<u8>,
+    /// Set once the user has confirmed a port other than the conventional one for
+    /// this type of service is intended
+    #[serde(default)]
+    pub allow_unusual_port: bool,
+    /// Seconds between checks; left to the engine when not set
+    #[serde(default)]
+    pub interval: Option<u32>,
+    /// Seconds before a check is given up on; left to the engine when not set
+    #[serde(default)]
+    pub timeout: Option<u32>,
+    /// Still checked for monitoring, but never worth any points
+    #[serde(default)]
+    pub informational: bool,
+    /// What passing the check means in plain words, for the answer key, when the
+    /// check has a single result rather than a list of environments
+    #[serde(default)]
+    pub expected_result: String,
+    /// Fields that were set by hand on purpose, which bulk changes leave alone
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub locked_fields: BTreeSet<LockableField>,
+}
+
+/// A field of a service that can be protected from bulk changes
+#[derive(Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd, Debug, Clone, Copy)]
+pub enum LockableField {
+    Port,
+    Points,
+}
+
+/// A value given to every service of one type at once
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BulkServiceChange {
+    Port(u16),
+    Points(u16),
+}
+
+impl ServiceEditor {
+    pub fn is_locked(&self, field: LockableField) -> bool {
+        self.locked_fields.contains(&field)
+    }
+
+    /// Sets the port as part of a change to many services at once, unless the port
+    /// is locked; returns whether it was set
+    pub fn bulk_set_port(&mut self, port: u16) -> bool {
+        if self.is_locked(LockableField::Port) {
+            return false;
+        }
+
+        self.port = port;
+        true
+    }
+
+    /// Sets the points as part of a change to many services at once, unless they are
+    /// locked; returns whether they were set
+    pub fn bulk_set_points(&mut self, points: u16) -> bool {
+        if self.is_locked(LockableField::Points) {
+            return false;
+        }
+
+        self.points = points;
+        true
+    }
+
+    /// The name the service is given in the generated configuration, which has to be
+    /// unique across machines
+    pub fn config_name(&self, machine: &str) -> String {
+        format!("{machine}-{}-{}", self.definition.check_name(), self.name)
+    }
+
+    /// The points the service is worth in the generated configuration
+    pub fn scored_points(&self) -> u16 {
+        if self.informational {
+            0
+        } else {
+            self.points
+        }
+    }
+
+    /// The result each enabled check looks for, along with what the organizers
+    /// wrote that it means
+    pub fn check_annotations(&self) -> Vec<CheckAnnotation> {
+        fn annotated<T: CheckInfo>(checks: &[T]) -> Vec<CheckAnnotation> {
+            checks
+                .iter()
+                .filter(|check| !check.disabled())
+                .map(|check| CheckAnnotation {
+                    matching_content: check.matching_content().to_owned(),
+                    expected_result: check.expected_result().to_owned(),
+                    negate: check.negate(),
+                })
+                .collect()
+        }
+
+        match &self.definition {
+            ServiceDefinition::Dns { environment } => annotated(environment),
+            ServiceDefinition::Docker { environment } => annotated(environment),
+            ServiceDefinition::Elasticsearch { environment } => annotated(environment),
+            ServiceDefinition::Ftp { environment } => annotated(environment),
+            ServiceDefinition::Http { environment }
+            | ServiceDefinition::Https { environment }
+            | ServiceDefinition::Wordpress { environment } => annotated(environment),
+            ServiceDefinition::Imap { environment } | ServiceDefinition::Imaps { environment } => {
+                annotated(environment)
+            }
+            ServiceDefinition::Ldap { environment } => annotated(environment),
+            ServiceDefinition::Mssql { environment }
+            | ServiceDefinition::Mysql { environment }
+            | ServiceDefinition::PostgreSql { environment } => annotated(environment),
+            ServiceDefinition::Nfs { environment } => annotated(environment),
+            ServiceDefinition::Pop3 { environment } | ServiceDefinition::Pop3s { environment } => {
+                annotated(environment)
+            }
+            ServiceDefinition::Smb { environment } => annotated(environment),
+            ServiceDefinition::Smtp { environment } | ServiceDefinition::Smtps { environment } => {
+                annotated(environment)
+            }
+            ServiceDefinition::Ssh { environment } | ServiceDefinition::WinRm { environment } => {
+                annotated(environment)
+            }
+            ServiceDefinition::Icmp { environment }
+            | ServiceDefinition::Rdp { environment }
+            | ServiceDefinition::Vnc { environment } => vec![CheckAnnotation {
+                matching_content: environment
+                    .as_deref()
+                    .or(self.definition.default_matching_content())
+                    .unwrap_or_default()
+                    .to_owned(),
+                expected_result: self.expected_result.clone(),
+                negate: false,
+            }],
+        }
+    }
+}
+
+/// A result a check looks for, and what it means for the service to produce it
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CheckAnnotation {
+    pub matching_content: String,
+    pub expected_result: String,
+    pub negate: bool,
+}
+
+/// The fields every kind of check environment has, whatever else it needs
+trait CheckInfo {
+    fn matching_content(&self) -> &str;
+    fn disabled(&self) -> bool;
+    /// What passing the check means in plain words, for the answer key
+    fn expected_result(&self) -> &str;
+    /// Whether the check passes when `matching_content` is missing from the result,
+    /// rather than when it's found
+    fn negate(&self) -> bool;
+}
+
+macro_rules! impl_check_info {
+    ($($check_info:ty),*) => {
+        $(
+            impl CheckInfo for $check_info {
+                fn matching_content(&self) -> &str {
+                    &self.matching_content
+                }
+
+                fn disabled(&self) -> bool {
+                    self.disabled
+                }
+
+                fn expected_result(&self) -> &str {
+                    &self.expected_result
+                }
+
+                fn negate(&self) -> bool {
+                    self.negate
+                }
+            }
+        )*
+    };
 }
 
+impl_check_info!(
+    DnsCheckInfo,
+    DockerCheckInfo,
+    ElasticsearchCheckInfo,
+    FtpCheckInfo,
+    HttpCheckInfo,
+    ImapCheckInfo,
+    LdapCheckInfo,
+    SqlCheckInfo,
+    NfsCheckInfo,
+    PopCheckInfo,
+    SmbCheckInfo,
+    SmtpCheckInfo,
+    RemoteCommandCheckInfo
+);
+
 #[derive(Deserialize, Serialize, Eq, PartialEq, Debug, Clone, Default)]
 pub struct DnsCheckInfo {
     pub matching_content: String,
     pub qtype: String,
     pub domain: String,
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default)]
+    pub expected_result: String,
+    #[serde(default)]
+    pub negate: bool,
 }
 
 #[derive(Deserialize, Serialize, Eq, PartialEq, Debug, Clone, Default)]
 pub struct DockerCheckInfo {
     pub matching_content: String,
     pub image: String,
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default)]
+    pub expected_result: String,
+    #[serde(default)]
+    pub negate: bool,
 }
 
 #[derive(Deserialize, Serialize, Eq, PartialEq, Debug, Clone, Default)]
@@ -250,6 +599,12 @@ pub struct ElasticsearchCheckInfo {
     pub matching_content: String,
     pub index: String,
     pub doc_type: String,
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default)]
+    pub expected_result: String,
+    #[serde(default)]
+    pub negate: bool,
 }
 
 #[derive(Deserialize, Serialize, Eq, PartialEq, Debug, Clone, Default)]
@@ -257,6 +612,12 @@ pub struct FtpCheckInfo {
     pub matching_content: String,
     pub remotefilepath: String,
     pub filecontents: String,
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default)]
+    pub expected_result: String,
+    #[serde(default)]
+    pub negate: bool,
 }
 
 #[derive(Deserialize, Serialize, Eq, PartialEq, Debug, Clone, Default)]
@@ -265,12 +626,24 @@ pub struct HttpCheckInfo {
     pub useragent: String,
     pub vhost: String,
     pub uri: String,
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default)]
+    pub expected_result: String,
+    #[serde(default)]
+    pub negate: bool,
 }
 
 #[derive(Deserialize, Serialize, Eq, PartialEq, Debug, Clone, Default)]
 pub struct ImapCheckInfo {
     pub matching_content: String,
     pub domain: String,
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default)]
+    pub expected_result: String,
+    #[serde(default)]
+    pub negate: bool,
 }
 
 #[derive(Deserialize, Serialize, Eq, PartialEq, Debug, Clone, Default)]
@@ -278,6 +651,12 @@ pub struct LdapCheckInfo {
     pub matching_content: String,
     pub domain: String,
     pub base_dn: String,
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default)]
+    pub expected_result: String,
+    #[serde(default)]
+    pub negate: bool,
 }
 
 #[derive(Deserialize, Serialize, Eq, PartialEq, Debug, Clone, Default)]
@@ -285,6 +664,12 @@ pub struct SqlCheckInfo {
     pub matching_content: String,
     pub database: String,
     pub command: String,
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default)]
+    pub expected_result: String,
+    #[serde(default)]
+    pub negate: bool,
 }
 
 #[derive(Deserialize, Serialize, Eq, PartialEq, Debug, Clone, Default)]
@@ -292,12 +677,24 @@ pub struct NfsCheckInfo {
     pub matching_content: String,
     pub remotefilepath: String,
     pub filecontents: String,
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default)]
+    pub expected_result: String,
+    #[serde(default)]
+    pub negate: bool,
 }
 
 #[derive(Deserialize, Serialize, Eq, PartialEq, Debug, Clone, Default)]
 pub struct PopCheckInfo {
     pub matching_content: String,
     pub domain: String,
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default)]
+    pub expected_result: String,
+    #[serde(default)]
+    pub negate: bool,
 }
 
 #[derive(Deserialize, Serialize, Eq, PartialEq, Debug, Clone, Default)]
@@ -307,6 +704,12 @@ pub struct SmbCheckInfo {
     pub share: String,
     pub file: String,
     pub hash: String,
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default)]
+    pub expected_result: String,
+    #[serde(default)]
+    pub negate: bool,
 }
 
 #[derive(Deserialize, Serialize, Eq, PartialEq, Debug, Clone, Default)]
@@ -315,12 +718,24 @@ pub struct SmtpCheckInfo {
     pub touser: String,
     pub subject: String,
     pub body: String,
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default)]
+    pub expected_result: String,
+    #[serde(default)]
+    pub negate: bool,
 }
 
 #[derive(Deserialize, Serialize, Eq, PartialEq, Debug, Clone, Default)]
 pub struct RemoteCommandCheckInfo {
     pub matching_content: String,
     pub commands: String,
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default)]
+    pub expected_result: String,
+    #[serde(default)]
+    pub negate: bool,
 }
 
 #[derive(Deserialize, Serialize, Eq, PartialEq, Debug, Clone)]
@@ -400,14 +815,61 @@ pub enum ServiceDefinition {
     },
 }
 
+/// A single failed check on one field of one of a service's environments, for
+/// pointing the user at the exact input that needs fixing
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FieldError {
+    pub environment: usize,
+    pub field: &'static str,
+    pub message: String,
+}
+
+/// Whether a virtual host is anything other than a host name with an optional port,
+/// such as a full URL, none of which belongs in a Host header
+fn is_not_bare_host(vhost: &str) -> bool {
+    if vhost.contains("://")
+        || vhost.contains(['/', '?', '#', '@'])
+        || vhost.chars().any(char::is_whitespace)
+    {
+        return true;
+    }
+
+    let port = match vhost.strip_prefix('[') {
+        // IPv6 addresses are bracketed so their colons aren't taken for a port
+        Some(bracketed) => match bracketed.split_once(']') {
+            Some((_, "")) => None,
+            Some((_, rest)) => match rest.strip_prefix(':') {
+                Some(port) => Some(port),
+                None => return true,
+            },
+            None => return true,
+        },
+        None => vhost.split_once(':').map(|(_, port)| port),
+    };
+
+    port.is_some_and(|port| port.parse::<u16>().is_err())
+}
+
 macro_rules! service_definition_check {
-    (($machine_name:expr, $service_name:expr, $properties:expr), (matching_content => ($($mc_check_expr:expr => $mc_error:expr),*), $($field:ident => ($($check:expr => $error:expr),*)),*)) => {{
+    (($properties:expr, $version:expr), (matching_content => ($($mc_check_expr:expr => $mc_error:expr),*), $($field:ident => ($($check:expr => $error:expr),*)),*)) => {{
         $properties
             .iter()
-            .map(|iter_item| {
+            .enumerate()
+            .filter(|(_, iter_item)| !iter_item.disabled)
+            .map(|(index, iter_item)| {
+                let field_error = |field: &'static str, message: &str| FieldError {
+                    environment: index,
+                    field,
+                    message: message.to_string(),
+                };
                 let errs = [
-                    $(if ($mc_check_expr)(&iter_item.matching_content) { vec![$mc_error.to_string()] } else { vec![] }),*,
-                    $( /* $field */ $(if ($check)(&iter_item.$field) { vec![$error.to_string()] } else { vec![] }),*),*
+                    $(if ($mc_check_expr)(&iter_item.matching_content) { vec![field_error("matching_content", $mc_error)] } else { vec![] }),*,
+                    $( /* $field */ $(if ($check)(&iter_item.$field) { vec![field_error(stringify!($field), $error)] } else { vec![] }),*),*,
+                    if iter_item.negate && !$version.supports_negation() {
+                        vec![field_error("negate", "This version of the engine can't pass a check on a missing result")]
+                    } else {
+                        vec![]
+                    }
                 ].concat();
                 if errs.is_empty() {
                     Ok(Environment {
@@ -417,25 +879,75 @@ macro_rules! service_definition_check {
                                 name: stringify!($field).to_string(),
                                 value: iter_item.$field.clone()
                             }),*
-                        ]
+                        ],
+                        negate: iter_item.negate,
+                        accounts: None,
                     })
                 } else {
-                    Err(crate::config::ConversionError::ServiceNotFullyConfigured($machine_name.to_string(), $service_name.to_string(), errs.join(", ")))
+                    Err(errs)
                 }
             })
-            .collect::<Result<Vec<_>, _>>()
+            .collect::<Vec<_>>()
     }};
 }
 
+/// Rebuilds the check environments of a generated service, taking each field from the
+/// property of the same name
+macro_rules! check_infos_from {
+    ($environments:expr, $check_info:ident { $($field:ident),* }) => {
+        $environments
+            .iter()
+            .map(|environment| $check_info {
+                matching_content: environment.matching_content.clone(),
+                $($field: environment.property(stringify!($field)),)*
+                negate: environment.negate,
+                ..Default::default()
+            })
+            .collect()
+    };
+}
+
 impl ServiceDefinition {
     pub fn environments(
         &self,
         mname: &str,
         sname: &str,
+        version: EngineVersion,
     ) -> Result<Vec<Environment>, ConversionError> {
+        self.checked_environments(version)
+            .into_iter()
+            .map(|environment| {
+                environment.map_err(|errs| {
+                    ConversionError::ServiceNotFullyConfigured(
+                        mname.to_string(),
+                        sname.to_string(),
+                        errs.iter()
+                            .map(|err| &*err.message)
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Every field of every environment that would keep this service from being
+    /// converted
+    pub fn field_errors(&self, version: EngineVersion) -> Vec<FieldError> {
+        self.checked_environments(version)
+            .into_iter()
+            .filter_map(Result::err)
+            .flatten()
+            .collect()
+    }
+
+    fn checked_environments(
+        &self,
+        version: EngineVersion,
+    ) -> Vec<Result<Environment, Vec<FieldError>>> {
         match self {
             ServiceDefinition::Dns { environment: dns } => service_definition_check! {
-                (mname, sname, dns),
+                (dns, version),
                 (
                     matching_content => (
                         str::is_empty => "Service match cannot be empty"
@@ -448,10 +960,23 @@ impl ServiceDefinition {
                     )
                 )
             },
+            ServiceDefinition::Docker {
+                environment: docker,
+            } => service_definition_check! {
+                (docker, version),
+                (
+                    matching_content => (
+                        str::is_empty => "Service match cannot be empty"
+                    ),
+                    image => (
+                        str::is_empty => "Image cannot be empty"
+                    )
+                )
+            },
             ServiceDefinition::Elasticsearch {
                 environment: elasticsearch,
-            } => service_definition_check! {
-                (mname, sname, elasticsearch),
+            } if version.uses_document_types() => service_definition_check! {
+                (elasticsearch, version),
                 (
                     matching_content => (
                         str::is_empty => "Service match cannot be empty"
@@ -464,8 +989,21 @@ impl ServiceDefinition {
                     )
                 )
             },
+            ServiceDefinition::Elasticsearch {
+                environment: elasticsearch,
+            } => service_definition_check! {
+                (elasticsearch, version),
+                (
+                    matching_content => (
+                        str::is_empty => "Service match cannot be empty"
+                    ),
+                    index => (
+                        str::is_empty => "Index cannot be empty"
+                    )
+                )
+            },
             ServiceDefinition::Ftp { environment: ftp } => service_definition_check! {
-                (mname, sname, ftp),
+                (ftp, version),
                 (
                     matching_content => (
                         str::is_empty => "Service match cannot be empty"
@@ -482,7 +1020,7 @@ impl ServiceDefinition {
             | ServiceDefinition::Https { environment: http }
             | ServiceDefinition::Wordpress { environment: http } => {
                 service_definition_check! {
-                    (mname, sname, http),
+                    (http, version),
                     (
                         matching_content => (
                             str::is_empty => "Service match cannot be empty"
@@ -491,7 +1029,8 @@ impl ServiceDefinition {
                             str::is_empty => "User agent cannot be empty"
                         ),
                         vhost => (
-                            str::is_empty => "Virtual host cannot be empty"
+                            str::is_empty => "Virtual host cannot be empty",
+                            is_not_bare_host => "Virtual host must be a host name with an optional port, without http:// or a path"
                         ),
                         uri => (
                             str::is_empty => "URI cannot be empty"
@@ -502,7 +1041,7 @@ impl ServiceDefinition {
             ServiceDefinition::Imap { environment: imap }
             | ServiceDefinition::Imaps { environment: imap } => {
                 service_definition_check! {
-                    (mname, sname, imap),
+                    (imap, version),
                     (
                         matching_content => (
                             str::is_empty => "Service match cannot be empty"
@@ -514,7 +1053,7 @@ impl ServiceDefinition {
                 }
             }
             ServiceDefinition::Ldap { environment: ldap } => service_definition_check! {
-                (mname, sname, ldap),
+                (ldap, version),
                 (
                     matching_content => (
                         str::is_empty => "Service match cannot be empty"
@@ -530,7 +1069,7 @@ impl ServiceDefinition {
             ServiceDefinition::Mssql { environment: sql }
             | ServiceDefinition::Mysql { environment: sql }
             | ServiceDefinition::PostgreSql { environment: sql } => service_definition_check! {
-                (mname, sname, sql),
+                (sql, version),
                 (
                     matching_content => (
                         str::is_empty => "Service match cannot be empty"
@@ -544,7 +1083,7 @@ impl ServiceDefinition {
                 )
             },
             ServiceDefinition::Nfs { environment: nfs } => service_definition_check! {
-                (mname, sname, nfs),
+                (nfs, version),
                 (
                     matching_content => (
                         str::is_empty => "Service match cannot be empty"
@@ -560,7 +1099,7 @@ impl ServiceDefinition {
             ServiceDefinition::Pop3 { environment: pop }
             | ServiceDefinition::Pop3s { environment: pop } => {
                 service_definition_check! {
-                    (mname, sname, pop),
+                    (pop, version),
                     (
                         matching_content => (
                             str::is_empty => "Service match cannot be empty"
@@ -572,7 +1111,7 @@ impl ServiceDefinition {
                 }
             }
             ServiceDefinition::Smb { environment: smb } => service_definition_check! {
-                (mname, sname, smb),
+                (smb, version),
                 (
                     matching_content => (
                         str::is_empty => "Service match cannot be empty"
@@ -595,7 +1134,7 @@ impl ServiceDefinition {
             ServiceDefinition::Smtp { environment: smtp }
             | ServiceDefinition::Smtps { environment: smtp } => {
                 service_definition_check! {
-                    (mname, sname, smtp),
+                    (smtp, version),
                     (
                         matching_content => (
                             str::is_empty => "Service match cannot be empty"
@@ -610,7 +1149,7 @@ impl ServiceDefinition {
             ServiceDefinition::Ssh { environment: cmd }
             | ServiceDefinition::WinRm { environment: cmd } => {
                 service_definition_check! {
-                    (mname, sname, cmd),
+                    (cmd, version),
                     (
                         matching_content => (
                             str::is_empty => "Service match must be empty"
@@ -621,18 +1160,17 @@ impl ServiceDefinition {
                     )
                 }
             }
-            ServiceDefinition::Icmp { environment: None } => Ok(vec![Environment {
-                matching_content: "1 packets transmitted, 1 received".to_string(),
-                properties: vec![],
-            }]),
-            ServiceDefinition::Rdp { environment: None } => Ok(vec![Environment {
-                matching_content: "SUCCESS$".to_string(),
-                properties: vec![],
-            }]),
-            ServiceDefinition::Vnc { environment: None } => Ok(vec![Environment {
-                matching_content: "ACCOUNT FOUND".to_string(),
+            ServiceDefinition::Icmp { environment: None }
+            | ServiceDefinition::Rdp { environment: None }
+            | ServiceDefinition::Vnc { environment: None } => vec![Ok(Environment {
+                matching_content: self
+                    .default_matching_content()
+                    .unwrap_or_default()
+                    .to_string(),
                 properties: vec![],
-            }]),
+                negate: false,
+                accounts: None,
+            })],
             ServiceDefinition::Icmp {
                 environment: Some(matcher),
             }
@@ -641,11 +1179,236 @@ impl ServiceDefinition {
             }
             | ServiceDefinition::Vnc {
                 environment: Some(matcher),
-            } => Ok(vec![Environment {
+            } => vec![Ok(Environment {
                 matching_content: matcher.clone(),
                 properties: vec![],
-            }]),
-            _ => Ok(vec![]),
+                negate: false,
+                accounts: None,
+            })],
+        }
+    }
+
+    /// Rebuilds a definition from the check and environments of a generated service,
+    /// or `None` if the check isn't one the editor knows. Disabled environments are
+    /// never generated, so they can't come back
+    pub fn from_environments(check_name: &str, environments: &[Environment]) -> Option<Self> {
+        let mut definition = match check_name {
+            "DNSCheck" => ServiceDefinition::Dns {
+                environment: check_infos_from!(environments, DnsCheckInfo { qtype, domain }),
+            },
+            "DockerCheck" => ServiceDefinition::Docker {
+                environment: check_infos_from!(environments, DockerCheckInfo { image }),
+            },
+            "ElasticsearchCheck" => ServiceDefinition::Elasticsearch {
+                environment: check_infos_from!(
+                    environments,
+                    ElasticsearchCheckInfo { index, doc_type }
+                ),
+            },
+            "FTPCheck" => ServiceDefinition::Ftp {
+                environment: check_infos_from!(
+                    environments,
+                    FtpCheckInfo {
+                        remotefilepath,
+                        filecontents
+                    }
+                ),
+            },
+            "HTTPCheck" => ServiceDefinition::Http {
+                environment: check_infos_from!(
+                    environments,
+                    HttpCheckInfo {
+                        useragent,
+                        vhost,
+                        uri
+                    }
+                ),
+            },
+            "HTTPSCheck" => ServiceDefinition::Https {
+                environment: check_infos_from!(
+                    environments,
+                    HttpCheckInfo {
+                        useragent,
+                        vhost,
+                        uri
+                    }
+                ),
+            },
+            "ICMPCheck" => ServiceDefinition::Icmp { environment: None },
+            "IMAPCheck" => ServiceDefinition::Imap {
+                environment: check_infos_from!(environments, ImapCheckInfo { domain }),
+            },
+            "IMAPSCheck" => ServiceDefinition::Imaps {
+                environment: check_infos_from!(environments, ImapCheckInfo { domain }),
+            },
+            "LDAPCheck" => ServiceDefinition::Ldap {
+                environment: check_infos_from!(environments, LdapCheckInfo { domain, base_dn }),
+            },
+            "MSSQLCheck" => ServiceDefinition::Mssql {
+                environment: check_infos_from!(environments, SqlCheckInfo { database, command }),
+            },
+            "MYSQLCheck" => ServiceDefinition::Mysql {
+                environment: check_infos_from!(environments, SqlCheckInfo { database, command }),
+            },
+            "NFSCheck" => ServiceDefinition::Nfs {
+                environment: check_infos_from!(
+                    environments,
+                    NfsCheckInfo {
+                        remotefilepath,
+                        filecontents
+                    }
+                ),
+            },
+            "POP3Check" => ServiceDefinition::Pop3 {
+                environment: check_infos_from!(environments, PopCheckInfo { domain }),
+            },
+            "POP3SCheck" => ServiceDefinition::Pop3s {
+                environment: check_infos_from!(environments, PopCheckInfo { domain }),
+            },
+            "PostgreSQLCheck" => ServiceDefinition::PostgreSql {
+                environment: check_infos_from!(environments, SqlCheckInfo { database, command }),
+            },
+            "RDPCheck" => ServiceDefinition::Rdp { environment: None },
+            "SMBCheck" => ServiceDefinition::Smb {
+                environment: check_infos_from!(
+                    environments,
+                    SmbCheckInfo {
+                        remote_name,
+                        share,
+                        file,
+                        hash
+                    }
+                ),
+            },
+            "SMTPCheck" => ServiceDefinition::Smtp {
+                environment: check_infos_from!(
+                    environments,
+                    SmtpCheckInfo {
+                        touser,
+                        subject,
+                        body
+                    }
+                ),
+            },
+            "SMTPSCheck" => ServiceDefinition::Smtps {
+                environment: check_infos_from!(
+                    environments,
+                    SmtpCheckInfo {
+                        touser,
+                        subject,
+                        body
+                    }
+                ),
+            },
+            "SSHCheck" => ServiceDefinition::Ssh {
+                environment: check_infos_from!(environments, RemoteCommandCheckInfo { commands }),
+            },
+            "VNCCheck" => ServiceDefinition::Vnc { environment: None },
+            "WinRMCheck" => ServiceDefinition::WinRm {
+                environment: check_infos_from!(environments, RemoteCommandCheckInfo { commands }),
+            },
+            "WordpressCheck" => ServiceDefinition::Wordpress {
+                environment: check_infos_from!(
+                    environments,
+                    HttpCheckInfo {
+                        useragent,
+                        vhost,
+                        uri
+                    }
+                ),
+            },
+            _ => return None,
+        };
+
+        // Matchers left at the default are kept unset, so they follow the default
+        let default = definition.default_matching_content();
+        if let ServiceDefinition::Icmp { environment }
+        | ServiceDefinition::Rdp { environment }
+        | ServiceDefinition::Vnc { environment } = &mut definition
+        {
+            *environment = environments
+                .first()
+                .map(|environment| environment.matching_content.clone())
+                .filter(|matcher| Some(&**matcher) != default);
+        }
+
+        Some(definition)
+    }
+
+    /// The conventional `matching_content` for a check of this type, used for the
+    /// synthesized environments above and to prefill new environment rows
+    pub fn default_matching_content(&self) -> Option<&'static str> {
+        match self {
+            ServiceDefinition::Icmp { .. } => Some("1 packets transmitted, 1 received"),
+            ServiceDefinition::Rdp { .. } => Some("SUCCESS$"),
+            ServiceDefinition::Vnc { .. } => Some("ACCOUNT FOUND"),
+            ServiceDefinition::Http { .. }
+            | ServiceDefinition::Https { .. }
+            | ServiceDefinition::Wordpress { .. } => Some("200 OK"),
+            ServiceDefinition::Ftp { .. }
+            | ServiceDefinition::Imap { .. }
+            | ServiceDefinition::Imaps { .. }
+            | ServiceDefinition::Ldap { .. }
+            | ServiceDefinition::Nfs { .. }
+            | ServiceDefinition::Pop3 { .. }
+            | ServiceDefinition::Pop3s { .. }
+            | ServiceDefinition::Smb { .. }
+            | ServiceDefinition::Smtp { .. }
+            | ServiceDefinition::Smtps { .. }
+            | ServiceDefinition::Ssh { .. }
+            | ServiceDefinition::WinRm { .. } => Some("SUCCESS"),
+            ServiceDefinition::Dns { .. }
+            | ServiceDefinition::Docker { .. }
+            | ServiceDefinition::Elasticsearch { .. }
+            | ServiceDefinition::Mssql { .. }
+            | ServiceDefinition::Mysql { .. }
+            | ServiceDefinition::PostgreSql { .. } => None,
+        }
+    }
+
+    /// Whether the check only works by logging in, so it cannot score anything
+    /// without accounts
+    pub fn requires_accounts(&self) -> bool {
+        matches!(
+            self,
+            ServiceDefinition::Rdp { .. } | ServiceDefinition::Vnc { .. }
+        )
+    }
+
+    /// Whether the check doesn't connect to a port at all, so the port of the service
+    /// has to be left at 0
+    pub fn is_portless(&self) -> bool {
+        matches!(self, ServiceDefinition::Icmp { .. })
+    }
+
+    /// The kind of service being checked, used to color code services so they can be
+    /// told apart at a glance
+    pub fn category(&self) -> ServiceCategory {
+        match self {
+            ServiceDefinition::Http { .. }
+            | ServiceDefinition::Https { .. }
+            | ServiceDefinition::Wordpress { .. } => ServiceCategory::Web,
+            ServiceDefinition::Imap { .. }
+            | ServiceDefinition::Imaps { .. }
+            | ServiceDefinition::Pop3 { .. }
+            | ServiceDefinition::Pop3s { .. }
+            | ServiceDefinition::Smtp { .. }
+            | ServiceDefinition::Smtps { .. } => ServiceCategory::Mail,
+            ServiceDefinition::Elasticsearch { .. }
+            | ServiceDefinition::Mssql { .. }
+            | ServiceDefinition::Mysql { .. }
+            | ServiceDefinition::PostgreSql { .. } => ServiceCategory::Database,
+            ServiceDefinition::Ftp { .. }
+            | ServiceDefinition::Nfs { .. }
+            | ServiceDefinition::Smb { .. } => ServiceCategory::File,
+            ServiceDefinition::Rdp { .. }
+            | ServiceDefinition::Ssh { .. }
+            | ServiceDefinition::Vnc { .. }
+            | ServiceDefinition::WinRm { .. } => ServiceCategory::RemoteAccess,
+            ServiceDefinition::Dns { .. }
+            | ServiceDefinition::Docker { .. }
+            | ServiceDefinition::Icmp { .. }
+            | ServiceDefinition::Ldap { .. } => ServiceCategory::Infrastructure,
         }
     }
 
@@ -679,12 +1442,131 @@ impl ServiceDefinition {
     }
 }
 
-#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum ServiceCategory {
+    Web,
+    Mail,
+    Database,
+    File,
+    RemoteAccess,
+    Infrastructure,
+}
+
+impl ServiceCategory {
+    pub const ALL: &'static [ServiceCategory] = &[
+        ServiceCategory::Web,
+        ServiceCategory::Mail,
+        ServiceCategory::Database,
+        ServiceCategory::File,
+        ServiceCategory::RemoteAccess,
+        ServiceCategory::Infrastructure,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ServiceCategory::Web => "Web",
+            ServiceCategory::Mail => "Mail",
+            ServiceCategory::Database => "Database",
+            ServiceCategory::File => "File",
+            ServiceCategory::RemoteAccess => "Remote access",
+            ServiceCategory::Infrastructure => "Infrastructure",
+        }
+    }
+
+    /// The CSS class that gives services of this category their color
+    pub fn class(self) -> &'static str {
+        match self {
+            ServiceCategory::Web => "category-web",
+            ServiceCategory::Mail => "category-mail",
+            ServiceCategory::Database => "category-database",
+            ServiceCategory::File => "category-file",
+            ServiceCategory::RemoteAccess => "category-remote-access",
+            ServiceCategory::Infrastructure => "category-infrastructure",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone, Default)]
 #[serde(tag = "scheme")]
 pub enum IpGeneratorScheme {
+    #[default]
     OneTeam,
     ReplaceXWithId,
-    ReplaceXWithIdTimesMultiplierPlusOffset { multiplier: u8 },
+    ReplaceXWithIdTimesMultiplierPlusOffset {
+        multiplier: u8,
+    },
+    /// Every machine of every team is given the next host of a network, team by team
+    /// and then machine by machine, so no templates are needed
+    Cidr {
+        base: String,
+        prefix: u8,
+    },
+}
+
+/// A machine's IP template that doesn't suit the scheme, such as one left behind after
+/// switching schemes, along with a template that would, if one can be worked out
+#[derive(Debug)]
+pub struct TemplateMismatch {
+    pub error: ConversionError,
+    pub fixed: Option<String>,
+}
+
+impl IpGeneratorScheme {
+    /// Whether machines need an IP template of their own, rather than being handed an
+    /// address by the scheme
+    pub fn uses_templates(&self) -> bool {
+        !matches!(self, IpGeneratorScheme::Cidr { .. })
+    }
+
+    /// Checks an IP template against the scheme, using the ID of the first blue team
+    /// to guess which part of an address should become or replace the X
+    pub fn template_mismatch(
+        &self,
+        machine: &str,
+        template: &str,
+        team_id: u8,
+    ) -> Option<TemplateMismatch> {
+        let template = template.trim();
+        let has_x = template.chars().any(|c| c == 'x' || c == 'X');
+
+        if template.is_empty() {
+            return None;
+        }
+
+        match self {
+            IpGeneratorScheme::OneTeam if has_x => Some(TemplateMismatch {
+                error: ConversionError::XInManualIP(machine.to_owned()),
+                fixed: Some(template.replace(['x', 'X'], &team_id.to_string())),
+            }),
+            IpGeneratorScheme::ReplaceXWithId
+            | IpGeneratorScheme::ReplaceXWithIdTimesMultiplierPlusOffset { .. }
+                if !has_x =>
+            {
+                let mut octets = template.split('.').collect::<Vec<_>>();
+                let fixed = (octets.len() == 4
+                    && octets.iter().all(|octet| octet.parse::<u8>().is_ok()))
+                .then(|| {
+                    // The part that matches the team is most likely the one that was
+                    // filled in by hand; otherwise the host part is the usual choice
+                    let id = team_id.to_string();
+                    let i = match self {
+                        IpGeneratorScheme::ReplaceXWithId => {
+                            octets.iter().position(|octet| *octet == id).unwrap_or(3)
+                        }
+                        _ => 3,
+                    };
+                    octets[i] = "X";
+                    octets.join(".")
+                });
+
+                Some(TemplateMismatch {
+                    error: ConversionError::NoXInTemplateIP(machine.to_owned()),
+                    fixed,
+                })
+            }
+            _ => None,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
@@ -701,39 +1583,477 @@ pub struct BlueTeamEditor {
     pub users: Vec<User>,
 }
 
-#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone, Default)]
 pub struct MachineEditor {
     pub name: String,
     pub services: Vec<ServiceEditor>,
     pub ip_template: String,
     pub ip_offset: Option<u8>,
+    /// When set and no template is given, the host part appended to the configuration's
+    /// base template to form this machine's template
+    #[serde(default)]
+    pub base_host: Option<String>,
+    /// Purely for organizing the machine list; not part of the generated configuration
+    #[serde(default)]
+    pub group: Option<String>,
+    /// When set, the DNS name services are checked at instead of an address, with
+    /// `{id}` and any part between dots that is just X replaced by the ID of the team
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname_template: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
-pub struct ConfigurationEditor {
-    pub red_white_teams: Vec<RedWhiteTeamEditor>,
-    pub blue_teams: Vec<BlueTeamEditor>,
-    pub machines: Vec<MachineEditor>,
-    pub ip_generator: IpGeneratorScheme,
+/// Whether a name could be looked up in DNS: dot separated labels of letters, digits
+/// and hyphens, none starting or ending with a hyphen, and not an address in disguise
+fn is_plausible_hostname(hostname: &str) -> bool {
+    let labels = hostname
+        .trim_end_matches('.')
+        .split('.')
+        .collect::<Vec<_>>();
+
+    hostname.len() <= 253
+        && labels.iter().all(|label| {
+            (1..=63).contains(&label.len())
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+        })
+        && labels
+            .last()
+            .is_some_and(|label| !label.chars().all(|c| c.is_ascii_digit()))
 }
 
-type ConversionState = HashMap<String, String>;
+impl MachineEditor {
+    /// Whether the services of this machine are checked by name rather than address
+    pub fn uses_hostname(&self) -> bool {
+        self.hostname_template.is_some()
+    }
 
-fn convert_id_to_ip(
-    used_ips: &mut ConversionState,
-    machine_name: &str,
-    ip_template: &str,
-    ip_offset: Option<u8>,
-    generator: &IpGeneratorScheme,
-    id: u8,
-) -> Result<String, ConversionError> {
+    /// The name this machine is checked at for a team, if it's checked by name
+    pub fn hostname_for(&self, team_id: u8) -> Option<String> {
+        let id = team_id.to_string();
+
+        // An X elsewhere is part of a name, as in MX or EXCHANGE
+        self.hostname_template.as_ref().map(|template| {
+            template
+                .split('.')
+                .map(|label| match label {
+                    "X" => id.clone(),
+                    _ => label.replace("{id}", &id),
+                })
+                .collect::<Vec<_>>()
+                .join(".")
+        })
+    }
+
+    /// Checks that the hostname template gives every team a valid name of its own
+    fn check_hostname(&self, team_count: usize) -> Result<(), ConversionError> {
+        let Some(template) = &self.hostname_template else {
+            return Ok(());
+        };
+
+        let per_team = template.contains("{id}") || template.split('.').any(|label| label == "X");
+        if team_count > 1 && !per_team {
+            return Err(ConversionError::HostnameNotPerTeam(self.name.clone()));
+        }
+
+        // Team IDs are only ever digits, so any one of them shows whether the
+        // template gives valid names
+        let hostname = self.hostname_for(1).unwrap_or_default();
+        if is_plausible_hostname(&hostname) {
+            Ok(())
+        } else {
+            Err(ConversionError::InvalidHostname(
+                self.name.clone(),
+                template.clone(),
+            ))
+        }
+    }
+
+    /// Adds copies of the services this machine doesn't already have a service of the
+    /// same name for, returning how many were added; unnamed services are left out, as
+    /// they can't be told apart
+    pub fn add_missing_services(&mut self, services: &[ServiceEditor]) -> usize {
+        let mut added = 0;
+
+        for service in services {
+            if service.name.is_empty()
+                || self
+                    .services
+                    .iter()
+                    .any(|existing| existing.name == service.name)
+            {
+                continue;
+            }
+
+            self.services.push(service.clone());
+            added += 1;
+        }
+
+        added
+    }
+}
+
+/// The release of the scoring engine a configuration is written for, for the few
+/// places where the schemas they accept differ
+#[derive(Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd, Debug, Clone, Copy, Default)]
+pub enum EngineVersion {
+    /// Releases whose Elasticsearch check still requires a document type
+    #[default]
+    Legacy,
+    /// Releases targeting Elasticsearch 7 and later, where document types were removed
+    Current,
+}
+
+impl EngineVersion {
+    pub const ALL: [EngineVersion; 2] = [EngineVersion::Legacy, EngineVersion::Current];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EngineVersion::Legacy => "Legacy (Elasticsearch document types)",
+            EngineVersion::Current => "Current (no Elasticsearch document types)",
+        }
+    }
+
+    pub fn uses_document_types(&self) -> bool {
+        matches!(self, EngineVersion::Legacy)
+    }
+
+    /// Whether checks can be told to pass when their result is absent
+    pub fn supports_negation(&self) -> bool {
+        matches!(self, EngineVersion::Current)
+    }
+
+    /// The commands that load a configuration into this release of the engine, where
+    /// `{path}` is the file the configuration is saved as and `{yaml}` its contents
+    pub fn default_cli_command_template(&self) -> &'static str {
+        match self {
+            EngineVersion::Legacy => {
+                "cat > {path} <<'EOF'\n{yaml}\nEOF\n./bin/setup --config {path}\n"
+            }
+            EngineVersion::Current => {
+                "cat > {path} <<'EOF'\n{yaml}\nEOF\ndocker compose run --rm -v \"$(pwd)/{path}:/app/bin/competition.yaml\" bootstrap\n"
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone, Default)]
+pub struct ConfigurationEditor {
+    pub red_white_teams: Vec<RedWhiteTeamEditor>,
+    pub blue_teams: Vec<BlueTeamEditor>,
+    pub machines: Vec<MachineEditor>,
+    pub ip_generator: IpGeneratorScheme,
+    #[serde(default)]
+    pub engine_version: EngineVersion,
+    #[serde(default)]
+    pub base_ip_template: String,
+    /// Replacements for the default CLI command template of an engine version
+    #[serde(default)]
+    pub cli_command_templates: BTreeMap<EngineVersion, String>,
+    /// Notes on the intended network and scoring for whoever edits the configuration;
+    /// never part of the generated configuration
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub flags: Vec<Flag>,
+}
+
+impl ConfigurationEditor {
+    /// Makes a change to every service using the named check, apart from those with
+    /// the field locked; returns how many services were changed and how many were
+    /// left alone
+    pub fn bulk_change_services(
+        &mut self,
+        check_name: &str,
+        change: BulkServiceChange,
+    ) -> (usize, usize) {
+        let mut changed = 0;
+        let mut skipped = 0;
+
+        for service in self
+            .machines
+            .iter_mut()
+            .flat_map(|machine| &mut machine.services)
+            .filter(|service| service.definition.check_name() == check_name)
+        {
+            let set = match change {
+                BulkServiceChange::Port(port) => service.bulk_set_port(port),
+                BulkServiceChange::Points(points) => service.bulk_set_points(points),
+            };
+
+            if set {
+                changed += 1;
+            } else {
+                skipped += 1;
+            }
+        }
+
+        (changed, skipped)
+    }
+
+    pub fn cli_command_template(&self) -> &str {
+        self.cli_command_templates
+            .get(&self.engine_version)
+            .map(String::as_str)
+            .unwrap_or_else(|| self.engine_version.default_cli_command_template())
+    }
+
+    /// The IP template a machine resolves to: its own template if it has one, otherwise
+    /// the base template followed by its host part
+    pub fn ip_template_for(&self, machine: &MachineEditor) -> String {
+        match &machine.base_host {
+            Some(host) if machine.ip_template.is_empty() => {
+                format!("{}{host}", self.base_ip_template)
+            }
+            _ => machine.ip_template.clone(),
+        }
+    }
+
+    /// Where a team's machine falls among the machines of every team, which decides
+    /// the address schemes that hand out hosts in order give it
+    pub fn host_slot(&self, team_id: u8, machine: usize) -> usize {
+        let team = self
+            .blue_teams
+            .iter()
+            .position(|team| team.id == team_id)
+            .unwrap_or_default();
+
+        team * self.machines.len() + machine
+    }
+
+    /// Indices of the machines that still need an IP offset, which only matters when
+    /// addresses are generated with a multiplier
+    pub fn machines_missing_offsets(&self) -> Vec<usize> {
+        if !matches!(
+            self.ip_generator,
+            IpGeneratorScheme::ReplaceXWithIdTimesMultiplierPlusOffset { .. }
+        ) {
+            return vec![];
+        }
+
+        self.machines
+            .iter()
+            .enumerate()
+            .filter(|(_, machine)| machine.ip_offset.is_none() && !machine.uses_hostname())
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Indices of the machines sharing each IP offset that more than one machine
+    /// uses, which only matters when addresses are generated with a multiplier
+    pub fn duplicate_offsets(&self) -> Vec<Vec<usize>> {
+        if !matches!(
+            self.ip_generator,
+            IpGeneratorScheme::ReplaceXWithIdTimesMultiplierPlusOffset { .. }
+        ) {
+            return vec![];
+        }
+
+        let mut machines_by_offset = BTreeMap::<u8, Vec<usize>>::new();
+        for (i, machine) in self.machines.iter().enumerate() {
+            if machine.uses_hostname() {
+                continue;
+            }
+
+            if let Some(offset) = machine.ip_offset {
+                machines_by_offset.entry(offset).or_default().push(i);
+            }
+        }
+
+        machines_by_offset
+            .into_values()
+            .filter(|machines| machines.len() > 1)
+            .collect()
+    }
+
+    /// Moves every machine but the first of each group sharing an IP offset to the
+    /// lowest offset below the multiplier that no machine uses, leaving machines whose
+    /// offsets are already unique alone. Returns the indices of the machines that were
+    /// moved; once the multiplier runs out of free offsets the rest are left as they are
+    pub fn renumber_duplicate_offsets(&mut self) -> Vec<usize> {
+        let IpGeneratorScheme::ReplaceXWithIdTimesMultiplierPlusOffset { multiplier } =
+            self.ip_generator
+        else {
+            return vec![];
+        };
+
+        let mut used = self
+            .machines
+            .iter()
+            .filter_map(|machine| machine.ip_offset)
+            .collect::<BTreeSet<_>>();
+        let mut moved = vec![];
+
+        for machines in self.duplicate_offsets() {
+            for i in machines.into_iter().skip(1) {
+                let Some(offset) = (0..multiplier).find(|offset| !used.contains(offset)) else {
+                    return moved;
+                };
+
+                used.insert(offset);
+                self.machines[i].ip_offset = Some(offset);
+                moved.push(i);
+            }
+        }
+
+        moved
+    }
+
+    /// Appends the teams and machines of another configuration to this one. Names that
+    /// are already taken get a numeric suffix and blue team IDs that are taken are
+    /// moved to the lowest free ID, with the services' excluded teams following them;
+    /// every such change is described in the result
+    pub fn merge(&mut self, other: &ConfigurationEditor) -> Vec<String> {
+        fn free_name(taken: &HashSet<String>, name: &str) -> String {
+            (2..)
+                .map(|n| format!("{name}-{n}"))
+                .find(|candidate| !taken.contains(candidate))
+                .unwrap()
+        }
+
+        let mut changes = vec![];
+
+        // Team names have to be unique across every color, not just within one
+        let mut taken = self
+            .red_white_teams
+            .iter()
+            .map(|team| team.name.clone())
+            .chain(self.blue_teams.iter().map(|team| team.name.clone()))
+            .collect::<HashSet<_>>();
+        for team in &other.red_white_teams {
+            let mut team = team.clone();
+            if taken.contains(&team.name) {
+                let name = free_name(&taken, &team.name);
+                changes.push(format!("renamed team '{}' to '{name}'", team.name));
+                team.name = name;
+            }
+            taken.insert(team.name.clone());
+            self.red_white_teams.push(team);
+        }
+
+        let mut taken_ids = self
+            .blue_teams
+            .iter()
+            .map(|team| team.id)
+            .collect::<HashSet<_>>();
+        // The ID each of the other configuration's blue teams ended up with
+        let mut moved_ids = HashMap::new();
+        for original in &other.blue_teams {
+            let mut team = original.clone();
+            if taken_ids.contains(&team.id) {
+                let Some(id) = (1..=u8::MAX).find(|id| !taken_ids.contains(id)) else {
+                    changes.push(format!(
+                        "skipped blue team '{}' as there are no free team IDs left",
+                        team.name
+                    ));
+                    continue;
+                };
+                changes.push(format!(
+                    "moved blue team '{}' from ID {} to {id}",
+                    team.name, team.id
+                ));
+                team.id = id;
+            }
+            if taken.contains(&team.name) {
+                let name = free_name(&taken, &team.name);
+                changes.push(format!("renamed blue team '{}' to '{name}'", team.name));
+                team.name = name;
+            }
+            moved_ids.insert(original.id, team.id);
+            taken_ids.insert(team.id);
+            taken.insert(team.name.clone());
+            self.blue_teams.push(team);
+        }
+
+        let mut taken = self
+            .machines
+            .iter()
+            .map(|machine| machine.name.clone())
+            .collect::<HashSet<_>>();
+        for machine in &other.machines {
+            let mut machine = machine.clone();
+
+            // Hosts on the other configuration's base template keep the address they had
+            if machine.base_host.is_some() && machine.ip_template.is_empty() {
+                machine.ip_template = other.ip_template_for(&machine);
+                machine.base_host = None;
+            }
+
+            // Teams that were skipped, or that the other configuration doesn't have,
+            // would otherwise stand for one of this configuration's teams
+            for service in &mut machine.services {
+                service.excluded_teams = service
+                    .excluded_teams
+                    .iter()
+                    .filter_map(|id| moved_ids.get(id).copied())
+                    .collect();
+            }
+
+            if taken.contains(&machine.name) {
+                let name = free_name(&taken, &machine.name);
+                changes.push(format!("renamed machine '{}' to '{name}'", machine.name));
+                machine.name = name;
+            }
+            taken.insert(machine.name.clone());
+            self.machines.push(machine);
+        }
+
+        changes
+    }
+}
+
+type ConversionState = HashMap<String, String>;
+
+/// The first host of a network and how many hosts it has room for, leaving out the
+/// network and broadcast addresses
+pub fn cidr_hosts(base: &str, prefix: u8) -> Result<(Ipv4Addr, u32), ConversionError> {
+    let invalid = || ConversionError::InvalidCidr(format!("{base}/{prefix}"));
+
+    let base = base.trim().parse::<Ipv4Addr>().map_err(|_| invalid())?;
+    if prefix > 30 {
+        return Err(invalid());
+    }
+
+    let mask = u32::MAX.checked_shl(32 - u32::from(prefix)).unwrap_or(0);
+    let network = u32::from(base) & mask;
+
+    Ok((Ipv4Addr::from(network + 1), !mask - 1))
+}
+
+/// Checks that an address, once any X in its template has been replaced, is one the
+/// scoring engine can reach
+pub fn check_ip_address(machine_name: &str, ip: &str) -> Result<(), ConversionError> {
+    ip.parse::<Ipv4Addr>()
+        .map(|_| ())
+        .map_err(|_| ConversionError::InvalidIpAddress(machine_name.to_owned(), ip.to_owned()))
+}
+
+/// `slot` is where the machine falls among the machines of every team, for schemes
+/// that hand out addresses in order
+fn convert_id_to_ip(
+    used_ips: &mut ConversionState,
+    machine_name: &str,
+    ip_template: &str,
+    ip_offset: Option<u8>,
+    generator: &IpGeneratorScheme,
+    id: u8,
+    slot: usize,
+) -> Result<String, ConversionError> {
     use IpGeneratorScheme::*;
 
+    if generator.uses_templates() && ip_template.trim().is_empty() {
+        return Err(ConversionError::EmptyHost(machine_name.to_owned()));
+    }
+
     match generator {
         OneTeam => {
             if ip_template.chars().any(|c| c == 'x' || c == 'X') {
                 return Err(ConversionError::XInManualIP(machine_name.to_owned()));
-            } else if let Some(other_machine) = used_ips.get(ip_template) {
+            }
+
+            check_ip_address(machine_name, ip_template)?;
+
+            if let Some(other_machine) = used_ips.get(ip_template) {
                 if other_machine != machine_name {
                     return Err(ConversionError::DuplicateIPs(
                         ip_template.to_owned(),
@@ -749,12 +2069,14 @@ fn convert_id_to_ip(
         }
         ReplaceXWithId => {
             if !ip_template.chars().any(|c| c == 'x' || c == 'X') {
-                return Err(ConversionError::NoXInTemplateIP(machine_name.to_owned()));
+                Err(ConversionError::NoXInTemplateIP(machine_name.to_owned()))
             } else {
                 let ip = ip_template
                     .replace('X', &id.to_string())
                     .replace('x', &id.to_string());
 
+                check_ip_address(machine_name, &ip)?;
+
                 if let Some(other_machine) = used_ips.get(&ip) {
                     if other_machine != machine_name {
                         return Err(ConversionError::DuplicateIPs(
@@ -773,18 +2095,189 @@ fn convert_id_to_ip(
             let Some(ip_offset) = ip_offset else {
                 return Err(ConversionError::OffsetNotSpecified(machine_name.to_owned()));
             };
-            let ip = multiplier * id + ip_offset;
-            if ip_template.chars().any(|c| c == 'x' || c == 'X') {
-                Ok(ip_template
-                    .replace('X', &ip.to_string())
-                    .replace('x', &ip.to_string()))
-            } else {
-                Err(ConversionError::NoXInTemplateIP(machine_name.to_owned()))
+            if !ip_template.chars().any(|c| c == 'x' || c == 'X') {
+                return Err(ConversionError::NoXInTemplateIP(machine_name.to_owned()));
             }
+
+            let part = u32::from(*multiplier) * u32::from(id) + u32::from(ip_offset);
+            let ip = ip_template
+                .replace('X', &part.to_string())
+                .replace('x', &part.to_string());
+
+            if part > u32::from(u8::MAX) {
+                return Err(ConversionError::InvalidIpAddress(
+                    machine_name.to_owned(),
+                    ip,
+                ));
+            }
+
+            check_ip_address(machine_name, &ip)?;
+            Ok(ip)
+        }
+        Cidr { base, prefix } => {
+            let (first, hosts) = cidr_hosts(base, *prefix)?;
+            let slot = u32::try_from(slot)
+                .ok()
+                .filter(|slot| *slot < hosts)
+                .ok_or_else(|| {
+                    ConversionError::CidrTooSmall(format!("{base}/{prefix}"), slot + 1, hosts)
+                })?;
+
+            Ok(Ipv4Addr::from(u32::from(first) + slot).to_string())
         }
     }
 }
 
+/// Where the services of a machine are checked for a team: the machine's hostname if
+/// it has one, otherwise its address
+fn machine_host(
+    conversion_state: &mut ConversionState,
+    config: &ConfigurationEditor,
+    machine: &MachineEditor,
+    i: usize,
+    team_id: u8,
+) -> Result<String, ConversionError> {
+    if let Some(hostname) = machine.hostname_for(team_id) {
+        return Ok(hostname);
+    }
+
+    convert_id_to_ip(
+        conversion_state,
+        &machine.name,
+        &config.ip_template_for(machine),
+        machine.ip_offset,
+        &config.ip_generator,
+        team_id,
+        config.host_slot(team_id, i),
+    )
+}
+
+fn services_generator(
+    conversion_state: &mut ConversionState,
+    config: &ConfigurationEditor,
+    team: &BlueTeamEditor,
+) -> Result<Vec<ServiceConfig>, ConversionError> {
+    Ok(config
+        .machines
+        .iter()
+        .enumerate()
+        .map(|(i, machine)| {
+            {
+                let mut service_names: HashSet<&str> = HashSet::new();
+
+                for service in &machine.services {
+                    if service.name.is_empty() {
+                        return Err(ConversionError::MachineHasEmptyService(
+                            machine.name.clone(),
+                        ));
+                    }
+
+                    if service_names.contains(&*service.name) {
+                        return Err(ConversionError::DuplicateServiceName(
+                            machine.name.clone(),
+                            service.name.clone(),
+                        ));
+                    }
+
+                    service_names.insert(&*service.name);
+                }
+            }
+
+            machine
+                .services
+                .iter()
+                .filter(|service| !service.excluded_teams.contains(&team.id))
+                .map(|service| -> Result<ServiceConfig, ConversionError> {
+                    if service.definition.requires_accounts()
+                        && service.accounts.as_ref().is_none_or(Vec::is_empty)
+                    {
+                        return Err(ConversionError::ServiceNeedsAccount(
+                            machine.name.clone(),
+                            service.name.clone(),
+                        ));
+                    }
+
+                    for (field, duration) in
+                        [("interval", service.interval), ("timeout", service.timeout)]
+                    {
+                        if duration == Some(0) {
+                            return Err(ConversionError::InvalidDuration(
+                                machine.name.clone(),
+                                service.name.clone(),
+                                field,
+                            ));
+                        }
+                    }
+
+                    let ip_template = config.ip_template_for(machine);
+                    if config.ip_generator.uses_templates()
+                        && !machine.uses_hostname()
+                        && ip_template.trim().is_empty()
+                    {
+                        return Err(ConversionError::ServiceHasNoHost(
+                            machine.name.clone(),
+                            service.name.clone(),
+                        ));
+                    }
+
+                    let mut service_config = ServiceConfig {
+                        name: service.config_name(&machine.name),
+                        check_name: service.definition.check_name().to_string(),
+                        host: machine_host(conversion_state, config, machine, i, team.id)?,
+                        port: service.port,
+                        points: service.scored_points(),
+                        interval: service.interval,
+                        timeout: service.timeout,
+                        accounts: service
+                            .accounts
+                            .clone()
+                            .map(|users| {
+                                users
+                                    .into_iter()
+                                    .map(|user| {
+                                        user.validate(format!(
+                                            "service {}-{}",
+                                            machine.name, service.name
+                                        ))
+                                    })
+                                    .collect::<Result<Vec<_>, ConversionError>>()
+                            })
+                            .transpose()?,
+                        environments: service.definition.environments(
+                            &machine.name,
+                            &service.name,
+                            config.engine_version,
+                        )?,
+                    };
+
+                    if service.inline_accounts {
+                        let accounts = service_config.accounts.take();
+                        for environment in &mut service_config.environments {
+                            environment.accounts = accounts.clone();
+                        }
+                    }
+
+                    Ok(service_config)
+                })
+                .collect::<Result<Vec<_>, ConversionError>>()
+        })
+        .collect::<Result<Vec<_>, ConversionError>>()?
+        .concat())
+}
+
+/// How many services a blue team will be scored on, worked out by generating just
+/// that team's services
+pub fn scored_service_count(
+    config: &ConfigurationEditor,
+    team: &BlueTeamEditor,
+) -> Result<usize, ConversionError> {
+    services_generator(&mut ConversionState::new(), config, team).map(|services| services.len())
+}
+
+/// Converts the editor's view of a configuration into what the scoring engine reads.
+/// The same configuration always gives the same result, or the same first error, so
+/// exports can be diffed and kept under version control; any collection that is walked
+/// to build the result or find a problem is ordered for that reason
 pub fn convert_editor_to_final(
     config: &ConfigurationEditor,
 ) -> Result<(FinalConfiguration, ConfigurationEditor), ConversionError> {
@@ -804,7 +2297,11 @@ pub fn convert_editor_to_final(
                     users: if team.users.is_empty() {
                         Err(ConversionError::TeamNeedsUser(team.name.clone()))
                     } else {
-                        Ok(team.users.clone())
+                        team.users
+                            .iter()
+                            .cloned()
+                            .map(|user| user.validate(format!("team {}", team.name)))
+                            .collect::<Result<Vec<_>, ConversionError>>()
                     }?,
                 }
             } else {
@@ -817,7 +2314,11 @@ pub fn convert_editor_to_final(
                     users: if team.users.is_empty() {
                         Err(ConversionError::TeamNeedsUser(team.name.clone()))
                     } else {
-                        Ok(team.users.clone())
+                        team.users
+                            .iter()
+                            .cloned()
+                            .map(|user| user.validate(format!("team {}", team.name)))
+                            .collect::<Result<Vec<_>, ConversionError>>()
                     }?,
                 }
             })
@@ -833,14 +2334,15 @@ pub fn convert_editor_to_final(
     if let IpGeneratorScheme::ReplaceXWithIdTimesMultiplierPlusOffset { multiplier: mult } =
         config.ip_generator
     {
-        let mcount = <usize as TryInto<u8>>::try_into(config.machines.len()).unwrap();
-        if mult < mcount {
+        let mcount = config.machines.len();
+        if (mult as usize) < mcount {
             return Err(ConversionError::MultNotBigEnough(mcount, mult));
         }
 
         let offsets = match config
             .machines
             .iter()
+            .filter(|m| !m.uses_hostname())
             .map(|m| {
                 m.ip_offset
                     .map(|off| (off, m.name.to_owned()))
@@ -852,7 +2354,7 @@ pub fn convert_editor_to_final(
             Err(m) => return Err(ConversionError::MissingOffset(m)),
         };
 
-        let mut offset_unique_detection = HashMap::<u8, Vec<String>>::new();
+        let mut offset_unique_detection = BTreeMap::<u8, Vec<String>>::new();
 
         for (off, mname) in offsets {
             match offset_unique_detection.get_mut(&off) {
@@ -872,6 +2374,19 @@ pub fn convert_editor_to_final(
         }
     }
 
+    if let IpGeneratorScheme::Cidr { base, prefix } = &config.ip_generator {
+        let (_, hosts) = cidr_hosts(base, *prefix)?;
+        let needed = config.machines.len() * config.blue_teams.len();
+
+        if needed > hosts as usize {
+            return Err(ConversionError::CidrTooSmall(
+                format!("{base}/{prefix}"),
+                needed,
+                hosts,
+            ));
+        }
+    }
+
     let mut conversion_state = ConversionState::new();
 
     {
@@ -887,89 +2402,66 @@ pub fn convert_editor_to_final(
             }
 
             machine_names.insert(&*machine.name);
+
+            machine.check_hostname(config.blue_teams.len())?;
         }
     }
 
-    fn services_generator(
-        conversion_state: &mut ConversionState,
-        config: &ConfigurationEditor,
-        team: &BlueTeamEditor,
-    ) -> Result<Vec<ServiceConfig>, ConversionError> {
-        Ok(config
-            .machines
-            .iter()
-            .map(|machine| -> Result<Vec<ServiceConfig>, ConversionError> {
-                {
-                    let mut service_names: HashSet<&str> = HashSet::new();
+    // Addresses are otherwise only compared while each blue team is converted, which
+    // never happens for a configuration without blue teams yet
+    if let IpGeneratorScheme::OneTeam = config.ip_generator {
+        let mut used_ips: HashMap<String, &str> = HashMap::new();
 
-                    for service in &machine.services {
-                        if service.name.is_empty() {
-                            return Err(ConversionError::MachineHasEmptyService(
-                                machine.name.clone(),
-                            ));
-                        }
+        for machine in config.machines.iter().filter(|m| !m.uses_hostname()) {
+            let ip = config.ip_template_for(machine);
 
-                        if service_names.contains(&*service.name) {
-                            return Err(ConversionError::DuplicateServiceName(
-                                machine.name.clone(),
-                                service.name.clone(),
-                            ));
-                        }
+            if ip.trim().is_empty() {
+                return Err(ConversionError::EmptyHost(machine.name.clone()));
+            }
 
-                        service_names.insert(&*service.name);
-                    }
-                }
+            if ip.chars().any(|c| c == 'x' || c == 'X') {
+                return Err(ConversionError::XInManualIP(machine.name.clone()));
+            }
 
-                Ok(machine
-                    .services
-                    .iter()
-                    .map(|service| -> Result<ServiceConfig, ConversionError> {
-                        Ok(ServiceConfig {
-                            name: format!(
-                                "{}-{}-{}",
-                                machine.name,
-                                service.definition.check_name(),
-                                service.name
-                            ),
-                            check_name: service.definition.check_name().to_string(),
-                            host: convert_id_to_ip(
-                                conversion_state,
-                                &machine.name,
-                                &machine.ip_template,
-                                machine.ip_offset,
-                                &config.ip_generator,
-                                team.id,
-                            )?,
-                            port: service.port,
-                            points: service.points,
-                            accounts: service
-                                .accounts
-                                .clone()
-                                .map(|users| {
-                                    users
-                                        .into_iter()
-                                        .map(|user| {
-                                            user.validate(format!(
-                                                "service {}-{}",
-                                                machine.name, service.name
-                                            ))
-                                        })
-                                        .collect::<Result<Vec<_>, ConversionError>>()
-                                })
-                                .transpose()?,
-                            environments: service
-                                .definition
-                                .environments(&machine.name, &service.name)?,
-                        })
-                    })
-                    .collect::<Result<Vec<_>, ConversionError>>()?)
-            })
-            .collect::<Result<Vec<_>, ConversionError>>()?
-            .concat())
+            check_ip_address(&machine.name, &ip)?;
+
+            if let Some(other_machine) = used_ips.get(&ip) {
+                return Err(ConversionError::DuplicateIPs(
+                    ip,
+                    machine.name.clone(),
+                    other_machine.to_string(),
+                ));
+            }
+
+            used_ips.insert(ip, &machine.name);
+        }
+    }
+
+    // Templates are otherwise only filled in as each blue team is converted, so one
+    // team's ID stands in for the rest to catch typos before there are any
+    let representative_id = config.blue_teams.first().map_or(1, |team| team.id);
+    for machine in config.machines.iter().filter(|m| !m.uses_hostname()) {
+        let replacement = match (&config.ip_generator, machine.ip_offset) {
+            (IpGeneratorScheme::ReplaceXWithId, _) => u32::from(representative_id),
+            (
+                IpGeneratorScheme::ReplaceXWithIdTimesMultiplierPlusOffset { multiplier },
+                Some(offset),
+            ) => u32::from(*multiplier) * u32::from(representative_id) + u32::from(offset),
+            _ => continue,
+        };
+
+        let template = config.ip_template_for(machine);
+        if template.chars().any(|c| c == 'x' || c == 'X') {
+            let ip = template
+                .replace('X', &replacement.to_string())
+                .replace('x', &replacement.to_string());
+
+            check_ip_address(&machine.name, &ip)?;
+        }
     }
 
     {
-        let mut blue_ids_map: HashMap<u8, Vec<&str>> = HashMap::new();
+        let mut blue_ids_map: BTreeMap<u8, Vec<&str>> = BTreeMap::new();
 
         for team in &config.blue_teams {
             let name_list_option = blue_ids_map.get_mut(&team.id);
@@ -991,6 +2483,23 @@ pub fn convert_editor_to_final(
         }
     }
 
+    {
+        let mut team_names: HashSet<&str> = HashSet::new();
+
+        let names = config
+            .red_white_teams
+            .iter()
+            .map(|team| &*team.name)
+            .chain(config.blue_teams.iter().map(|team| &*team.name));
+
+        // Teams without names are reported on their own as they're converted
+        for name in names.filter(|name| !name.is_empty()) {
+            if !team_names.insert(name) {
+                return Err(ConversionError::DuplicateTeamName(name.to_owned()));
+            }
+        }
+    }
+
     let blue = config
         .blue_teams
         .iter()
@@ -1004,67 +2513,1049 @@ pub fn convert_editor_to_final(
                 users: if team.users.is_empty() {
                     Err(ConversionError::TeamNeedsUser(team.name.clone()))
                 } else {
-                    Ok(team.users.clone())
+                    team.users
+                        .iter()
+                        .cloned()
+                        .map(|user| user.validate(format!("team {}", team.name)))
+                        .collect::<Result<Vec<_>, ConversionError>>()
                 }?,
                 services: services_generator(&mut conversion_state, &config, team)?,
             })
         })
         .collect::<Result<Vec<_>, ConversionError>>()?;
 
-    {
-        let mut names: HashMap<&str, Vec<&str>> = HashMap::new();
+    // Names are checked per machine while editing, but the engine needs the names
+    // composed from the machine, check and service to be unique across the team
+    for team in &blue {
+        let TeamConfig::Blue { name, services, .. } = team else {
+            continue;
+        };
 
-        macro_rules! add_names_branch {
-            ($name:expr, $users:expr) => {{
-                if let Some(names_list) = names.get_mut(&$name as &str) {
-                    names_list.extend($users.iter().map(|user| &*user.username));
-                } else {
-                    names.insert(
-                        &$name as &str,
-                        $users
-                            .iter()
-                            .map(|user| &*user.username)
-                            .collect::<Vec<_>>(),
-                    );
-                }
-            }};
-        }
-
-        macro_rules! add_names {
-            ($teams:expr) => {
-                for team in &$teams {
-                    match team {
-                        TeamConfig::Red {
-                            ref name, users, ..
-                        } => add_names_branch!(name, users),
-                        TeamConfig::White {
-                            ref name, users, ..
-                        } => add_names_branch!(name, users),
-                        TeamConfig::Blue {
-                            ref name, users, ..
-                        } => add_names_branch!(name, users),
-                    };
-                }
-            };
+        let mut service_names = HashSet::new();
+        for service in services {
+            if !service_names.insert(&*service.name) {
+                return Err(ConversionError::DuplicateGeneratedServiceName(
+                    name.clone(),
+                    service.name.clone(),
+                ));
+            }
         }
+    }
 
-        add_names!(blue);
-        add_names!(red_white);
+    {
+        // Teams are told apart by position rather than by name, so that teams sharing a
+        // name (reported above) can't hide or invent repeated usernames
+        let mut teams_by_username: BTreeMap<&str, Vec<(usize, &str)>> = BTreeMap::new();
+
+        for (i, team) in red_white.iter().chain(&blue).enumerate() {
+            let (TeamConfig::Red { name, users, .. }
+            | TeamConfig::White { name, users, .. }
+            | TeamConfig::Blue { name, users, .. }) = team;
+
+            for user in users {
+                let teams = teams_by_username.entry(&user.username).or_default();
+                if teams.last().map(|(last, _)| *last) != Some(i) {
+                    teams.push((i, name));
+                }
+            }
+        }
 
-        for (team_name, user_names) in names {
-            if user_names.len() > 1 {
+        for (username, teams) in teams_by_username {
+            if teams.len() > 1 {
                 return Err(ConversionError::DuplicateUserNameForTeams(
-                    team_name.to_string(),
-                    user_names.iter().map(ToString::to_string).collect(),
+                    username.to_owned(),
+                    teams.iter().map(|(_, name)| name.to_string()).collect(),
                 ));
             }
         }
     }
 
+    {
+        let mut flag_names: HashSet<&str> = HashSet::new();
+
+        for flag in &config.flags {
+            if flag.name.is_empty() {
+                return Err(ConversionError::FlagHasEmptyName);
+            }
+
+            if !flag_names.insert(&flag.name) {
+                return Err(ConversionError::DuplicateFlagName(flag.name.clone()));
+            }
+        }
+    }
+
     Ok((
         FinalConfiguration {
             teams: [red_white, blue].concat(),
+            flags: config.flags.clone(),
         },
         config,
     ))
 }
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Works out the IP template of a machine from the addresses each team's copy of it
+/// has: the octets that differ between teams become an X, and have to agree with each
+/// other. Returns the template along with the value the X takes for each team
+fn ip_template_from_hosts(
+    machine: &str,
+    hosts: &BTreeMap<usize, Ipv4Addr>,
+) -> Result<(String, BTreeMap<usize, u8>), ConversionError> {
+    let unrecoverable = || ConversionError::UnrecoverableHost(machine.to_owned());
+
+    let octets = hosts
+        .iter()
+        .map(|(team, ip)| (*team, ip.octets()))
+        .collect::<BTreeMap<_, _>>();
+    let first = octets.values().next().ok_or_else(unrecoverable)?;
+
+    let mut varying = (0..4)
+        .filter(|i| octets.values().any(|octet| octet[*i] != first[*i]))
+        .collect::<Vec<_>>();
+    if varying.is_empty() {
+        // Teams would all share the address; a machine only one team has could
+        // follow any pattern, and the host part is the usual one to vary
+        if octets.len() > 1 {
+            return Err(unrecoverable());
+        }
+        varying.push(3);
+    }
+
+    let values = octets
+        .iter()
+        .map(|(team, octet)| {
+            let value = octet[varying[0]];
+            if varying.iter().all(|i| octet[*i] == value) {
+                Ok((*team, value))
+            } else {
+                Err(unrecoverable())
+            }
+        })
+        .collect::<Result<BTreeMap<_, _>, _>>()?;
+
+    let template = (0..4)
+        .map(|i| {
+            if varying.contains(&i) {
+                "X".to_owned()
+            } else {
+                first[i].to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(".");
+
+    Ok((template, values))
+}
+
+/// Works out the hostname template of a machine from the names each team's copy of
+/// it has, by finding where the team's ID appears in them
+fn hostname_template_from_hosts(
+    machine: &MachineEditor,
+    hosts: &BTreeMap<usize, String>,
+    ids: &[u8],
+) -> Result<String, ConversionError> {
+    let unrecoverable = || ConversionError::UnrecoverableHost(machine.name.clone());
+
+    let names = hosts
+        .values()
+        .map(|host| host.chars().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let first = names.first().ok_or_else(unrecoverable)?;
+
+    if hosts.len() == 1 {
+        return Ok(first.iter().collect());
+    }
+
+    let shortest = names.iter().map(Vec::len).min().unwrap_or_default();
+    let mut prefix = (0..shortest)
+        .take_while(|i| names.iter().all(|name| name[*i] == first[*i]))
+        .count();
+    let mut suffix = (0..shortest - prefix)
+        .take_while(|i| {
+            names
+                .iter()
+                .all(|name| name[name.len() - 1 - i] == first[first.len() - 1 - i])
+        })
+        .count();
+
+    // IDs sharing leading or trailing digits, such as 1 and 12, look like part of
+    // the name around them
+    while prefix > 0 && first[prefix - 1].is_ascii_digit() {
+        prefix -= 1;
+    }
+    while suffix > 0 && first[first.len() - suffix].is_ascii_digit() {
+        suffix -= 1;
+    }
+
+    let template = format!(
+        "{}{{id}}{}",
+        first[..prefix].iter().collect::<String>(),
+        first[first.len() - suffix..].iter().collect::<String>()
+    );
+
+    let machine = MachineEditor {
+        hostname_template: Some(template.clone()),
+        ..machine.clone()
+    };
+    if hosts
+        .iter()
+        .all(|(team, host)| machine.hostname_for(ids[*team]).as_ref() == Some(host))
+    {
+        Ok(template)
+    } else {
+        Err(unrecoverable())
+    }
+}
+
+/// Converts a generated configuration back into one the editor can work with, for
+/// configurations that weren't exported with their editor data. Each blue team gets
+/// its own copy of every machine on the way out, so this collapses the services of
+/// the teams back into machine templates, split apart by the
+/// `{machine}-{check}-{service}` names they were given, and works out the IP scheme
+/// from the addresses each team ended up with.
+///
+/// Some things never make it into a generated configuration, so they can't be
+/// recovered:
+///
+/// - disabled environments, expected results, groups, locked fields, unusual port
+///   confirmations, the description and CLI command templates
+/// - whether a service worth no points was informational
+/// - base IP templates and hosts, which come back as each machine's own template
+/// - CIDR networks, which come back as a multiplier and offsets giving the same
+///   addresses where they can
+/// - blue team IDs, where the addresses don't show them; the engine version, where
+///   no check shows which one it was written for
+pub fn convert_final_to_editor(
+    config: &FinalConfiguration,
+) -> Result<ConfigurationEditor, ConversionError> {
+    let mut red_white_teams = vec![];
+    let mut blue_teams = vec![];
+
+    for team in &config.teams {
+        match team {
+            TeamConfig::Red { name, users } | TeamConfig::White { name, users } => red_white_teams
+                .push(RedWhiteTeamEditor {
+                    name: name.clone(),
+                    users: users.clone(),
+                    white_team: matches!(team, TeamConfig::White { .. }),
+                }),
+            TeamConfig::Blue {
+                name,
+                users,
+                services,
+            } => blue_teams.push((name, users, services)),
+        }
+    }
+
+    let mut machines: Vec<MachineEditor> = vec![];
+    // For every machine, the host of each team's copy of it, and for every service
+    // the teams that have it
+    let mut hosts: Vec<BTreeMap<usize, String>> = vec![];
+    let mut service_teams: Vec<Vec<BTreeSet<usize>>> = vec![];
+    let mut engine_version = EngineVersion::Legacy;
+
+    for (team, (_, _, services)) in blue_teams.iter().enumerate() {
+        for service in services.iter() {
+            let (machine_name, service_name) = service
+                .name
+                .split_once(&format!("-{}-", service.check_name))
+                .map(|(machine, name)| (machine.to_owned(), name.to_owned()))
+                .unwrap_or_else(|| (service.host.clone(), service.name.clone()));
+
+            let definition =
+                ServiceDefinition::from_environments(&service.check_name, &service.environments)
+                    .ok_or_else(|| {
+                        ConversionError::UnknownCheck(
+                            service.name.clone(),
+                            service.check_name.clone(),
+                        )
+                    })?;
+
+            let without_doc_type = matches!(definition, ServiceDefinition::Elasticsearch { .. })
+                && service
+                    .environments
+                    .iter()
+                    .any(|environment| environment.property("doc_type").is_empty());
+            if without_doc_type || service.environments.iter().any(|e| e.negate) {
+                engine_version = EngineVersion::Current;
+            }
+
+            let m = match machines.iter().position(|m| m.name == machine_name) {
+                Some(m) => m,
+                None => {
+                    machines.push(MachineEditor {
+                        name: machine_name.clone(),
+                        ..Default::default()
+                    });
+                    hosts.push(BTreeMap::new());
+                    service_teams.push(vec![]);
+                    machines.len() - 1
+                }
+            };
+
+            if let Some(host) = hosts[m].insert(team, service.host.clone()) {
+                if host != service.host {
+                    return Err(ConversionError::UnrecoverableHost(machine_name));
+                }
+            }
+
+            let existing = machines[m].services.iter().position(|existing| {
+                existing.name == service_name
+                    && existing.definition.check_name() == service.check_name
+            });
+            if let Some(s) = existing {
+                service_teams[m][s].insert(team);
+                continue;
+            }
+
+            let inline_accounts = service.accounts.is_none()
+                && service
+                    .environments
+                    .iter()
+                    .any(|environment| environment.accounts.is_some());
+
+            machines[m].services.push(ServiceEditor {
+                name: service_name,
+                port: service.port,
+                points: service.points,
+                definition,
+                accounts: if inline_accounts {
+                    service
+                        .environments
+                        .iter()
+                        .find_map(|environment| environment.accounts.clone())
+                } else {
+                    service.accounts.clone()
+                },
+                inline_accounts,
+                excluded_teams: BTreeSet::new(),
+                allow_unusual_port: false,
+                interval: service.interval,
+                timeout: service.timeout,
+                informational: false,
+                expected_result: String::new(),
+                locked_fields: BTreeSet::new(),
+            });
+            service_teams[m].push(BTreeSet::from([team]));
+        }
+    }
+
+    let mut ids = vec![None::<u8>; blue_teams.len()];
+    let mut ip_generator = IpGeneratorScheme::OneTeam;
+
+    let mut ip_hosts = vec![];
+    for (machine, hosts) in machines.iter().zip(&hosts) {
+        let ips = hosts
+            .iter()
+            .filter_map(|(team, host)| Some((*team, host.parse::<Ipv4Addr>().ok()?)))
+            .collect::<BTreeMap<_, _>>();
+
+        if ips.is_empty() {
+            ip_hosts.push(None);
+        } else if ips.len() == hosts.len() {
+            ip_hosts.push(Some(ips));
+        } else {
+            return Err(ConversionError::UnrecoverableHost(machine.name.clone()));
+        }
+    }
+
+    if blue_teams.len() > 1 {
+        ip_generator = IpGeneratorScheme::ReplaceXWithId;
+
+        // The value of the X of each machine for each team
+        let mut values = vec![];
+        for (machine, ips) in machines.iter_mut().zip(&ip_hosts) {
+            let Some(ips) = ips else {
+                values.push(BTreeMap::new());
+                continue;
+            };
+
+            let (template, machine_values) = ip_template_from_hosts(&machine.name, ips)?;
+            machine.ip_template = template;
+            values.push(machine_values);
+        }
+
+        // Every machine having the same value for a team means the value is its ID
+        let mut same_per_team = BTreeMap::<usize, u8>::new();
+        let replaced_with_id = values
+            .iter()
+            .flatten()
+            .all(|(team, value)| *same_per_team.entry(*team).or_insert(*value) == *value)
+            && same_per_team.values().collect::<BTreeSet<_>>().len() == same_per_team.len();
+
+        if replaced_with_id {
+            for (team, id) in same_per_team {
+                ids[team] = Some(id);
+            }
+        } else {
+            let multiplier = values
+                .iter()
+                .flat_map(|values| {
+                    let first = values.values().next().copied().unwrap_or_default();
+                    values
+                        .values()
+                        .map(move |value| u32::from(first.abs_diff(*value)))
+                })
+                .fold(0, gcd);
+            let unrecoverable = || {
+                let machine = values
+                    .iter()
+                    .position(|values| !values.is_empty())
+                    .map(|m| machines[m].name.clone())
+                    .unwrap_or_default();
+                ConversionError::UnrecoverableHost(machine)
+            };
+            let multiplier = u8::try_from(multiplier)
+                .ok()
+                .filter(|multiplier| *multiplier > 0)
+                .ok_or_else(unrecoverable)?;
+
+            for values in &values {
+                for (team, value) in values {
+                    let id = value / multiplier;
+                    ids[*team] = Some(ids[*team].map_or(id, |other| other.min(id)));
+                }
+            }
+
+            for (m, values) in values.iter().enumerate() {
+                let Some((team, value)) = values.iter().next() else {
+                    continue;
+                };
+                let offset = value - multiplier * ids[*team].unwrap_or_default();
+
+                let consistent = values.iter().all(|(team, value)| {
+                    ids[*team]
+                        .and_then(|id| multiplier.checked_mul(id))
+                        .and_then(|base| base.checked_add(offset))
+                        == Some(*value)
+                });
+                if !consistent {
+                    return Err(ConversionError::UnrecoverableHost(machines[m].name.clone()));
+                }
+
+                machines[m].ip_offset = Some(offset);
+            }
+
+            ip_generator =
+                IpGeneratorScheme::ReplaceXWithIdTimesMultiplierPlusOffset { multiplier };
+        }
+    } else {
+        for (machine, ips) in machines.iter_mut().zip(&ip_hosts) {
+            if let Some(ip) = ips.iter().flat_map(BTreeMap::values).next() {
+                machine.ip_template = ip.to_string();
+            }
+        }
+    }
+
+    // Teams without any addresses to go by are given the lowest IDs left
+    let mut taken = ids.iter().flatten().copied().collect::<BTreeSet<_>>();
+    let ids = ids
+        .into_iter()
+        .map(|id| {
+            id.unwrap_or_else(|| {
+                let id = (1..=u8::MAX)
+                    .find(|id| !taken.contains(id))
+                    .unwrap_or_default();
+                taken.insert(id);
+                id
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for ((machine, hosts), ips) in machines.iter_mut().zip(&hosts).zip(&ip_hosts) {
+        if ips.is_none() {
+            machine.hostname_template = Some(hostname_template_from_hosts(machine, hosts, &ids)?);
+        }
+    }
+
+    for (machine, service_teams) in machines.iter_mut().zip(&service_teams) {
+        for (service, teams) in machine.services.iter_mut().zip(service_teams) {
+            service.excluded_teams = (0..ids.len())
+                .filter(|team| !teams.contains(team))
+                .map(|team| ids[team])
+                .collect();
+        }
+    }
+
+    Ok(ConfigurationEditor {
+        red_white_teams,
+        blue_teams: blue_teams
+            .into_iter()
+            .zip(ids)
+            .map(|((name, users, _), id)| BlueTeamEditor {
+                id,
+                name: name.clone(),
+                users: users.clone(),
+            })
+            .collect(),
+        machines,
+        ip_generator,
+        engine_version,
+        flags: config.flags.clone(),
+        ..Default::default()
+    })
+}
+
+/// A figure that can differ between blue teams, when services leave some teams out
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct PerTeam<T> {
+    pub fewest: T,
+    pub most: T,
+}
+
+impl<T: Ord + Copy> PerTeam<T> {
+    fn of(values: impl IntoIterator<Item = T>) -> Option<Self> {
+        values.into_iter().fold(None, |range, value| {
+            Some(match range {
+                None => PerTeam {
+                    fewest: value,
+                    most: value,
+                },
+                Some(PerTeam { fewest, most }) => PerTeam {
+                    fewest: fewest.min(value),
+                    most: most.max(value),
+                },
+            })
+        })
+    }
+}
+
+impl<T: Display + PartialEq> Display for PerTeam<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.fewest == self.most {
+            write!(f, "{}", self.fewest)
+        } else {
+            write!(f, "{} to {}, depending on the team", self.fewest, self.most)
+        }
+    }
+}
+
+/// Headline figures of a configuration, for a last look before it leaves the editor
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ConfigurationSummary {
+    pub blue_teams: usize,
+    pub machines: usize,
+    pub services_per_team: PerTeam<usize>,
+    pub points_per_team: PerTeam<u32>,
+}
+
+impl ConfigurationSummary {
+    pub fn of(config: &ConfigurationEditor) -> Self {
+        let services = config
+            .machines
+            .iter()
+            .flat_map(|machine| &machine.services)
+            .collect::<Vec<_>>();
+
+        // Without any blue teams yet, every service counts as it would for the first
+        let teams = if config.blue_teams.is_empty() {
+            vec![None]
+        } else {
+            config.blue_teams.iter().map(|team| Some(team.id)).collect()
+        };
+
+        let per_team = teams
+            .into_iter()
+            .map(|team| {
+                let scored = services
+                    .iter()
+                    .filter(|service| team.is_none_or(|id| !service.excluded_teams.contains(&id)))
+                    .collect::<Vec<_>>();
+
+                (
+                    scored.len(),
+                    scored
+                        .iter()
+                        .map(|service| u32::from(service.scored_points()))
+                        .sum::<u32>(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        Self {
+            blue_teams: config.blue_teams.len(),
+            machines: config.machines.len(),
+            services_per_team: PerTeam::of(per_team.iter().map(|(services, _)| *services))
+                .unwrap_or_default(),
+            points_per_team: PerTeam::of(per_team.iter().map(|(_, points)| *points))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Machine readable statistics about a configuration, for dashboards and scripts that
+/// compare configurations between events
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct ConfigurationStats {
+    pub red_teams: usize,
+    pub white_teams: usize,
+    pub blue_teams: usize,
+    pub machines: usize,
+    pub services: usize,
+    pub services_by_check: BTreeMap<&'static str, usize>,
+    pub total_points: u32,
+    pub points_by_team: BTreeMap<String, u32>,
+    pub services_by_port: BTreeMap<u16, usize>,
+}
+
+impl ConfigurationStats {
+    pub fn of(config: &ConfigurationEditor) -> Self {
+        let services = config
+            .machines
+            .iter()
+            .flat_map(|machine| &machine.services)
+            .collect::<Vec<_>>();
+
+        let mut services_by_check = BTreeMap::new();
+        let mut services_by_port = BTreeMap::new();
+        for service in &services {
+            *services_by_check
+                .entry(service.definition.check_name())
+                .or_default() += 1;
+            *services_by_port.entry(service.port).or_default() += 1;
+        }
+
+        let points_by_team = config
+            .blue_teams
+            .iter()
+            .map(|team| {
+                let points = services
+                    .iter()
+                    .filter(|service| !service.excluded_teams.contains(&team.id))
+                    .map(|service| u32::from(service.scored_points()))
+                    .sum();
+
+                (team.name.clone(), points)
+            })
+            .collect::<BTreeMap<_, u32>>();
+
+        Self {
+            red_teams: config
+                .red_white_teams
+                .iter()
+                .filter(|team| !team.white_team)
+                .count(),
+            white_teams: config
+                .red_white_teams
+                .iter()
+                .filter(|team| team.white_team)
+                .count(),
+            blue_teams: config.blue_teams.len(),
+            machines: config.machines.len(),
+            services: services.len(),
+            services_by_check,
+            total_points: points_by_team.values().sum(),
+            points_by_team,
+            services_by_port,
+        }
+    }
+}
+
+/// Where a single machine ends up on the network for a single blue team
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct IpPlanEntry {
+    pub team: String,
+    pub machine: String,
+    pub host: String,
+    pub services: Vec<(String, u16)>,
+}
+
+/// Resolves the address of every machine for every blue team, for handing off to
+/// whoever is building the network. The whole configuration has to convert
+/// successfully first so that the plan matches what the scoring engine would see
+pub fn ip_plan(config: &ConfigurationEditor) -> Result<Vec<IpPlanEntry>, ConversionError> {
+    convert_editor_to_final(config)?;
+
+    let mut conversion_state = ConversionState::new();
+    let mut plan = vec![];
+
+    for team in &config.blue_teams {
+        for (i, machine) in config.machines.iter().enumerate() {
+            plan.push(IpPlanEntry {
+                team: team.name.clone(),
+                machine: machine.name.clone(),
+                host: machine_host(&mut conversion_state, config, machine, i, team.id)?,
+                services: machine
+                    .services
+                    .iter()
+                    .filter(|service| !service.excluded_teams.contains(&team.id))
+                    .map(|service| (service.name.clone(), service.port))
+                    .collect(),
+            });
+        }
+    }
+
+    Ok(plan)
+}
+
+/// An address that more than one machine resolves to, along with the team and machine
+/// behind each use of it
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct IpCollision {
+    pub host: String,
+    pub uses: Vec<(String, String)>,
+}
+
+/// Resolves every machine for every blue team and lists each address used more than
+/// once. Unlike the checks made during conversion, which stop at the first problem,
+/// this goes through the whole set of teams, so templates that only collide for some
+/// team ids (such as 10.0.X.5 and 10.0.5.X when X is 5) are all found
+pub fn ip_collisions(config: &ConfigurationEditor) -> Vec<IpCollision> {
+    let mut uses = BTreeMap::<String, Vec<(String, String)>>::new();
+
+    for team in &config.blue_teams {
+        for (i, machine) in config.machines.iter().enumerate() {
+            if machine.uses_hostname() {
+                continue;
+            }
+
+            // Addresses that can't be resolved at all are reported by conversion
+            let Ok(host) = convert_id_to_ip(
+                &mut ConversionState::new(),
+                &machine.name,
+                &config.ip_template_for(machine),
+                machine.ip_offset,
+                &config.ip_generator,
+                team.id,
+                config.host_slot(team.id, i),
+            ) else {
+                continue;
+            };
+
+            uses.entry(host)
+                .or_default()
+                .push((team.name.clone(), machine.name.clone()));
+        }
+    }
+
+    uses.into_iter()
+        .filter(|(_, uses)| uses.len() > 1)
+        .map(|(host, uses)| IpCollision { host, uses })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(username: &str) -> User {
+        User {
+            username: username.to_owned(),
+            password: "password".to_owned(),
+        }
+    }
+
+    fn service(name: &str, port: u16, definition: ServiceDefinition) -> ServiceEditor {
+        ServiceEditor {
+            name: name.to_owned(),
+            port,
+            points: 100,
+            definition,
+            accounts: None,
+            inline_accounts: false,
+            excluded_teams: BTreeSet::new(),
+            allow_unusual_port: false,
+            interval: None,
+            timeout: None,
+            informational: false,
+            expected_result: String::new(),
+            locked_fields: BTreeSet::new(),
+        }
+    }
+
+    fn blue_team(id: u8, name: &str) -> BlueTeamEditor {
+        BlueTeamEditor {
+            id,
+            name: name.to_owned(),
+            users: vec![user(&format!("team{id}"))],
+        }
+    }
+
+    fn machine(name: &str, ip_template: &str, ip_offset: Option<u8>) -> MachineEditor {
+        MachineEditor {
+            name: name.to_owned(),
+            services: vec![service(
+                "web",
+                80,
+                ServiceDefinition::Http {
+                    environment: vec![HttpCheckInfo {
+                        matching_content: "Welcome".to_owned(),
+                        useragent: "scoring engine".to_owned(),
+                        vhost: "www.example.com".to_owned(),
+                        uri: "/".to_owned(),
+                        ..Default::default()
+                    }],
+                },
+            )],
+            ip_template: ip_template.to_owned(),
+            ip_offset,
+            ..Default::default()
+        }
+    }
+
+    /// Two blue teams, a white team and a couple of machines with services that have
+    /// accounts, which is enough for every collection conversion walks to matter
+    fn sample_config() -> ConfigurationEditor {
+        let mut ssh = service(
+            "ssh",
+            22,
+            ServiceDefinition::Ssh {
+                environment: vec![RemoteCommandCheckInfo {
+                    matching_content: "root".to_owned(),
+                    commands: "id".to_owned(),
+                    ..Default::default()
+                }],
+            },
+        );
+        ssh.accounts = Some(vec![user("root"), user("admin")]);
+
+        let mut db = machine("db", "10.X.1.20", None);
+        db.services.push(ssh);
+
+        ConfigurationEditor {
+            red_white_teams: vec![RedWhiteTeamEditor {
+                name: "White".to_owned(),
+                users: vec![user("white")],
+                white_team: true,
+            }],
+            blue_teams: vec![blue_team(1, "Team 1"), blue_team(2, "Team 2")],
+            machines: vec![machine("web", "10.X.1.10", None), db],
+            ip_generator: IpGeneratorScheme::ReplaceXWithId,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn conversion_is_deterministic() {
+        let config = sample_config();
+
+        let (first, _) = convert_editor_to_final(&config).unwrap();
+        let (second, _) = convert_editor_to_final(&config.clone()).unwrap();
+
+        assert_eq!(
+            serde_yaml::to_string(&first).unwrap(),
+            serde_yaml::to_string(&second).unwrap()
+        );
+    }
+
+    #[test]
+    fn duplicate_offsets_report_the_lowest_offset_first() {
+        let config = ConfigurationEditor {
+            machines: vec![
+                machine("a", "10.0.0.X", Some(7)),
+                machine("b", "10.0.0.X", Some(3)),
+                machine("c", "10.0.0.X", Some(7)),
+                machine("d", "10.0.0.X", Some(3)),
+            ],
+            ip_generator: IpGeneratorScheme::ReplaceXWithIdTimesMultiplierPlusOffset {
+                multiplier: 20,
+            },
+            ..sample_config()
+        };
+
+        for _ in 0..10 {
+            match convert_editor_to_final(&config) {
+                Err(ConversionError::DuplicateOffsets(machines)) => {
+                    assert_eq!(machines, ["b", "d"]);
+                }
+                other => panic!("expected duplicate offsets, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn duplicate_blue_team_ids_report_the_lowest_id_first() {
+        let config = ConfigurationEditor {
+            blue_teams: vec![
+                blue_team(4, "Team A"),
+                blue_team(2, "Team B"),
+                blue_team(4, "Team C"),
+                blue_team(2, "Team D"),
+            ],
+            ..sample_config()
+        };
+
+        for _ in 0..10 {
+            match convert_editor_to_final(&config) {
+                Err(ConversionError::DuplicateBlueTeamIDs(id, names)) => {
+                    assert_eq!(id, 2);
+                    assert_eq!(names, ["Team B", "Team D"]);
+                }
+                other => panic!("expected duplicate blue team IDs, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn generated_configurations_convert_back() {
+        let config = sample_config();
+
+        let (final_config, _) = convert_editor_to_final(&config).unwrap();
+        let recovered = convert_final_to_editor(&final_config).unwrap();
+
+        assert_eq!(recovered.red_white_teams, config.red_white_teams);
+        assert_eq!(recovered.blue_teams, config.blue_teams);
+        assert_eq!(recovered.ip_generator, config.ip_generator);
+        assert_eq!(recovered.machines, config.machines);
+    }
+
+    #[test]
+    fn multiplier_schemes_convert_back() {
+        let mut config = sample_config();
+        config.ip_generator =
+            IpGeneratorScheme::ReplaceXWithIdTimesMultiplierPlusOffset { multiplier: 20 };
+        config.machines[0].ip_template = "10.0.0.X".to_owned();
+        config.machines[0].ip_offset = Some(10);
+        config.machines[1].ip_template = "10.0.0.X".to_owned();
+        config.machines[1].ip_offset = Some(11);
+
+        let (final_config, _) = convert_editor_to_final(&config).unwrap();
+        let recovered = convert_final_to_editor(&final_config).unwrap();
+
+        assert_eq!(recovered.blue_teams, config.blue_teams);
+        assert_eq!(recovered.ip_generator, config.ip_generator);
+        assert_eq!(recovered.machines, config.machines);
+    }
+
+    #[test]
+    fn multiplied_addresses_past_255_are_rejected() {
+        let mut config = sample_config();
+        config.ip_generator =
+            IpGeneratorScheme::ReplaceXWithIdTimesMultiplierPlusOffset { multiplier: 100 };
+        config.machines[0].ip_template = "10.0.0.X".to_owned();
+        config.machines[0].ip_offset = Some(60);
+        config.machines[1].ip_template = "10.0.1.X".to_owned();
+        config.machines[1].ip_offset = Some(50);
+
+        // The first team's addresses fit; the second team's would end in 260
+        match convert_editor_to_final(&config) {
+            Err(ConversionError::InvalidIpAddress(machine, ip)) => {
+                assert_eq!(machine, "web");
+                assert_eq!(ip, "10.0.0.260");
+            }
+            other => panic!("expected an invalid address, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn summary_counts_each_team_without_its_excluded_services() {
+        let mut config = sample_config();
+        config.machines[1].services[1].excluded_teams.insert(2);
+
+        let summary = ConfigurationSummary::of(&config);
+
+        assert_eq!(summary.services_per_team, PerTeam { fewest: 2, most: 3 });
+        assert_eq!(
+            summary.points_per_team,
+            PerTeam {
+                fewest: 200,
+                most: 300
+            }
+        );
+        assert_eq!(
+            summary.services_per_team.to_string(),
+            "2 to 3, depending on the team"
+        );
+    }
+
+    #[test]
+    fn merged_services_exclude_the_teams_they_excluded_before() {
+        let mut config = sample_config();
+        let mut other = sample_config();
+        other.machines[0].services[0].excluded_teams = BTreeSet::from([1, 7]);
+
+        config.merge(&other);
+
+        assert_eq!(
+            config
+                .blue_teams
+                .iter()
+                .map(|team| team.id)
+                .collect::<Vec<_>>(),
+            [1, 2, 3, 4]
+        );
+        // Team 1 of the other configuration is now team 3, and there was no team 7
+        assert_eq!(
+            config.machines[2].services[0].excluded_teams,
+            BTreeSet::from([3])
+        );
+    }
+
+    #[test]
+    fn merged_team_names_are_unique_across_colors() {
+        let mut config = sample_config();
+        let other = ConfigurationEditor {
+            red_white_teams: vec![RedWhiteTeamEditor {
+                name: "Team 1".to_owned(),
+                users: vec![user("red")],
+                white_team: false,
+            }],
+            ..Default::default()
+        };
+
+        let changes = config.merge(&other);
+
+        assert_eq!(config.red_white_teams[1].name, "Team 1-2");
+        assert_eq!(changes, ["renamed team 'Team 1' to 'Team 1-2'"]);
+    }
+
+    #[test]
+    fn locked_fields_are_left_alone_by_bulk_setters() {
+        let mut web = service("web", 80, ServiceDefinition::Icmp { environment: None });
+        web.locked_fields.insert(LockableField::Port);
+
+        assert!(!web.bulk_set_port(8080));
+        assert!(web.bulk_set_points(50));
+        assert_eq!((web.port, web.points), (80, 50));
+
+        web.locked_fields = BTreeSet::from([LockableField::Points]);
+
+        assert!(web.bulk_set_port(8080));
+        assert!(!web.bulk_set_points(10));
+        assert_eq!((web.port, web.points), (8080, 50));
+    }
+
+    #[test]
+    fn bulk_changes_skip_locked_services() {
+        let mut config = sample_config();
+        config.machines[1].services[0]
+            .locked_fields
+            .insert(LockableField::Port);
+
+        let counts = config.bulk_change_services("HTTPCheck", BulkServiceChange::Port(8080));
+
+        assert_eq!(counts, (1, 1));
+        assert_eq!(config.machines[0].services[0].port, 8080);
+        assert_eq!(config.machines[1].services[0].port, 80);
+        // Services of other types aren't touched
+        assert_eq!(config.machines[1].services[1].port, 22);
+    }
+
+    #[test]
+    fn hostnames_keep_the_xs_in_their_names() {
+        let mut mail = machine("mail", "10.X.1.30", None);
+
+        mail.hostname_template = Some("MX{id}.EXCHANGE.comp.local".to_owned());
+        assert_eq!(
+            mail.hostname_for(1).as_deref(),
+            Some("MX1.EXCHANGE.comp.local")
+        );
+
+        mail.hostname_template = Some("mx.X.comp.local".to_owned());
+        assert_eq!(mail.hostname_for(12).as_deref(), Some("mx.12.comp.local"));
+
+        // With no {id} and no part that is just X, every team would get the same name
+        mail.hostname_template = Some("MX.comp.local".to_owned());
+        assert!(matches!(
+            mail.check_hostname(2),
+            Err(ConversionError::HostnameNotPerTeam(_))
+        ));
+    }
+}
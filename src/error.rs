@@ -23,6 +23,7 @@ use crate::config::ConversionError;
 pub enum EditorError {
     Conversion(ConversionError),
     Serialize(serde_yaml::Error),
+    SerializeJson(serde_json::Error),
 }
 
 impl Display for EditorError {
@@ -30,6 +31,9 @@ impl Display for EditorError {
         match self {
             Self::Conversion(err) => write!(f, "error converting configuration: {err}"),
             Self::Serialize(err) => write!(f, "error serializing configuration: {err}"),
+            Self::SerializeJson(err) => {
+                write!(f, "error serializing configuration as JSON: {err}")
+            }
         }
     }
 }
@@ -47,3 +51,9 @@ impl From<serde_yaml::Error> for EditorError {
         Self::Serialize(err)
     }
 }
+
+impl From<serde_json::Error> for EditorError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::SerializeJson(err)
+    }
+}
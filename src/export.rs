@@ -0,0 +1,258 @@
+// export.rs: Helpers for handing data generated by the editor to the user as files
+//
+// Copyright (C) 2023 Andrew Rioux
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+
+use gloo_timers::callback::Timeout;
+use js_sys::{Function, Promise, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{window, Blob, BlobPropertyBag, HtmlAnchorElement, Url};
+
+use crate::config::{ConfigurationEditor, FinalConfiguration, TeamConfig};
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// Renders a header and rows as CSV, quoting only the fields that need it
+pub fn to_csv(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut csv = header
+        .iter()
+        .map(|field| csv_field(field))
+        .collect::<Vec<_>>()
+        .join(",");
+    csv.push('\n');
+
+    for row in rows {
+        csv.push_str(
+            &row.iter()
+                .map(|field| csv_field(field))
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+        csv.push('\n');
+    }
+
+    csv
+}
+
+/// Turns a file name into something that can be safely offered as a download
+pub fn file_name(name: &str, extension: &str) -> String {
+    let name = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+
+    format!("{name}.{extension}")
+}
+
+/// One row per team user of the generated configuration, with the team's name and
+/// color so whoever receives a login sheet knows what the account is for
+pub fn roster_rows(config: &FinalConfiguration) -> Vec<Vec<String>> {
+    config
+        .teams
+        .iter()
+        .flat_map(|team| {
+            let (color, name, users) = match team {
+                TeamConfig::Red { name, users } => ("red", name, users),
+                TeamConfig::White { name, users } => ("white", name, users),
+                TeamConfig::Blue { name, users, .. } => ("blue", name, users),
+            };
+
+            users.iter().map(move |user| {
+                vec![
+                    name.clone(),
+                    color.to_owned(),
+                    user.username.clone(),
+                    user.password.clone(),
+                ]
+            })
+        })
+        .collect()
+}
+
+/// A Markdown document listing what every check of every service looks for and what
+/// the organizers say that means, for the white team to refer to during the event
+pub fn answer_key(config_name: &str, config: &ConfigurationEditor) -> String {
+    let mut key = format!("# Answer key: {config_name}\n");
+
+    for machine in &config.machines {
+        key.push_str(&format!(
+            "\n## {} ({})\n",
+            machine.name,
+            config.ip_template_for(machine)
+        ));
+
+        for service in &machine.services {
+            key.push_str(&format!(
+                "\n### {} ({}, port {})\n\n",
+                service.name,
+                service.definition.check_name(),
+                service.port
+            ));
+
+            let annotations = service.check_annotations();
+            if annotations.is_empty() {
+                key.push_str("No checks are enabled.\n");
+            }
+
+            for annotation in annotations {
+                let expected = if annotation.expected_result.trim().is_empty() {
+                    "(not described)"
+                } else {
+                    annotation.expected_result.trim()
+                };
+
+                let looks_for = if annotation.negate {
+                    "Must not find"
+                } else {
+                    "Looks for"
+                };
+
+                key.push_str(&format!(
+                    "- {looks_for} `{}`: {expected}\n",
+                    annotation.matching_content
+                ));
+            }
+        }
+    }
+
+    key
+}
+
+/// Fills in a CLI command template with the path the configuration is saved to and
+/// the configuration itself
+pub fn cli_command(template: &str, path: &str, yaml: &str) -> String {
+    template
+        .replace("{path}", path)
+        .replace("{yaml}", yaml.trim_end())
+}
+
+/// YAML comment lines recording which editor produced a file, when, and from which
+/// configuration; being comments, they leave the configuration itself untouched
+pub fn provenance_header(config_name: &str, exported_at: &str) -> String {
+    let config_name = config_name.replace(['\n', '\r'], " ");
+
+    format!(
+        "# Generated by {} {}\n# Source configuration: {config_name}\n# Exported: {exported_at}\n",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+    )
+}
+
+/// Puts a YAML comment naming the machine and service above each service of the
+/// generated configuration, for people reading the file rather than the engine
+pub fn comment_services(yaml: &str, config: &ConfigurationEditor) -> String {
+    let services = config
+        .machines
+        .iter()
+        .flat_map(|machine| {
+            machine.services.iter().map(|service| {
+                (
+                    service.config_name(&machine.name),
+                    format!(
+                        "machine {}, service {} ({})",
+                        machine.name,
+                        service.name,
+                        service.definition.check_name()
+                    ),
+                )
+            })
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut commented = String::with_capacity(yaml.len());
+
+    for line in yaml.lines() {
+        let indent = &line[..line.len() - line.trim_start().len()];
+        let name = line
+            .trim_start()
+            .strip_prefix("- name: ")
+            .map(|name| name.trim_matches(['\'', '"']));
+
+        if let Some(context) = name.and_then(|name| services.get(name)) {
+            commented.push_str(&format!("{indent}# {context}\n"));
+        }
+
+        commented.push_str(line);
+        commented.push('\n');
+    }
+
+    commented
+}
+
+/// Prompts the browser to save the contents provided as a file
+pub fn download(file_name: &str, mime_type: &str, contents: &str) -> Result<(), JsValue> {
+    let parts = js_sys::Array::of1(&JsValue::from_str(contents));
+    let mut options = BlobPropertyBag::new();
+    options.type_(mime_type);
+    let blob = Blob::new_with_str_sequence_and_options(&parts, &options)?;
+    let url = Url::create_object_url_with_blob(&blob)?;
+
+    let document = window()
+        .and_then(|window| window.document())
+        .ok_or_else(|| JsValue::from_str("no document to download from"))?;
+    let anchor = document
+        .create_element("a")?
+        .dyn_into::<HtmlAnchorElement>()?;
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    anchor.click();
+
+    // Some browsers start reading the blob only after the click has been handled, so
+    // the URL is kept until the current task is done
+    Timeout::new(0, move || {
+        if let Err(e) = Url::revoke_object_url(&url) {
+            log::error!("Could not release a downloaded file: {e:?}");
+        }
+    })
+    .forget();
+
+    Ok(())
+}
+
+/// Starts copying text to the clipboard, or returns `None` without copying anything
+/// when the browser doesn't offer the clipboard API, as happens outside of HTTPS. The
+/// API is looked up by name because web-sys only exposes it as an unstable API
+pub fn copy_to_clipboard(text: &str) -> Option<JsFuture> {
+    let navigator = window()?.navigator();
+    let clipboard = Reflect::get(&navigator, &JsValue::from_str("clipboard"))
+        .ok()
+        .filter(|clipboard| !clipboard.is_undefined())?;
+    let write_text = Reflect::get(&clipboard, &JsValue::from_str("writeText"))
+        .ok()?
+        .dyn_into::<Function>()
+        .ok()?;
+    let promise = write_text
+        .call1(&clipboard, &JsValue::from_str(text))
+        .ok()?
+        .dyn_into::<Promise>()
+        .ok()?;
+
+    Some(JsFuture::from(promise))
+}
@@ -0,0 +1,126 @@
+// flags.rs: Edits the flags planted for the red team to capture
+//
+// Copyright (C) 2023 Andrew Rioux
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+use crate::{
+    config::Flag,
+    state::{EditorMessage, EditorStateContext},
+};
+
+#[function_component]
+pub fn FlagsEditor() -> Html {
+    let editor_state = use_context::<EditorStateContext>().unwrap();
+    let flags = &editor_state.force_init().0.flags;
+    let locked = editor_state.is_locked();
+
+    let add_flag = {
+        let editor_state = editor_state.clone();
+        let flags = flags.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+
+            let mut flags = flags.clone();
+            flags.push(Flag::default());
+            editor_state.dispatch(EditorMessage::UpdateFlags(flags));
+        })
+    };
+
+    let rows = flags.iter().enumerate().map(|(i, flag)| {
+        let edit = |set: fn(&mut Flag, String)| {
+            let editor_state = editor_state.clone();
+            let flags = flags.clone();
+
+            Callback::from(move |e: Event| {
+                let mut flags = flags.clone();
+                set(
+                    &mut flags[i],
+                    e.target_unchecked_into::<HtmlInputElement>().value(),
+                );
+                editor_state.dispatch(EditorMessage::UpdateFlags(flags));
+            })
+        };
+
+        let remove = {
+            let editor_state = editor_state.clone();
+            let flags = flags.clone();
+
+            Callback::from(move |e: MouseEvent| {
+                e.prevent_default();
+
+                let mut flags = flags.clone();
+                flags.remove(i);
+                editor_state.dispatch(EditorMessage::UpdateFlags(flags));
+            })
+        };
+
+        html! {
+            <tr key={i}>
+                <td>
+                    <input
+                        value={flag.name.clone()}
+                        onchange={edit(|flag, name| flag.name = name)}
+                        disabled={locked}
+                        placeholder="Name"
+                    />
+                </td>
+                <td>
+                    <input
+                        value={flag.value.clone()}
+                        onchange={edit(|flag, value| flag.value = value)}
+                        disabled={locked}
+                        placeholder="Value"
+                    />
+                </td>
+                <td>
+                    if !locked {
+                        <a href="#" onclick={remove}>{ "Remove" }</a>
+                    }
+                </td>
+            </tr>
+        }
+    });
+
+    html! {
+        <div class="flags-editor">
+            <h4>{ "Flags" }</h4>
+
+            if flags.is_empty() {
+                <p>{ "No flags yet; the configuration is generated with an empty list of flags." }</p>
+            } else {
+                <table>
+                    <tr>
+                        <th>{ "Name" }</th>
+                        <th>{ "Value" }</th>
+                        <th></th>
+                    </tr>
+                    { for rows }
+                </table>
+            }
+
+            <a
+                href="#"
+                class={classes!("button", locked.then_some("disabled"))}
+                onclick={add_flag}
+            >
+                { "Add flag" }
+            </a>
+        </div>
+    }
+}
@@ -15,20 +15,62 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use gloo_file::{
+    callbacks::{read_as_text, FileReader},
+    File,
+};
 use wasm_bindgen::JsCast;
-use web_sys::HtmlInputElement;
+use web_sys::{window, HtmlInputElement, HtmlTextAreaElement};
 use yew::prelude::*;
 
-use crate::state::{EditingState, EditorMessage, EditorStateContext};
+use crate::{
+    config::{convert_final_to_editor, Configuration, FinalConfiguration},
+    export,
+    state::{
+        leave_warning_enabled, set_leave_warning_enabled, EditingState, EditorMessage,
+        EditorStateContext, StoredConfigurations,
+    },
+    warnings::conversion_error_count,
+};
+
+/// The outcome of checking one stored configuration, kept by name as well as position
+/// so that a result isn't used to open a configuration that has since moved
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct LibraryCheck {
+    index: usize,
+    name: String,
+    errors: usize,
+}
+
+/// Adds a configuration to the library under `name`. Configurations exported with their
+/// editor data come back exactly; others are worked back into machine templates as well
+/// as they can be
+fn import_configuration(
+    editor_state: &EditorStateContext,
+    name: String,
+    yaml: &str,
+) -> Result<(), String> {
+    let imported = match serde_yaml::from_str::<Configuration>(yaml) {
+        Ok(imported) => imported.editor_info,
+        Err(_) => {
+            let final_config = serde_yaml::from_str::<FinalConfiguration>(yaml)
+                .map_err(|e| format!("This isn't a scoring engine configuration: {e}"))?;
+
+            convert_final_to_editor(&final_config)
+                .map_err(|e| format!("This configuration can't be opened in the editor: {e}"))?
+        }
+    };
+
+    editor_state.dispatch(EditorMessage::Import(name, imported));
+    Ok(())
+}
 
 #[function_component]
 pub fn InitEditor() -> Html {
     let editor_state = use_context::<EditorStateContext>().unwrap();
 
-    let error = &editor_state.error;
-
     let new_config_name = use_state(String::default);
-    let edited_config_name = use_state(Option::<u8>::default);
+    let edited_config_name = use_state(Option::<usize>::default);
 
     let config_name_editor = use_node_ref();
 
@@ -40,7 +82,7 @@ pub fn InitEditor() -> Html {
             let Some(input) = config_name_editor.cast::<HtmlInputElement>() else {
                 return;
             };
-            new_config_name.set(input.value().into());
+            new_config_name.set(input.value());
         })
     };
 
@@ -54,17 +96,291 @@ pub fn InitEditor() -> Html {
         })
     };
 
-    let config_len = editor_state.configs.len();
+    let create_first = {
+        let editor_state = editor_state.clone();
+        let new_config_name = new_config_name.clone();
+
+        Callback::from(move |_| {
+            let name = if new_config_name.is_empty() {
+                "New configuration".to_owned()
+            } else {
+                new_config_name.to_string()
+            };
+            editor_state.dispatch(EditorMessage::CreateNew(name));
+            new_config_name.set(String::default());
+        })
+    };
+
+    let import_details = use_node_ref();
+
+    let open_import = {
+        let import_details = import_details.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            let Some(details) = import_details.cast::<web_sys::Element>() else {
+                return;
+            };
+            if let Err(e) = details.set_attribute("open", "") {
+                log::error!("Could not open the import section: {e:?}");
+            }
+            details.scroll_into_view();
+        })
+    };
+
+    let import_name = use_state(String::new);
+    let import_text = use_state(String::new);
+    let import_error = use_state(Option::<String>::default);
+
+    let set_import_name = {
+        let import_name = import_name.clone();
+
+        Callback::from(move |e: InputEvent| {
+            import_name.set(e.target_unchecked_into::<HtmlInputElement>().value());
+        })
+    };
+
+    let set_import_text = {
+        let import_text = import_text.clone();
+
+        Callback::from(move |e: InputEvent| {
+            import_text.set(e.target_unchecked_into::<HtmlTextAreaElement>().value());
+        })
+    };
+
+    let onimport = {
+        let editor_state = editor_state.clone();
+        let import_name = import_name.clone();
+        let import_text = import_text.clone();
+        let import_error = import_error.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+
+            let name = if import_name.trim().is_empty() {
+                "Imported configuration".to_owned()
+            } else {
+                import_name.trim().to_owned()
+            };
+
+            match import_configuration(&editor_state, name, &import_text) {
+                Ok(()) => {
+                    import_name.set(String::new());
+                    import_text.set(String::new());
+                    import_error.set(None);
+                }
+                Err(e) => import_error.set(Some(e)),
+            }
+        })
+    };
+
+    // Reading a file finishes later, and is abandoned if its reader is dropped
+    let file_reader = use_mut_ref(Option::<FileReader>::default);
+
+    let onimport_file = {
+        let editor_state = editor_state.clone();
+        let import_name = import_name.clone();
+        let import_error = import_error.clone();
+        let file_reader = file_reader.clone();
+
+        Callback::from(move |e: Event| {
+            let input = e.target_unchecked_into::<HtmlInputElement>();
+            let Some(file) = input.files().and_then(|files| files.get(0)) else {
+                return;
+            };
+            // Lets the same file be picked again after fixing it
+            input.set_value("");
+
+            let file = File::from(file);
+            let name = if import_name.trim().is_empty() {
+                let file_name = file.name();
+                file_name
+                    .strip_suffix(".yaml")
+                    .or_else(|| file_name.strip_suffix(".yml"))
+                    .unwrap_or(&file_name)
+                    .to_owned()
+            } else {
+                import_name.trim().to_owned()
+            };
+
+            let editor_state = editor_state.clone();
+            let import_name = import_name.clone();
+            let import_error = import_error.clone();
+
+            *file_reader.borrow_mut() = Some(read_as_text(&file, move |text| {
+                let imported = text
+                    .map_err(|e| format!("Could not read the file: {e}"))
+                    .and_then(|text| import_configuration(&editor_state, name, &text));
+
+                match imported {
+                    Ok(()) => {
+                        import_name.set(String::new());
+                        import_error.set(None);
+                    }
+                    Err(e) => import_error.set(Some(e)),
+                }
+            }));
+        })
+    };
+
+    let export_all = {
+        let editor_state = editor_state.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+
+            let exported = serde_json::to_string_pretty(&editor_state.configs)
+                .map_err(|e| format!("could not export the configurations: {e}"))
+                .and_then(|backup| {
+                    export::download(
+                        &export::file_name("scoringengine-editor-backup", "json"),
+                        "application/json",
+                        &backup,
+                    )
+                    .map_err(|e| format!("could not export the configurations: {e:?}"))
+                });
+
+            if let Err(e) = exported {
+                editor_state.dispatch(EditorMessage::Error(e));
+            }
+        })
+    };
+
+    let backup_error = use_state(Option::<String>::default);
+    let backup_reader = use_mut_ref(Option::<FileReader>::default);
+
+    let import_all = {
+        let editor_state = editor_state.clone();
+        let backup_error = backup_error.clone();
+
+        Callback::from(move |e: Event| {
+            let input = e.target_unchecked_into::<HtmlInputElement>();
+            let Some(file) = input.files().and_then(|files| files.get(0)) else {
+                return;
+            };
+            input.set_value("");
+
+            let editor_state = editor_state.clone();
+            let backup_error = backup_error.clone();
+
+            *backup_reader.borrow_mut() = Some(read_as_text(&File::from(file), move |text| {
+                let backup = text
+                    .map_err(|e| format!("Could not read the file: {e}"))
+                    .and_then(|text| {
+                        serde_json::from_str::<Vec<StoredConfigurations>>(&text).map_err(|e| {
+                            format!("This isn't a backup of the editor's configurations: {e}")
+                        })
+                    });
+
+                match backup {
+                    Ok(backup) => {
+                        backup_error.set(None);
+                        editor_state.dispatch(EditorMessage::ReplaceAllConfigs(backup));
+                    }
+                    Err(e) => backup_error.set(Some(e)),
+                }
+            }));
+        })
+    };
+
+    let library_checks = use_state(Option::<Vec<LibraryCheck>>::default);
+
+    let check_library = {
+        let editor_state = editor_state.clone();
+        let library_checks = library_checks.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            library_checks.set(Some(
+                editor_state
+                    .configs
+                    .iter()
+                    .enumerate()
+                    .map(|(index, stored)| LibraryCheck {
+                        index,
+                        name: stored.name.clone(),
+                        errors: conversion_error_count(&stored.config),
+                    })
+                    .collect(),
+            ));
+        })
+    };
+
+    let dismiss_library_checks = {
+        let library_checks = library_checks.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            library_checks.set(None);
+        })
+    };
+
     let selected_config = match &editor_state.state {
         EditingState::Initializing => None,
         EditingState::HasConfig { config, .. } => Some(*config),
     };
 
+    let library_check_rows = library_checks.iter().flatten().map(|check| {
+        let open = {
+            let editor_state = editor_state.clone();
+            let check = check.clone();
+
+            Callback::from(move |_| {
+                let still_there = editor_state
+                    .configs
+                    .get(check.index)
+                    .is_some_and(|stored| stored.name == check.name);
+
+                let leaving = selected_config != Some(check.index);
+
+                if check.errors > 0 && still_there && (!leaving || editor_state.confirm_leaving()) {
+                    editor_state.dispatch(EditorMessage::FinishInit(check.index));
+                }
+            })
+        };
+
+        html! {
+            <tr
+                key={check.index}
+                class={classes!((check.errors > 0).then_some("invalid"))}
+                onclick={open}
+                title={(check.errors > 0).then_some("Open this configuration to fix it")}
+            >
+                <td>{ &check.name }</td>
+                <td>{ if check.errors == 0 { "Yes" } else { "No" } }</td>
+                <td>{ check.errors }</td>
+            </tr>
+        }
+    });
+
+    let leave_warning = use_state(leave_warning_enabled);
+
+    let set_leave_warning = {
+        let leave_warning = leave_warning.clone();
+
+        Callback::from(move |e: Event| {
+            let enabled = e.target_unchecked_into::<HtmlInputElement>().checked();
+            set_leave_warning_enabled(enabled);
+            leave_warning.set(enabled);
+        })
+    };
+
+    let invalid_count = library_checks
+        .iter()
+        .flatten()
+        .filter(|check| check.errors > 0)
+        .count();
+
+    let config_len = editor_state.configs.len();
+
     let configs = editor_state.configs.iter().enumerate().map(|(i, config)| {
         let edit = {
             let editor_state = editor_state.clone();
             Callback::from(move |_| {
-                editor_state.dispatch(EditorMessage::FinishInit(i as u8));
+                if selected_config != Some(i) && !editor_state.confirm_leaving() {
+                    return;
+                }
+                editor_state.dispatch(EditorMessage::FinishInit(i));
             })
         };
 
@@ -76,7 +392,7 @@ pub fn InitEditor() -> Html {
                 if new_config_name.is_empty() {
                     return;
                 }
-                editor_state.dispatch(EditorMessage::Copy(new_config_name.to_string(), i as u8));
+                editor_state.dispatch(EditorMessage::Copy(new_config_name.to_string(), i));
                 new_config_name.set("".to_owned());
             })
         };
@@ -84,10 +400,50 @@ pub fn InitEditor() -> Html {
         let delete = {
             let editor_state = editor_state.clone();
             Callback::from(move |_| {
-                editor_state.dispatch(EditorMessage::DeleteConfig(i as u8));
+                editor_state.dispatch(EditorMessage::DeleteConfig(i));
+            })
+        };
+
+        let lock = {
+            let editor_state = editor_state.clone();
+            Callback::from(move |_| {
+                editor_state.dispatch(EditorMessage::SetLocked(i, true));
+            })
+        };
+
+        let unlock = {
+            let editor_state = editor_state.clone();
+            let name = config.name.clone();
+            Callback::from(move |_| {
+                let confirmed = window()
+                    .and_then(|window| {
+                        window
+                            .confirm_with_message(&format!(
+                                "Unlock '{name}'? It can be changed again until it is locked."
+                            ))
+                            .ok()
+                    })
+                    .unwrap_or(false);
+
+                if confirmed {
+                    editor_state.dispatch(EditorMessage::SetLocked(i, false));
+                }
+            })
+        };
+
+        let merge = {
+            let editor_state = editor_state.clone();
+            Callback::from(move |_| {
+                editor_state.dispatch(EditorMessage::MergeInto(i));
             })
         };
 
+        let merge_target = selected_config
+            .filter(|selected| *selected != i)
+            .and_then(|selected| editor_state.configs.get(selected))
+            .filter(|selected| !selected.locked)
+            .map(|selected| selected.name.clone());
+
         let stop_editing = {
             let edited_config_name = edited_config_name.clone();
 
@@ -100,7 +456,7 @@ pub fn InitEditor() -> Html {
             let edited_config_name = edited_config_name.clone();
 
             Callback::from(move |_| {
-                edited_config_name.set(Some(i as u8));
+                edited_config_name.set(Some(i));
             })
         };
 
@@ -117,18 +473,18 @@ pub fn InitEditor() -> Html {
                 let Some(name) = name else {
                     return;
                 };
-                editor_state.dispatch(EditorMessage::EditConfigName(name, i as u8));
+                editor_state.dispatch(EditorMessage::EditConfigName(name, i));
             })
         };
 
-        let editing_name = Some(i as u8) == *edited_config_name;
+        let editing_name = Some(i) == *edited_config_name;
 
         let service_count: usize = config.config.machines.iter().map(|machine| machine.services.len()).sum();
 
         html! {
             <div class={classes!(
                 "config-row",
-                (Some(i as u8) == selected_config).then(|| Some("selected"))
+                (Some(i) == selected_config).then_some(Some("selected"))
             )}>
                 <div class="config-name">
                     if editing_name {
@@ -137,13 +493,25 @@ pub fn InitEditor() -> Html {
                             onblur={stop_editing}
                             value={config.name.clone()}
                         />
+                    } else if config.locked {
+                        <span title="Unlock this configuration to rename it">{config.name.clone()}</span>
                     } else {
                         <span onclick={start_edit_name}>{config.name.clone()}</span>
                     }
                 </div>
 
+                if !config.config.description.is_empty() {
+                    <div class="config-description" title={config.config.description.clone()}>
+                        { &config.config.description }
+                    </div>
+                }
+
                 <div class="config-details">
                     <div class="config-numbers">
+                        <div>{ format!("Engine version: {}", config.config.engine_version.name()) }</div>
+                        if config.locked {
+                            <div class="config-locked">{ "Locked" }</div>
+                        }
                         { format!(
                             "{} users * {} machine templates * {} services = {} total services across {} machines",
                             config.config.blue_teams.len(),
@@ -156,11 +524,26 @@ pub fn InitEditor() -> Html {
 
                     <div class="config-buttons">
                         <a href="#" onclick={edit} class="button">{ "Edit" }</a>
+                        if let Some(target) = merge_target {
+                            <a
+                                href="#"
+                                onclick={merge}
+                                class="button"
+                                title={format!("Add the teams and machines of this configuration to '{target}'")}
+                            >
+                                { "Merge into current" }
+                            </a>
+                        }
                         <a href="#" onclick={copy} class={classes!(
                             "button",
-                            new_config_name.is_empty().then(|| Some("disabled"))
+                            new_config_name.is_empty().then_some(Some("disabled"))
                         )}>{ "Copy" }</a>
-                        <a href="#" onclick={delete} class="button">{ "Delete" }</a>
+                        if config.locked {
+                            <a href="#" onclick={unlock} class="button">{ "Unlock" }</a>
+                        } else {
+                            <a href="#" onclick={lock} class="button">{ "Lock" }</a>
+                            <a href="#" onclick={delete} class="button">{ "Delete" }</a>
+                        }
                     </div>
                 </div>
             </div>
@@ -169,16 +552,12 @@ pub fn InitEditor() -> Html {
 
     html! {
         <main id="input">
-            if let Some(msg) = &error {
-                <div id="error">{ "Error! " } { msg }</div>
-            }
-
             <h3>{ "Select a configuration file to edit" }</h3>
 
             <div class="new-config-row">
                 <a class={classes!(
                     "button",
-                    new_config_name.is_empty().then(|| Some("disabled"))
+                    new_config_name.is_empty().then_some(Some("disabled"))
                 )} href="#" onclick={oncreatenew}>
                     { "Or, create a new one:" }
                 </a>
@@ -191,11 +570,121 @@ pub fn InitEditor() -> Html {
                 />
             </div>
 
+            <details class="import-config" ref={import_details}>
+                <summary>{ "Import a configuration" }</summary>
+
+                <input
+                    value={(*import_name).clone()}
+                    oninput={set_import_name}
+                    placeholder="Name for the imported configuration"
+                />
+
+                <textarea
+                    placeholder="Paste a generated configuration; one exported with its editor data comes back exactly"
+                    value={(*import_text).clone()}
+                    oninput={set_import_text}
+                />
+
+                <a class={classes!(
+                    "button",
+                    import_text.trim().is_empty().then_some("disabled")
+                )} href="#" onclick={onimport}>
+                    { "Import from text" }
+                </a>
+
+                if let Some(error) = &*import_error {
+                    <div class="error">{ error }</div>
+                }
+
+                <label class="import-file">
+                    { "Or import a file: " }
+                    <input type="file" accept=".yaml,.yml" onchange={onimport_file} />
+                </label>
+            </details>
+
+            <div class="library-backup">
+                if config_len > 0 {
+                    <a
+                        href="#"
+                        class="button"
+                        onclick={export_all}
+                        title="Download every configuration in this browser, to keep in case its storage is cleared"
+                    >
+                        { "Export all" }
+                    </a>
+                }
+
+                <label title="Adds the configurations of a backup, renaming any whose names are already taken">
+                    { "Import all from a backup: " }
+                    <input type="file" accept=".json" onchange={import_all} />
+                </label>
+
+                if let Some(error) = &*backup_error {
+                    <div class="error">{ error }</div>
+                }
+            </div>
+
+            if config_len > 0 {
+                <div class="library-check">
+                    <a href="#" class="button" onclick={check_library}>
+                        { "Validate all configurations" }
+                    </a>
+
+                    <label title="Asks before opening another configuration or going back to this page">
+                        <input type="checkbox" checked={*leave_warning} onchange={set_leave_warning} />
+                        { "Warn before leaving a configuration with errors" }
+                    </label>
+
+                    if let Some(checks) = &*library_checks {
+                        <span>
+                            { match invalid_count {
+                                0 => format!("All {} configurations are valid. ", checks.len()),
+                                1 => format!("1 of {} configurations has errors. ", checks.len()),
+                                n => format!("{n} of {} configurations have errors. ", checks.len()),
+                            } }
+                            <a href="#" onclick={dismiss_library_checks}>{ "Dismiss" }</a>
+                        </span>
+
+                        <table>
+                            <tr>
+                                <th>{ "Name" }</th>
+                                <th>{ "Valid?" }</th>
+                                <th>{ "Errors" }</th>
+                            </tr>
+                            { for library_check_rows }
+                        </table>
+                    }
+                </div>
+            }
+
             <div class="configs">
                 { for configs }
 
                 if config_len == 0 {
-                    <i>{ "No configurations yet" }</i>
+                    <div class="empty-state">
+                        <h4>{ "No configurations yet" }</h4>
+
+                        <p>
+                            { "A configuration describes the teams, machines, and services the scoring engine will check. Building one goes like this:" }
+                        </p>
+
+                        <ol>
+                            <li>{ "Create a configuration and give it a name, or import one generated before" }</li>
+                            <li><b>{ "Teams: " }</b>{ "add the red, white, and blue teams and their users" }</li>
+                            <li><b>{ "Machines: " }</b>{ "add each machine and drag the services it runs onto it" }</li>
+                            <li><b>{ "IP Settings: " }</b>{ "choose how every blue team's IP addresses are generated" }</li>
+                            <li><b>{ "Generated config: " }</b>{ "copy the result into the scoring engine" }</li>
+                        </ol>
+
+                        <div class="empty-state-buttons">
+                            <a href="#" class="button" onclick={create_first}>
+                                { "Create a configuration" }
+                            </a>
+                            <a href="#" class="button" onclick={open_import}>
+                                { "Import a configuration" }
+                            </a>
+                        </div>
+                    </div>
                 }
             </div>
         </main>
@@ -0,0 +1,200 @@
+// inventory.rs: Builds machines out of a plain list of hosts, such as an inventory
+// spreadsheet or a hosts file
+//
+// Copyright (C) 2023 Andrew Rioux
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::net::IpAddr;
+
+use web_sys::HtmlTextAreaElement;
+use yew::prelude::*;
+
+use crate::{
+    config::MachineEditor,
+    machines::palette,
+    state::{EditorMessage, EditorStateContext},
+};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct InventoryHost {
+    pub hostname: String,
+    pub address: String,
+    pub services: Vec<String>,
+}
+
+/// Reads hosts out of lines like `web 10.0.0.5 http,ssh`, where the services are
+/// optional. Lines from a hosts file, which put the address first and follow it with
+/// aliases rather than services, are read too. Blank lines and `#` comments are skipped
+pub fn parse_inventory(text: &str) -> Vec<InventoryHost> {
+    let mut hosts = vec![];
+
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut words = line.split_whitespace();
+
+        let (Some(first), Some(second)) = (words.next(), words.next()) else {
+            continue;
+        };
+
+        if first.parse::<IpAddr>().is_ok() {
+            hosts.push(InventoryHost {
+                hostname: second.to_owned(),
+                address: first.to_owned(),
+                services: vec![],
+            });
+            continue;
+        }
+
+        // Spreadsheets tend to put a space after each comma, so the rest of the line is
+        // one list of services rather than a word per service
+        let services = words
+            .collect::<Vec<_>>()
+            .join(" ")
+            .split(',')
+            .map(str::trim)
+            .filter(|service| !service.is_empty())
+            .map(str::to_owned)
+            .collect();
+
+        hosts.push(InventoryHost {
+            hostname: first.to_owned(),
+            address: second.to_owned(),
+            services,
+        });
+    }
+
+    hosts
+}
+
+/// A machine for a listed host, with a service for every listed name that matches the
+/// name or check of one of the services in the service list, along with the names
+/// nothing matched
+pub fn machine_from_inventory(host: &InventoryHost) -> (MachineEditor, Vec<String>) {
+    let palette = palette();
+    let mut unknown_services = vec![];
+
+    let services = host
+        .services
+        .iter()
+        .filter_map(|name| {
+            let service = palette
+                .iter()
+                .find(|service| {
+                    service.name.eq_ignore_ascii_case(name)
+                        || service.definition.check_name().eq_ignore_ascii_case(name)
+                })
+                .cloned();
+            if service.is_none() {
+                unknown_services.push(name.clone());
+            }
+            service
+        })
+        .collect();
+
+    let name = host
+        .hostname
+        .split('.')
+        .next()
+        .filter(|name| !name.is_empty())
+        .unwrap_or(&host.hostname);
+
+    let machine = MachineEditor {
+        name: name.to_owned(),
+        services,
+        ip_template: host.address.clone(),
+        ..MachineEditor::default()
+    };
+
+    (machine, unknown_services)
+}
+
+#[function_component]
+pub fn InventoryImport() -> Html {
+    let editor_state = use_context::<EditorStateContext>().unwrap();
+
+    let inventory = use_state(String::new);
+
+    let on_input = {
+        let inventory = inventory.clone();
+        Callback::from(move |e: InputEvent| {
+            inventory.set(e.target_unchecked_into::<HtmlTextAreaElement>().value());
+        })
+    };
+
+    let machines = parse_inventory(&inventory)
+        .iter()
+        .map(machine_from_inventory)
+        .collect::<Vec<_>>();
+
+    let add_machines = {
+        let editor_state = editor_state.clone();
+        let machines = machines.clone();
+        let inventory = inventory.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            for (machine, _) in &machines {
+                editor_state.dispatch(EditorMessage::AddMachine(machine.clone()));
+            }
+            inventory.set(String::new());
+        })
+    };
+
+    html! {
+        <details class="scan-import">
+            <summary>{ "Import machines from an inventory" }</summary>
+
+            <textarea
+                placeholder="One host per line, as hostname, address, then any services separated by commas: web 10.X.1.5 http,ssh"
+                value={inventory.to_string()}
+                oninput={on_input}
+            />
+
+            if !inventory.trim().is_empty() {
+                if machines.is_empty() {
+                    <p>{ "No hosts were found in the inventory" }</p>
+                } else {
+                    <ul>
+                        { for machines.iter().map(|(machine, unknown_services)| html! {
+                            <li>
+                                <strong>{ format!("{} ({}): ", machine.name, machine.ip_template) }</strong>
+                                if machine.services.is_empty() {
+                                    { "no services" }
+                                } else {
+                                    { machine.services.iter().map(|service| service.name.as_str()).collect::<Vec<_>>().join(", ") }
+                                }
+                                if !unknown_services.is_empty() {
+                                    <span class="scan-unknown-ports">
+                                        { format!(
+                                            "; no service is called {}, so add those by hand",
+                                            unknown_services.join(", ")
+                                        ) }
+                                    </span>
+                                }
+                            </li>
+                        }) }
+                    </ul>
+
+                    <a href="#" class="button edit-action" onclick={add_machines}>
+                        { match machines.len() {
+                            1 => "Add 1 machine".to_owned(),
+                            n => format!("Add {n} machines"),
+                        } }
+                    </a>
+                }
+            }
+        </details>
+    }
+}
@@ -18,14 +18,17 @@
 use web_sys::HtmlInputElement;
 use yew::prelude::*;
 
-use crate::{config::IpGeneratorScheme, state};
+use crate::{
+    config::{cidr_hosts, ip_collisions, IpGeneratorScheme},
+    machines::parse_whole_number,
+    state,
+};
 
 #[function_component]
 pub fn IpSettingsEditor() -> Html {
     let editor_state = use_context::<crate::state::EditorStateContext>().unwrap();
     let editor_state_c = editor_state.force_init();
     let machine_count = editor_state_c.0.machines.len();
-    let error = editor_state.error();
 
     let offsetreplace_state = use_state(|| "".to_string());
 
@@ -73,6 +76,19 @@ pub fn IpSettingsEditor() -> Html {
         })
     };
 
+    let base_template_ref = use_node_ref();
+
+    let set_base_template = {
+        let base_template_ref = base_template_ref.clone();
+        let editor_state = editor_state.clone();
+
+        Callback::from(move |_| {
+            if let Some(input) = base_template_ref.cast::<HtmlInputElement>() {
+                editor_state.dispatch(state::EditorMessage::UpdateBaseIpTemplate(input.value()));
+            }
+        })
+    };
+
     let set_multiplier = {
         let input_node_ref = input_node_ref.clone();
         let editor_state = editor_state.clone();
@@ -81,7 +97,7 @@ pub fn IpSettingsEditor() -> Html {
             if let Some(input) = input_node_ref.cast::<HtmlInputElement>() {
                 let value = input.value();
 
-                match value.parse::<u8>() {
+                match parse_whole_number(&value, 0, u8::MAX, "The multiplier") {
                     Ok(mult) if (mult as usize) < machine_count => {
                         editor_state.dispatch(state::EditorMessage::Error(format!(
                             "Multiplier ({mult}) must be higher than the current machine count ({machine_count})"
@@ -90,22 +106,103 @@ pub fn IpSettingsEditor() -> Html {
                     Ok(mult) => editor_state.dispatch(state::EditorMessage::UpdateIpSettings(
                         IpGeneratorScheme::ReplaceXWithIdTimesMultiplierPlusOffset { multiplier: mult },
                     )),
-                    Err(e) => editor_state.dispatch(state::EditorMessage::Error(format!(
-                        "Unable to parse input: {e:?}"
-                    ))),
+                    Err(e) => editor_state.dispatch(state::EditorMessage::Error(e)),
                 }
             }
         })
     };
 
+    let cidr_ref = use_node_ref();
+
+    let team_count = editor_state_c.0.blue_teams.len();
+
+    let set_cidr = {
+        let cidr_ref = cidr_ref.clone();
+        let editor_state = editor_state.clone();
+
+        Callback::from(move |_| {
+            let Some(input) = cidr_ref.cast::<HtmlInputElement>() else {
+                return;
+            };
+            let value = input.value();
+            let Some((base, prefix)) = value.trim().split_once('/') else {
+                editor_state.dispatch(state::EditorMessage::Error(format!(
+                    "'{value}' needs a prefix length, such as 10.0.0.0/24"
+                )));
+                return;
+            };
+            let prefix = match parse_whole_number(prefix, 0, 30, "The prefix length") {
+                Ok(prefix) => prefix,
+                Err(e) => {
+                    editor_state.dispatch(state::EditorMessage::Error(e));
+                    return;
+                }
+            };
+
+            match cidr_hosts(base, prefix) {
+                Ok((_, hosts)) if (hosts as usize) < machine_count * team_count => {
+                    editor_state.dispatch(state::EditorMessage::Error(format!(
+                        "{value} only has room for {hosts} hosts, but there are {machine_count} machines for each of {team_count} teams"
+                    )))
+                }
+                Ok(_) => editor_state.dispatch(state::EditorMessage::UpdateIpSettings(
+                    IpGeneratorScheme::Cidr {
+                        base: base.trim().to_owned(),
+                        prefix,
+                    },
+                )),
+                Err(e) => editor_state.dispatch(state::EditorMessage::Error(e.to_string())),
+            }
+        })
+    };
+
+    let cidr_class = Some("selected").filter(|_| {
+        matches!(
+            editor_state_c.0.ip_generator,
+            IpGeneratorScheme::Cidr { .. }
+        )
+    });
+
+    // Under the manual scheme there's only the one team, whose addresses are already
+    // checked against each other during conversion
+    let collisions = if matches!(editor_state_c.0.ip_generator, IpGeneratorScheme::OneTeam) {
+        vec![]
+    } else {
+        ip_collisions(editor_state_c.0)
+    };
+
     html! {
         <main id="ipsettings">
+            <crate::machines::MissingOffsets on_machines_page=false />
+            <crate::machines::DuplicateOffsets />
+
+            if !collisions.is_empty() {
+                <div class="ip-collisions">
+                    <h4>{ "Address collisions" }</h4>
+
+                    <p>{ "These addresses are given to more than one machine once every blue team's addresses are worked out:" }</p>
+
+                    <ul>
+                        { for collisions.iter().map(|collision| html! {
+                            <li>
+                                <strong>{ &collision.host } { ": " }</strong>
+                                { collision.uses
+                                    .iter()
+                                    .map(|(team, machine)| format!("{machine} for team {team}"))
+                                    .collect::<Vec<_>>()
+                                    .join(", ") }
+                            </li>
+                        }) }
+                    </ul>
+                </div>
+            }
+
             <div class={classes!("ipoption", "manual", manual_class)}>
                 <div class="settingheader">
                     <h3>{ "Manual configuration" }</h3>
 
                     <div class="button-box">
-                        <a href="#" onclick={set_manual}>
+                        <a href="#" class="edit-action" onclick={set_manual}>
                             { "Select" }
                         </a>
                     </div>
@@ -121,7 +218,7 @@ pub fn IpSettingsEditor() -> Html {
                     <h3>{ "Simple ID substitution" }</h3>
 
                     <div class="button-box">
-                        <a href="#" onclick={set_dumb_replace}>{ "Select" }</a>
+                        <a href="#" class="edit-action" onclick={set_dumb_replace}>{ "Select" }</a>
                     </div>
                 </div>
 
@@ -132,16 +229,12 @@ pub fn IpSettingsEditor() -> Html {
                 </div>
             </div>
 
-            if let Some(msg) = error {
-                <div id="error">{ "Error! " } { msg }</div>
-            }
-
             <div class={classes!("ipoption", "offsetreplace")}>
                 <div class="settingheader">
                     <h3>{ "ID Offset Multiplier" }</h3>
 
                     <div class="button-box">
-                        <a href="#" onclick={set_multiplier}>{ "Select" }</a>
+                        <a href="#" class="edit-action" onclick={set_multiplier}>{ "Select" }</a>
                     </div>
                 </div>
 
@@ -175,6 +268,66 @@ pub fn IpSettingsEditor() -> Html {
                     </div>
                 </div>
             </div>
+
+            <div class={classes!("ipoption", "cidr", cidr_class)}>
+                <div class="settingheader">
+                    <h3>{ "Network range" }</h3>
+
+                    <div class="button-box">
+                        <a href="#" class="edit-action" onclick={set_cidr}>{ "Select" }</a>
+                    </div>
+                </div>
+
+                <div class="description">
+                    <p>
+                        { "Every machine is given the next free host of a network, without any templates. The first team gets the first hosts, one per machine in the order they're listed, followed by the second team, and so on; with 3 machines, 10.0.0.0/24 gives the first team 10.0.0.1-10.0.0.3 and the second 10.0.0.4-10.0.0.6." }
+                    </p>
+
+                    <p>
+                        { "Adding, removing or reordering machines or teams moves the addresses of the ones after them." }
+                    </p>
+                </div>
+
+                <div class="form">
+                    <label>{ "Network" }</label>
+
+                    <div>
+                        <input
+                            ref={cidr_ref}
+                            value={match &editor_state_c.0.ip_generator {
+                                IpGeneratorScheme::Cidr { base, prefix } => format!("{base}/{prefix}"),
+                                _ => String::new(),
+                            }}
+                            placeholder="10.0.0.0/24"
+                        />
+                    </div>
+                </div>
+            </div>
+
+            <div class={classes!("ipoption", "basetemplate")}>
+                <div class="settingheader">
+                    <h3>{ "Base IP template" }</h3>
+                </div>
+
+                <div class="description">
+                    <p>
+                        { "Machines that leave their IP template empty and specify a host on the base template get the base template followed by that host, e.g. a base of 10.0.X. with a host of 5 gives 10.0.X.5. Machines with their own template keep using it." }
+                    </p>
+                </div>
+
+                <div class="form">
+                    <label>{ "Base template" }</label>
+
+                    <div>
+                        <input
+                            ref={base_template_ref}
+                            value={editor_state_c.0.base_ip_template.clone()}
+                            placeholder="10.0.X."
+                            onchange={set_base_template}
+                        />
+                    </div>
+                </div>
+            </div>
         </main>
     }
 }
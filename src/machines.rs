@@ -15,11 +15,15 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use web_sys::HtmlInputElement;
+use std::fmt::Display;
+
+use web_sys::{window, HtmlInputElement, HtmlTextAreaElement};
 use yew::prelude::*;
 
 use crate::{
     config::{self, MachineEditor},
+    inventory::InventoryImport,
+    nmap::ScanImport,
     state,
 };
 
@@ -28,6 +32,91 @@ macro_rules! count_properties {
     ($p:ident,$($p2:ident,)*) => (1usize + count_properties!($($p2,)*));
 }
 
+/// Parses a whole number typed into an input, with a message fit to show the user
+/// when it isn't one or falls outside `min..=max`
+pub fn parse_whole_number<T>(value: &str, min: T, max: T, field: &str) -> Result<T, String>
+where
+    T: Copy + Display + Into<i64> + TryFrom<i64>,
+{
+    let error = || format!("{field} must be a whole number between {min} and {max}");
+
+    let value = value.trim().parse::<i64>().map_err(|_| error())?;
+    if value < min.into() || value > max.into() {
+        return Err(error());
+    }
+
+    T::try_from(value).map_err(|_| error())
+}
+
+/// Reads a whole number out of a numeric input, clamping it to `min..=u16::MAX` and
+/// writing the clamped value back so the input shows what was actually stored
+fn read_u16_input(input: &HtmlInputElement, min: u16, field: &str) -> Result<u16, String> {
+    let value = input.value().trim().parse::<i64>().map_err(|_| {
+        format!(
+            "{field} must be a whole number between {min} and {}",
+            u16::MAX
+        )
+    })?;
+    let clamped = value.clamp(min.into(), u16::MAX.into()) as u16;
+
+    if i64::from(clamped) != value {
+        input.set_value(&clamped.to_string());
+    }
+
+    Ok(clamped)
+}
+
+/// Reads a name out of an input without any leading or trailing whitespace, writing
+/// the trimmed value back so stray spaces don't linger in the input either
+pub fn read_trimmed_input(input: &HtmlInputElement) -> String {
+    let value = input.value();
+    let trimmed = value.trim();
+
+    if trimmed.len() != value.len() {
+        input.set_value(trimmed);
+    }
+
+    trimmed.to_owned()
+}
+
+/// Reads users out of text with one `username,password` per line, skipping blank
+/// lines. The password is everything after the first comma, so it may contain commas
+pub fn parse_user_lines(text: &str) -> Result<Vec<config::User>, String> {
+    let mut users: Vec<config::User> = vec![];
+
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let Some((username, password)) = line.split_once(',') else {
+            return Err(format!("line {}: expected username,password", i + 1));
+        };
+        let username = username.trim();
+
+        if username.is_empty() || password.is_empty() {
+            return Err(format!(
+                "line {}: both a username and a password are needed",
+                i + 1
+            ));
+        }
+
+        if users.iter().any(|user| user.username == username) {
+            return Err(format!(
+                "line {}: {username} is listed more than once",
+                i + 1
+            ));
+        }
+
+        users.push(config::User {
+            username: username.to_owned(),
+            password: password.to_owned(),
+        });
+    }
+
+    Ok(users)
+}
+
 macro_rules! define_service_environment_editor {
     ($service_definition_type:ty, $new_service:ident, ) => {
         mod environment_editor {
@@ -38,10 +127,7 @@ macro_rules! define_service_environment_editor {
             #[derive(Properties, PartialEq)]
             pub struct EditorProperties {
                 pub update_service: Callback<config::ServiceEditor>,
-                pub name: String,
-                pub port: u16,
-                pub points: u16,
-                pub accounts: Option<Vec<config::User>>,
+                pub service: config::ServiceEditor,
                 pub service_definition: $service_definition_type,
             }
 
@@ -49,20 +135,14 @@ macro_rules! define_service_environment_editor {
             pub fn Editor(props: &EditorProperties) -> Html {
                 let service_updater = || -> Box<dyn Fn($service_definition_type)> {
                     let update_service = props.update_service.clone();
-                    let name = props.name.clone();
-                    let port = props.port;
-                    let points = props.points;
-                    let accounts = props.accounts.clone();
+                    let service = props.service.clone();
 
                     Box::new(move |check| {
                         let new_service = config::ServiceEditor {
-                            name: name.clone(),
-                            port,
-                            points,
-                            accounts: accounts.clone(),
                             definition: config::ServiceDefinition::$new_service {
                                 environment: check,
                             },
+                            ..service.clone()
                         };
 
                         update_service.emit(new_service);
@@ -95,7 +175,27 @@ macro_rules! define_service_environment_editor {
                     Callback::from(move |_| {
                         let Some(input) = check_input_ref.cast::<HtmlInputElement>() else { return; };
 
-                        update_service(Some(input.value()));
+                        // Clearing the text goes back to the default check rather than
+                        // matching everything
+                        let value = input.value();
+                        update_service(Some(value).filter(|value| !value.trim().is_empty()));
+                    })
+                };
+
+                let expected_result_ref = use_node_ref();
+
+                let update_expected_result = {
+                    let expected_result_ref = expected_result_ref.clone();
+                    let update_service = props.update_service.clone();
+                    let service = props.service.clone();
+
+                    Callback::from(move |_| {
+                        let Some(input) = expected_result_ref.cast::<HtmlInputElement>() else { return; };
+
+                        update_service.emit(config::ServiceEditor {
+                            expected_result: input.value(),
+                            ..service.clone()
+                        });
                     })
                 };
 
@@ -131,6 +231,20 @@ macro_rules! define_service_environment_editor {
                                 </div>
                             </div>
                         }
+
+                        <div class="service-property">
+                            <div class="service-property-name">
+                                { "What passing means (answer key only):" }
+                            </div>
+
+                            <div class="service-property-value">
+                                <input
+                                    ref={expected_result_ref}
+                                    value={props.service.expected_result.clone()}
+                                    onchange={update_expected_result}
+                                />
+                            </div>
+                        </div>
                     </>
                 }
             }
@@ -139,6 +253,7 @@ macro_rules! define_service_environment_editor {
     ($service_definition_type:ty, $new_service:ident, $($property:ident => $property_name:expr),*) => {
         mod environment_editor {
             use crate::config;
+            use web_sys::HtmlInputElement;
             use yew::prelude::*;
 
             trait Extractor<T> {
@@ -152,6 +267,9 @@ macro_rules! define_service_environment_editor {
             #[derive(PartialEq, Clone)]
             pub struct Environment {
                 matching_content: String,
+                disabled: bool,
+                expected_result: String,
+                negate: bool,
                 $($property: String),*
             }
 
@@ -159,7 +277,12 @@ macro_rules! define_service_environment_editor {
             struct EnvironmentEditorProperties {
                 pub update_check: Callback<Environment>,
                 pub delete_check: Callback<()>,
-                pub environment: Environment
+                /// Swaps the check with the one before it, if there is one
+                pub move_up: Option<Callback<()>>,
+                /// Swaps the check with the one after it, if there is one
+                pub move_down: Option<Callback<()>>,
+                pub environment: Environment,
+                pub errors: Vec<config::FieldError>,
             }
 
             #[function_component]
@@ -175,6 +298,7 @@ macro_rules! define_service_environment_editor {
                             pub struct PropertyEditorProperties {
                                 pub update_check: Callback<Environment>,
                                 pub environment: Environment,
+                                pub error: Option<AttrValue>,
                             }
 
                             #[function_component]
@@ -204,6 +328,8 @@ macro_rules! define_service_environment_editor {
 
                                         <div>
                                             <input
+                                                class={classes!(props.error.is_some().then_some("invalid"))}
+                                                title={props.error.clone()}
                                                 value={props.environment.$property_ed.clone()}
                                                 ref={input_ref}
                                                 {onchange}
@@ -217,6 +343,7 @@ macro_rules! define_service_environment_editor {
                 }
 
                 create_editor!(matching_content => "Result to check");
+                create_editor!(expected_result => "What passing means (answer key only)");
 
                 $(
                     create_editor!($property => $property_name);
@@ -228,25 +355,131 @@ macro_rules! define_service_environment_editor {
                     Callback::from(move |_| delete_check.emit(()))
                 };
 
+                let move_up = props
+                    .move_up
+                    .clone()
+                    .map(|move_up| Callback::from(move |_| move_up.emit(())));
+
+                let move_down = props
+                    .move_down
+                    .clone()
+                    .map(|move_down| Callback::from(move |_| move_down.emit(())));
+
+                let enabled_ref = use_node_ref();
+
+                let toggle_enabled = {
+                    let environment = props.environment.clone();
+                    let update_check = props.update_check.clone();
+                    let enabled_ref = enabled_ref.clone();
+
+                    Callback::from(move |_| {
+                        let Some(input) = enabled_ref.cast::<HtmlInputElement>() else { return; };
+                        let mut new_environment = environment.clone();
+
+                        new_environment.disabled = !input.checked();
+
+                        update_check.emit(new_environment);
+                    })
+                };
+
+                let negate_ref = use_node_ref();
+
+                let toggle_negate = {
+                    let environment = props.environment.clone();
+                    let update_check = props.update_check.clone();
+                    let negate_ref = negate_ref.clone();
+
+                    Callback::from(move |_| {
+                        let Some(input) = negate_ref.cast::<HtmlInputElement>() else { return; };
+                        let mut new_environment = environment.clone();
+
+                        new_environment.negate = input.checked();
+
+                        update_check.emit(new_environment);
+                    })
+                };
+
+                let field_error = |field: &str| {
+                    props
+                        .errors
+                        .iter()
+                        .find(|err| err.field == field)
+                        .map(|err| AttrValue::from(err.message.clone()))
+                };
+
                 html! {
-                    <div class="service-check">
+                    <div class={classes!("service-check", props.environment.disabled.then_some("disabled"))}>
+                        <div class="service-check-row">
+                            <div>
+                                { "Enabled" }
+                            </div>
+
+                            <div>
+                                <input
+                                    ref={enabled_ref}
+                                    type="checkbox"
+                                    checked={!props.environment.disabled}
+                                    onchange={toggle_enabled}
+                                />
+                            </div>
+                        </div>
+
                         <matching_content::PropertyEditor
                             update_check={props.update_check.clone()}
                             environment={props.environment.clone()}
+                            error={field_error("matching_content")}
                         />
 
+                        <div class="service-check-row">
+                            <div>
+                                { "Pass when the result is missing" }
+                            </div>
+
+                            <div>
+                                <input
+                                    ref={negate_ref}
+                                    type="checkbox"
+                                    class={classes!(field_error("negate").is_some().then_some("invalid"))}
+                                    title={field_error("negate")}
+                                    checked={props.environment.negate}
+                                    onchange={toggle_negate}
+                                />
+                            </div>
+                        </div>
+
                         $(
                             <$property::PropertyEditor
                                 update_check={props.update_check.clone()}
                                 environment={props.environment.clone()}
+                                error={field_error(stringify!($property))}
                             />
                         )*
 
+                        <expected_result::PropertyEditor
+                            update_check={props.update_check.clone()}
+                            environment={props.environment.clone()}
+                            error={None::<AttrValue>}
+                        />
+
                         <div class="service-check-row">
                             <div />
 
                             <div>
-                                <a href="#" onclick={delete_check}>
+                                if let Some(move_up) = move_up {
+                                    <a href="#" class="edit-action" onclick={move_up}>
+                                        { "Move up" }
+                                    </a>
+                                    { " " }
+                                }
+
+                                if let Some(move_down) = move_down {
+                                    <a href="#" class="edit-action" onclick={move_down}>
+                                        { "Move down" }
+                                    </a>
+                                    { " " }
+                                }
+
+                                <a href="#" class="edit-action" onclick={delete_check}>
                                     { "Delete check" }
                                 </a>
                             </div>
@@ -258,10 +491,7 @@ macro_rules! define_service_environment_editor {
             #[derive(Properties, PartialEq)]
             pub struct EditorProperties {
                 pub update_service: Callback<config::ServiceEditor>,
-                pub name: String,
-                pub port: u16,
-                pub points: u16,
-                pub accounts: Option<Vec<config::User>>,
+                pub service: config::ServiceEditor,
                 pub service_definition: $service_definition_type,
             }
 
@@ -269,23 +499,17 @@ macro_rules! define_service_environment_editor {
             pub fn Editor(props: &EditorProperties) -> Html {
                 let service_updater = || -> Box<dyn Fn(&dyn Fn(&mut $service_definition_type))> {
                     let update_service = props.update_service.clone();
-                    let name = props.name.clone();
-                    let port = props.port;
-                    let points = props.points;
-                    let accounts = props.accounts.clone();
+                    let service = props.service.clone();
                     let checks = props.service_definition.clone();
 
                     Box::new(move |update_checks| {
                         let mut new_checks = checks.clone();
                         (update_checks)(&mut new_checks);
                         let new_service = config::ServiceEditor {
-                            name: name.clone(),
-                            port,
-                            points,
-                            accounts: accounts.clone(),
                             definition: config::ServiceDefinition::$new_service {
                                 environment: new_checks,
                             },
+                            ..service.clone()
                         };
 
                         update_service.emit(new_service);
@@ -294,15 +518,43 @@ macro_rules! define_service_environment_editor {
 
                 let add_check = {
                     let service_updater = service_updater();
+                    let default_matching_content = config::ServiceDefinition::$new_service {
+                        environment: vec![],
+                    }
+                    .default_matching_content()
+                    .unwrap_or_default();
 
                     Callback::from(move |_| {
                         service_updater(&|checks| {
-                            checks.push(<$service_definition_type as Extractor<_>>::Item::default());
+                            let mut check = <$service_definition_type as Extractor<_>>::Item::default();
+                            check.matching_content = default_matching_content.to_owned();
+                            checks.push(check);
                         })
                     })
                 };
 
+                let editor_state = use_context::<crate::state::EditorStateContext>().unwrap();
+                let field_errors = config::ServiceDefinition::$new_service {
+                    environment: props.service_definition.clone(),
+                }
+                .field_errors(editor_state.force_init().0.engine_version);
+
+                let check_count = props.service_definition.len();
+
                 let checks = props.service_definition.iter().enumerate().map(|(i, environment)| {
+                    let swap_with = |other: usize| {
+                        let service_updater = service_updater();
+
+                        Callback::from(move |()| {
+                            service_updater(&|checks| {
+                                checks.swap(i, other);
+                            })
+                        })
+                    };
+
+                    let move_up = (i > 0).then(|| swap_with(i - 1));
+                    let move_down = (i + 1 < check_count).then(|| swap_with(i + 1));
+
                     let delete_check = {
                         let service_updater = service_updater();
 
@@ -320,6 +572,9 @@ macro_rules! define_service_environment_editor {
                             let update = update.clone();
                             service_updater(&|checks| {
                                 checks[i].matching_content = update.matching_content.clone();
+                                checks[i].disabled = update.disabled;
+                                checks[i].expected_result = update.expected_result.clone();
+                                checks[i].negate = update.negate;
                                 $(
                                     checks[i].$property = update.$property.clone();
                                 )*
@@ -331,8 +586,14 @@ macro_rules! define_service_environment_editor {
                         <EnvironmentEditor
                             {update_check}
                             {delete_check}
+                            {move_up}
+                            {move_down}
+                            errors={field_errors.iter().filter(|err| err.environment == i).cloned().collect::<Vec<_>>()}
                             environment={Environment {
                                 matching_content: environment.matching_content.clone(),
+                                disabled: environment.disabled,
+                                expected_result: environment.expected_result.clone(),
+                                negate: environment.negate,
                                 $($property: environment.$property.clone()),*
                             }}
                         />
@@ -341,7 +602,7 @@ macro_rules! define_service_environment_editor {
 
                 html! {
                     <>
-                        <a href="#" onclick={add_check} class="add-service">
+                        <a href="#" onclick={add_check} class="add-service edit-action">
                             { "Add check" }
                         </a>
 
@@ -400,6 +661,27 @@ macro_rules! setup_service {
             use yew::prelude::*;
             use web_sys::HtmlInputElement;
 
+            /// A service of this type as it is first dropped onto a machine
+            pub fn new_service() -> ServiceEditor {
+                ServiceEditor {
+                    name: $new_name.to_string(),
+                    port: $new_port,
+                    points: $new_points,
+                    accounts: $new_accounts,
+                    definition: config::ServiceDefinition::$new_service {
+                        environment: $new_service_params
+                    },
+                    inline_accounts: false,
+                    excluded_teams: Default::default(),
+                    allow_unusual_port: false,
+                    interval: None,
+                    timeout: None,
+                    informational: false,
+                    expected_result: String::new(),
+                    locked_fields: Default::default(),
+                }
+            }
+
             #[derive(Properties, PartialEq)]
             pub struct NewServiceComponentProps {
                 pub name_filter: AttrValue,
@@ -413,15 +695,7 @@ macro_rules! setup_service {
                     let handle_pickup = props.handle_pickup.clone();
 
                     Callback::from(move |_| {
-                        handle_pickup.emit(ServiceEditor {
-                            name: $new_name.to_string(),
-                            port: $new_port,
-                            points: $new_points,
-                            accounts: $new_accounts,
-                            definition: config::ServiceDefinition::$new_service {
-                                environment: $new_service_params
-                            },
-                        });
+                        handle_pickup.emit(new_service());
                     })
                 };
 
@@ -433,11 +707,14 @@ macro_rules! setup_service {
                     })
                 };
 
+                let category = new_service().definition.category();
+
                 html! {
                     <div
                         draggable={"true"}
                         class={classes!(
                             "new-service",
+                            category.class(),
                             Some("hidden").filter(|_| !$pretty_name.to_lowercase().contains(&props.name_filter.to_lowercase()))
                         )}
                         {ondragstart}
@@ -445,6 +722,7 @@ macro_rules! setup_service {
                     >
                         <h3>
                             { $pretty_name }
+                            <span class="category-tag">{ category.label() }</span>
                         </h3>
 
                         <div class="service-details">
@@ -494,10 +772,8 @@ macro_rules! setup_service {
             pub struct ServiceEditorProps {
                 pub update_service: Callback<config::ServiceEditor>,
                 pub delete_service: Callback<()>,
-                pub name: String,
-                pub port: u16,
-                pub points: u16,
-                pub accounts: Option<Vec<config::User>>,
+                pub host_error: Option<AttrValue>,
+                pub service: config::ServiceEditor,
                 pub service_definition: $service_definition_type
             }
 
@@ -511,6 +787,74 @@ macro_rules! setup_service {
 
                 let service_editor_error = use_state(Option::<AttrValue>::default);
 
+                let reset_service = {
+                    let update_service = props.update_service.clone();
+                    let service = props.service.clone();
+
+                    Callback::from(move |e: MouseEvent| {
+                        e.prevent_default();
+                        let defaults = super::default_service(&service.definition);
+                        let mut service = service.clone();
+
+                        if service.bulk_set_port(defaults.port) {
+                            service.allow_unusual_port = false;
+                        }
+                        service.bulk_set_points(defaults.points);
+
+                        update_service.emit(config::ServiceEditor {
+                            accounts: defaults.accounts,
+                            ..service
+                        });
+                    })
+                };
+
+                let toggle_lock = |field: config::LockableField| {
+                    let update_service = props.update_service.clone();
+                    let service = props.service.clone();
+
+                    Callback::from(move |e: Event| {
+                        let input = e.target_unchecked_into::<HtmlInputElement>();
+                        let mut locked_fields = service.locked_fields.clone();
+
+                        if input.checked() {
+                            locked_fields.insert(field);
+                        } else {
+                            locked_fields.remove(&field);
+                        }
+
+                        update_service.emit(config::ServiceEditor {
+                            locked_fields,
+                            ..service.clone()
+                        });
+                    })
+                };
+
+                let clear_port = {
+                    let update_service = props.update_service.clone();
+                    let service = props.service.clone();
+
+                    Callback::from(move |e: MouseEvent| {
+                        e.prevent_default();
+                        update_service.emit(config::ServiceEditor {
+                            port: 0,
+                            ..service.clone()
+                        });
+                    })
+                };
+
+                let allow_unusual_port = {
+                    let update_service = props.update_service.clone();
+                    let service = props.service.clone();
+
+                    Callback::from(move |e: MouseEvent| {
+                        e.prevent_default();
+                        update_service.emit(config::ServiceEditor {
+                            allow_unusual_port: true,
+                            ..service.clone()
+                        });
+                    })
+                };
+
                 #[derive(Copy, Clone)]
                 enum Tabs {
                     Essentials,
@@ -534,29 +878,21 @@ macro_rules! setup_service {
                     let service_editor_error = service_editor_error.clone();
                     let service_port_ref = service_port_ref.clone();
                     let update_service = props.update_service.clone();
-                    let name = props.name.clone();
-                    let points = props.points;
-                    let accounts = props.accounts.clone();
-                    let service = props.service_definition.clone();
+                    let service = props.service.clone();
 
                     Callback::from(move |_| {
                         let Some(input) = service_port_ref.cast::<HtmlInputElement>() else { return; };
 
-                        match input.value().parse::<u16>() {
+                        match super::read_u16_input(&input, 1, "Port") {
                             Ok(port) => {
                                 service_editor_error.set(None);
                                 update_service.emit(config::ServiceEditor {
-                                    name: name.clone(),
                                     port,
-                                    points,
-                                    accounts: accounts.clone(),
-                                    definition: config::ServiceDefinition::$new_service {
-                                        environment: service.clone()
-                                    }
+                                    ..service.clone()
                                 });
                             }
                             Err(e) => {
-                                service_editor_error.set(Some(format!("Error parsing service port: {e:?}").into()));
+                                service_editor_error.set(Some(e.into()));
                             }
                         }
                     })
@@ -568,29 +904,64 @@ macro_rules! setup_service {
                     let service_editor_error = service_editor_error.clone();
                     let service_points_ref = service_points_ref.clone();
                     let update_service = props.update_service.clone();
-                    let name = props.name.clone();
-                    let port = props.port;
-                    let accounts = props.accounts.clone();
-                    let service = props.service_definition.clone();
+                    let service = props.service.clone();
 
                     Callback::from(move |_| {
                         let Some(input) = service_points_ref.cast::<HtmlInputElement>() else { return; };
 
-                        match input.value().parse::<u16>() {
+                        match super::read_u16_input(&input, 0, "Points") {
                             Ok(points) => {
                                 service_editor_error.set(None);
                                 update_service.emit(config::ServiceEditor {
-                                    name: name.clone(),
-                                    port,
                                     points,
-                                    accounts: accounts.clone(),
-                                    definition: config::ServiceDefinition::$new_service {
-                                        environment: service.clone()
-                                    }
+                                    ..service.clone()
                                 });
                             }
                             Err(e) => {
-                                service_editor_error.set(Some(format!("Error parsing service port: {e:?}").into()));
+                                service_editor_error.set(Some(e.into()));
+                            }
+                        }
+                    })
+                };
+
+                let toggle_informational = {
+                    let update_service = props.update_service.clone();
+                    let service = props.service.clone();
+
+                    Callback::from(move |e: Event| {
+                        let input = e.target_unchecked_into::<HtmlInputElement>();
+                        update_service.emit(config::ServiceEditor {
+                            informational: input.checked(),
+                            ..service.clone()
+                        });
+                    })
+                };
+
+                let set_duration = |field: &'static str, set: fn(&mut config::ServiceEditor, Option<u32>)| {
+                    let service_editor_error = service_editor_error.clone();
+                    let update_service = props.update_service.clone();
+                    let service = props.service.clone();
+
+                    Callback::from(move |e: Event| {
+                        let input = e.target_unchecked_into::<HtmlInputElement>();
+                        let value = input.value();
+                        let value = value.trim();
+
+                        let duration = if value.is_empty() {
+                            Ok(None)
+                        } else {
+                            super::parse_whole_number(value, 1, u32::MAX, &format!("{field} (in seconds)")).map(Some)
+                        };
+
+                        match duration {
+                            Ok(duration) => {
+                                service_editor_error.set(None);
+                                let mut service = service.clone();
+                                set(&mut service, duration);
+                                update_service.emit(service);
+                            }
+                            Err(e) => {
+                                service_editor_error.set(Some(e.into()));
                             }
                         }
                     })
@@ -601,57 +972,103 @@ macro_rules! setup_service {
                 let set_service_name = {
                     let service_name_ref = service_name_ref.clone();
                     let update_service = props.update_service.clone();
-                    let port = props.port;
-                    let points = props.points;
-                    let accounts = props.accounts.clone();
-                    let service = props.service_definition.clone();
+                    let service = props.service.clone();
 
                     Callback::from(move |_| {
                         let Some(input) = service_name_ref.cast::<HtmlInputElement>() else { return; };
                         let new_service = config::ServiceEditor {
-                            name: input.value().clone(),
-                            port,
-                            points,
-                            accounts: accounts.clone(),
-                            definition: config::ServiceDefinition::$new_service {
-                                environment: service.clone()
-                            }
+                            name: super::read_trimmed_input(&input),
+                            ..service.clone()
                         };
 
                         update_service.emit(new_service);
                     })
                 };
 
+                let inline_accounts_ref = use_node_ref();
+
+                let set_inline_accounts = {
+                    let inline_accounts_ref = inline_accounts_ref.clone();
+                    let update_service = props.update_service.clone();
+                    let service = props.service.clone();
+
+                    Callback::from(move |_| {
+                        let Some(input) = inline_accounts_ref.cast::<HtmlInputElement>() else { return; };
+
+                        update_service.emit(config::ServiceEditor {
+                            inline_accounts: input.checked(),
+                            ..service.clone()
+                        });
+                    })
+                };
+
                 let add_account = {
                     let update_service = props.update_service.clone();
-                    let name = props.name.clone();
-                    let port = props.port;
-                    let points = props.points;
-                    let accounts = props.accounts.clone();
-                    let service = props.service_definition.clone();
+                    let service = props.service.clone();
 
                     Callback::from(move |_| {
-                        let accounts = accounts.clone().map(|accounts| {
-                            let mut accounts = accounts.clone();
-                            accounts.push(config::User {
-                                username: "".to_owned(),
-                                password: "Chiapet1!".to_owned()
-                            });
-                            accounts
+                        let mut accounts = service.accounts.clone().unwrap_or_default();
+                        accounts.push(config::User {
+                            username: "".to_owned(),
+                            password: crate::passwords::DEFAULT_PASSWORD.to_owned()
                         });
+                        let accounts = Some(accounts);
                         let new_service = config::ServiceEditor {
-                            name: name.clone(),
-                            port,
-                            points,
                             accounts,
-                            definition: config::ServiceDefinition::$new_service {
-                                environment: service.clone()
-                            }
+                            ..service.clone()
                         };
                         update_service.emit(new_service);
                     })
                 };
 
+                let update_accounts = {
+                    let update_service = props.update_service.clone();
+                    let service = props.service.clone();
+
+                    Callback::from(move |accounts| {
+                        update_service.emit(config::ServiceEditor {
+                            accounts,
+                            ..service.clone()
+                        });
+                    })
+                };
+
+                let editor_state = use_context::<crate::state::EditorStateContext>().unwrap();
+                let blue_teams = editor_state.force_init().0.blue_teams.clone();
+
+                let team_toggles = blue_teams.iter().map(|team| {
+                    let update_service = props.update_service.clone();
+                    let service = props.service.clone();
+                    let id = team.id;
+
+                    let onchange = Callback::from(move |e: Event| {
+                        let input = e.target_unchecked_into::<HtmlInputElement>();
+                        let mut excluded_teams = service.excluded_teams.clone();
+
+                        if input.checked() {
+                            excluded_teams.remove(&id);
+                        } else {
+                            excluded_teams.insert(id);
+                        }
+
+                        update_service.emit(config::ServiceEditor {
+                            excluded_teams,
+                            ..service.clone()
+                        });
+                    });
+
+                    html! {
+                        <label key={id} class="service-team">
+                            <input
+                                type="checkbox"
+                                checked={!props.service.excluded_teams.contains(&id)}
+                                {onchange}
+                            />
+                            { format!("{} ({})", team.name, team.id) }
+                        </label>
+                    }
+                });
+
                 #[derive(Properties, PartialEq)]
                 struct AccountEditorProps {
                     pub update_user: Callback<config::User>,
@@ -661,6 +1078,7 @@ macro_rules! setup_service {
 
                 #[function_component]
                 fn AccountEditor(props: &AccountEditorProps) -> Html {
+                    let editor_state = use_context::<crate::state::EditorStateContext>().unwrap();
                     let username_ref = use_node_ref();
 
                     let username_change = {
@@ -671,7 +1089,7 @@ macro_rules! setup_service {
                         Callback::from(move |_| {
                             let Some(input) = username_ref.cast::<HtmlInputElement>() else { return; };
                             let mut new_user = user.clone();
-                            new_user.username = input.value();
+                            new_user.username = super::read_trimmed_input(&input);
                             update_user.emit(new_user);
                         })
                     };
@@ -691,6 +1109,23 @@ macro_rules! setup_service {
                         })
                     };
 
+                    let generate_password = {
+                        let editor_state = editor_state.clone();
+                        let update_user = props.update_user.clone();
+                        let user = props.user.clone();
+
+                        Callback::from(move |e: MouseEvent| {
+                            e.prevent_default();
+                            match crate::passwords::generate_password() {
+                                Ok(password) => update_user.emit(config::User {
+                                    password,
+                                    ..user.clone()
+                                }),
+                                Err(e) => editor_state.dispatch(crate::state::EditorMessage::Error(e)),
+                            }
+                        })
+                    };
+
                     let delete_user = {
                         let delete_user = props.delete_user.clone();
 
@@ -727,11 +1162,20 @@ macro_rules! setup_service {
                                 </div>
                             </div>
 
+                            if props.user.password == crate::passwords::DEFAULT_PASSWORD {
+                                <div class="service-hint">
+                                    { "This account still has the default password, which anyone can look up. " }
+                                    <a href="#" class="edit-action" onclick={generate_password}>
+                                        { "Generate a password" }
+                                    </a>
+                                </div>
+                            }
+
                             <div class="service-user-row">
                                 <div />
 
                                 <div>
-                                    <a href="#" onclick={delete_user}>
+                                    <a href="#" class="edit-action" onclick={delete_user}>
                                         { "Delete user" }
                                     </a>
                                 </div>
@@ -740,20 +1184,14 @@ macro_rules! setup_service {
                     }
                 }
 
-                let accounts = props.accounts.clone().unwrap_or(vec![]);
+                let accounts = props.service.accounts.clone().unwrap_or(vec![]);
                 let accounts = accounts.iter().enumerate().map(|(i, account)| {
                     let update_service = props.update_service.clone();
-                    let name = props.name.clone();
-                    let port = props.port;
-                    let points = props.points;
-                    let service = props.service_definition.clone();
-                    let accounts = props.accounts.clone();
+                    let service = props.service.clone();
+                    let accounts = props.service.accounts.clone();
 
                     let update_user = {
                         let update_service = update_service.clone();
-                        let name = name.clone();
-                        let port = port;
-                        let points = points;
                         let service = service.clone();
                         let accounts = accounts.clone();
 
@@ -764,22 +1202,14 @@ macro_rules! setup_service {
                                 new_accounts
                             });
                             update_service.emit(ServiceEditor {
-                                name: name.clone(),
-                                port,
-                                points,
-                                definition: config::ServiceDefinition::$new_service {
-                                    environment: service.clone()
-                                },
-                                accounts
+                                accounts,
+                                ..service.clone()
                             })
                         })
                     };
 
                     let delete_user = {
                         let update_service = update_service.clone();
-                        let name = name.clone();
-                        let port = port;
-                        let points = points;
                         let service = service.clone();
                         let accounts = accounts.clone();
 
@@ -790,13 +1220,8 @@ macro_rules! setup_service {
                                 new_accounts
                             });
                             update_service.emit(ServiceEditor {
-                                name: name.clone(),
-                                port,
-                                points,
-                                definition: config::ServiceDefinition::$new_service {
-                                    environment: service.clone()
-                                },
-                                accounts
+                                accounts,
+                                ..service.clone()
                             })
                         })
                     };
@@ -811,18 +1236,40 @@ macro_rules! setup_service {
                     }
                 });
 
+                let category = props.service.definition.category();
+
                 html! {
-                    <div class="machine-service">
+                    <div class={classes!(
+                        "machine-service",
+                        category.class(),
+                        props.service.informational.then_some("informational")
+                    )}>
                         <div class="machine-service-header">
                             <h3>
                                 { $pretty_name } { ":" }
+                                <span class="category-tag">{ category.label() }</span>
+                                if props.service.informational {
+                                    <span class="informational-tag">{ "Informational" }</span>
+                                }
                             </h3>
 
-                            <a href="#" onclick={delete_service}>
-                                { "Remove service" }
-                            </a>
+                            <div class="machine-service-buttons">
+                                <a href="#" class="edit-action" onclick={reset_service} title="Restore the default port, points and accounts of this service type, keeping any locked port or points">
+                                    { "Reset to defaults" }
+                                </a>
+
+                                <a href="#" class="edit-action" onclick={delete_service}>
+                                    { "Remove service" }
+                                </a>
+                            </div>
                         </div>
 
+                        if let Some(err) = &props.host_error {
+                            <div class="error">
+                                { err }
+                            </div>
+                        }
+
                         if let Some(err) = &*service_editor_error {
                             <div class="error">
                                 { err }
@@ -880,13 +1327,22 @@ macro_rules! setup_service {
                                     <div class="service-property-value">
                                         <input
                                             ref={service_name_ref}
-                                            value={props.name.clone()}
+                                            value={props.service.name.clone()}
                                             onchange={set_service_name}
                                         />
                                     </div>
                                 </div>
 
-                                if $new_port != 0 {
+                                if props.service.definition.is_portless() {
+                                    if props.service.port != 0 {
+                                        <div class="service-error">
+                                            { format!("{} checks don't use a port, but this one is set to port {}. ", $pretty_name, props.service.port) }
+                                            <a href="#" class="edit-action" onclick={clear_port}>
+                                                { "Clear the port" }
+                                            </a>
+                                        </div>
+                                    }
+                                } else {
                                     <div class="service-property">
                                         <div class="service-property-name">
                                             { "Service port:" }
@@ -895,11 +1351,33 @@ macro_rules! setup_service {
                                         <div class="service-property-value">
                                             <input
                                                 ref={service_port_ref}
-                                                value={props.port.to_string()}
+                                                type="number"
+                                                min="1"
+                                                max="65535"
+                                                step="1"
+                                                value={props.service.port.to_string()}
                                                 onchange={set_service_port}
                                             />
+
+                                            <label class="field-lock" title="Keep changes made to many services at once from changing the port">
+                                                <input
+                                                    type="checkbox"
+                                                    checked={props.service.is_locked(config::LockableField::Port)}
+                                                    onchange={toggle_lock(config::LockableField::Port)}
+                                                />
+                                                { "Locked" }
+                                            </label>
                                         </div>
                                     </div>
+
+                                    if props.service.port != $new_port && !props.service.allow_unusual_port {
+                                        <div class="service-hint">
+                                            { format!("Unusual port for {}; {} is conventional. ", $pretty_name, $new_port) }
+                                            <a href="#" class="edit-action" onclick={allow_unusual_port}>
+                                                { "This is intended" }
+                                            </a>
+                                        </div>
+                                    }
                                 }
 
                                 <div class="service-property">
@@ -910,27 +1388,103 @@ macro_rules! setup_service {
                                     <div class="service-property-value">
                                         <input
                                             ref={service_points_ref}
-                                            value={props.points.to_string()}
+                                            type="number"
+                                            min="0"
+                                            max="65535"
+                                            step="1"
+                                            value={props.service.points.to_string()}
                                             onchange={set_service_points}
+                                            disabled={props.service.informational}
                                         />
+
+                                        <label class="field-lock" title="Keep changes made to many services at once from changing the points">
+                                            <input
+                                                type="checkbox"
+                                                checked={props.service.is_locked(config::LockableField::Points)}
+                                                onchange={toggle_lock(config::LockableField::Points)}
+                                            />
+                                            { "Locked" }
+                                        </label>
                                     </div>
                                 </div>
-                            </div>
 
-                            <div
-                                class={classes!(
-                                    "service-properties-pane",
-                                    Some("hidden").filter(|_| !matches!(*current_tab_index, Tabs::Environments))
-                                )}
-                            >
-                                <environment_editor::Editor
-                                    update_service={props.update_service.clone()}
-                                    name={props.name.clone()}
-                                    accounts={props.accounts.clone()}
-                                    service_definition={props.service_definition.clone()}
-                                    port={props.port}
-                                    points={props.points}
+                                <div class="service-property">
+                                    <div class="service-property-name">
+                                        { "Informational only:" }
+                                    </div>
+
+                                    <div class="service-property-value">
+                                        <label title="Checked for monitoring, but always worth 0 points">
+                                            <input
+                                                type="checkbox"
+                                                checked={props.service.informational}
+                                                onchange={toggle_informational}
+                                            />
+                                            { "Check it, but don't score it" }
+                                        </label>
+                                    </div>
+                                </div>
+
+                                <div class="service-property">
+                                    <div class="service-property-name">
+                                        { "Check interval (seconds):" }
+                                    </div>
+
+                                    <div class="service-property-value">
+                                        <input
+                                            type="number"
+                                            min="1"
+                                            step="1"
+                                            placeholder="Engine default"
+                                            value={props.service.interval.map(|seconds| seconds.to_string()).unwrap_or_default()}
+                                            onchange={set_duration("Check interval", |service, interval| service.interval = interval)}
+                                        />
+                                    </div>
+                                </div>
+
+                                <div class="service-property">
+                                    <div class="service-property-name">
+                                        { "Timeout (seconds):" }
+                                    </div>
+
+                                    <div class="service-property-value">
+                                        <input
+                                            type="number"
+                                            min="1"
+                                            step="1"
+                                            placeholder="Engine default"
+                                            value={props.service.timeout.map(|seconds| seconds.to_string()).unwrap_or_default()}
+                                            onchange={set_duration("Timeout", |service, timeout| service.timeout = timeout)}
+                                        />
+                                    </div>
+                                </div>
+
+                                if !blue_teams.is_empty() {
+                                    <div class="service-property">
+                                        <div class="service-property-name">
+                                            { "Scored for teams:" }
+                                        </div>
+
+                                        <div class="service-property-value service-teams">
+                                            { for team_toggles }
+                                        </div>
+                                    </div>
+                                }
+                            </div>
+
+                            <div
+                                class={classes!(
+                                    "service-properties-pane",
+                                    Some("hidden").filter(|_| !matches!(*current_tab_index, Tabs::Environments))
+                                )}
+                            >
+                                <environment_editor::Editor
+                                    update_service={props.update_service.clone()}
+                                    service={props.service.clone()}
+                                    service_definition={props.service_definition.clone()}
                                 />
+
+                                <super::EnvironmentPreview service={props.service.clone()} />
                             </div>
 
                             <div
@@ -939,10 +1493,38 @@ macro_rules! setup_service {
                                     Some("hidden").filter(|_| !matches!(*current_tab_index, Tabs::Accounts))
                                 )}
                             >
-                                <a href="#" onclick={add_account} class="add-user">
+                                <div class="service-property">
+                                    <div class="service-property-name">
+                                        { "Emit accounts on each check:" }
+                                    </div>
+
+                                    <div class="service-property-value">
+                                        <input
+                                            ref={inline_accounts_ref}
+                                            type="checkbox"
+                                            checked={props.service.inline_accounts}
+                                            onchange={set_inline_accounts}
+                                        />
+                                    </div>
+                                </div>
+
+                                if props.service.definition.requires_accounts()
+                                    && props.service.accounts.as_ref().is_none_or(Vec::is_empty)
+                                {
+                                    <div class="service-error">
+                                        { "This check logs in with these accounts, so it won't score anything until at least one is added" }
+                                    </div>
+                                }
+
+                                <a href="#" onclick={add_account} class="add-user edit-action">
                                     { "Add account" }
                                 </a>
 
+                                <super::BulkAccountEditor
+                                    accounts={props.service.accounts.clone()}
+                                    {update_accounts}
+                                />
+
                                 { for accounts }
                             </div>
                         </div>
@@ -955,10 +1537,25 @@ macro_rules! setup_service {
 
 macro_rules! setup_general_service_editor {
     ($($case:ident => $mod:ident),*) => {
+        /// The port, points and accounts a service of the given type starts out with
+        pub fn default_service(definition: &config::ServiceDefinition) -> config::ServiceEditor {
+            match definition {
+                $(
+                    config::ServiceDefinition::$case { .. } => $mod::new_service(),
+                )*
+            }
+        }
+
+        /// A new service of every type offered in the service list, in the order listed
+        pub fn palette() -> Vec<config::ServiceEditor> {
+            vec![$($mod::new_service()),*]
+        }
+
         #[derive(Properties, PartialEq)]
         struct ServiceEditorComponentProps {
             pub update_service: Callback<config::ServiceEditor>,
             pub delete_service: Callback<()>,
+            pub host_error: Option<AttrValue>,
             pub service_to_edit: config::ServiceEditor,
         }
 
@@ -970,10 +1567,8 @@ macro_rules! setup_general_service_editor {
                         <$mod::ServiceEditorComponent
                             update_service={props.update_service.clone()}
                             delete_service={props.delete_service.clone()}
-                            name={props.service_to_edit.name.clone()}
-                            port={props.service_to_edit.port}
-                            points={props.service_to_edit.points}
-                            accounts={props.service_to_edit.accounts.clone()}
+                            host_error={props.host_error.clone()}
+                            service={props.service_to_edit.clone()}
                             service_definition={environment.clone()}
                         />
                     }
@@ -1071,7 +1666,7 @@ setup_service! {
     },
     (
         useragent => "Browser user agent",
-        vhost => "Remote host name",
+        vhost => "Remote host name (host[:port], no http:// or path)",
         uri => "Request URI"
     )
 }
@@ -1079,14 +1674,14 @@ setup_service! {
     (https, "HTTPS", Vec<config::HttpCheckInfo>),
     ServiceEditor {
         name => "HTTPS",
-        port => 80,
+        port => 443,
         points => 150,
         accounts => None,
         definition => Https
     },
     (
         useragent => "Browser user agent",
-        vhost => "Remote host name",
+        vhost => "Remote host name (host[:port], no http:// or path)",
         uri => "Request URI"
     )
 }
@@ -1118,10 +1713,10 @@ setup_service! {
     (imaps, "IMAPS", Vec<config::ImapCheckInfo>),
     ServiceEditor {
         name => "IMAPS",
-        port => 143,
+        port => 993,
         points => 100,
         accounts => Some(vec![]),
-        definition => Imap
+        definition => Imaps
     },
     (
         domain => "Email domain"
@@ -1145,7 +1740,7 @@ setup_service! {
     (mssql, "MSSQL", Vec<config::SqlCheckInfo>),
     ServiceEditor {
         name => "MSSQL",
-        port => 3389,
+        port => 1433,
         points => 100,
         accounts => Some(vec![]),
         definition => Mssql
@@ -1200,10 +1795,10 @@ setup_service! {
     (pop3s, "POP3S", Vec<config::PopCheckInfo>),
     ServiceEditor {
         name => "POP3S",
-        port => 110,
+        port => 995,
         points => 100,
         accounts => Some(vec![]),
-        definition => Pop3
+        definition => Pop3s
     },
     (
         domain => "Email domain"
@@ -1269,7 +1864,7 @@ setup_service! {
     (smtps, "SMTPS", Vec<config::SmtpCheckInfo>),
     ServiceEditor {
         name => "SMTPS",
-        port => 25,
+        port => 465,
         points => 100,
         accounts => Some(vec![]),
         definition => Smtps
@@ -1328,7 +1923,7 @@ setup_service! {
     },
     (
         useragent => "Browser user agent",
-        vhost => "Remote host name",
+        vhost => "Remote host name (host[:port], no http:// or path)",
         uri => "Request URI"
     )
 }
@@ -1363,7 +1958,12 @@ setup_general_service_editor! {
 #[derive(Properties, PartialEq)]
 pub struct MachineServiceListEditorProps {
     pub update_services: Callback<Vec<config::ServiceEditor>>,
+    pub remove_service: Callback<usize>,
+    pub machine_name: AttrValue,
+    /// Whether the machine resolves to a host its services can be checked on
+    pub has_host: bool,
     pub services: Vec<config::ServiceEditor>,
+    pub drag_service: Callback<Option<usize>>,
 }
 
 #[function_component]
@@ -1384,22 +1984,43 @@ pub fn MachineServiceListEditor(props: &MachineServiceListEditorProps) -> Html {
         };
 
         let delete_service = {
-            let update_services = props.update_services.clone();
-            let new_services = services_vec.clone();
-            Callback::from(move |_| {
-                let mut new_services = new_services.clone();
-                new_services.remove(i);
-                update_services.emit(new_services);
-            })
+            let remove_service = props.remove_service.clone();
+            Callback::from(move |_| remove_service.emit(i))
+        };
+
+        let host_error = (!props.has_host).then(|| {
+            AttrValue::from(
+                config::ConversionError::ServiceHasNoHost(
+                    props.machine_name.to_string(),
+                    service.name.clone(),
+                )
+                .to_string(),
+            )
+        });
+
+        let ondragstart = {
+            let drag_service = props.drag_service.clone();
+            Callback::from(move |_| drag_service.emit(Some(i)))
+        };
+
+        let ondragend = {
+            let drag_service = props.drag_service.clone();
+            Callback::from(move |_| drag_service.emit(None))
         };
 
         html! {
-            <ServiceEditorComponent
-                key={i}
-                {update_service}
-                {delete_service}
-                {service_to_edit}
-            />
+            <div key={i} class="machine-service-container">
+                <div class="drag-handle" draggable="true" {ondragstart} {ondragend}>
+                    { "Drag to trash" }
+                </div>
+
+                <ServiceEditorComponent
+                    {update_service}
+                    {delete_service}
+                    {host_error}
+                    {service_to_edit}
+                />
+            </div>
         }
     });
 
@@ -1408,10 +2029,317 @@ pub fn MachineServiceListEditor(props: &MachineServiceListEditorProps) -> Html {
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum DraggedItem {
+    Machine(usize),
+    Service(usize, usize),
+}
+
+#[derive(Properties, PartialEq)]
+struct BulkAccountEditorProps {
+    accounts: Option<Vec<config::User>>,
+    update_accounts: Callback<Option<Vec<config::User>>>,
+}
+
+/// Adds or replaces many service accounts at once from pasted `username,password` lines
+#[function_component]
+fn BulkAccountEditor(props: &BulkAccountEditorProps) -> Html {
+    let text = use_state(String::new);
+    let error = use_state(Option::<String>::default);
+
+    let on_input = {
+        let text = text.clone();
+        let error = error.clone();
+
+        Callback::from(move |e: InputEvent| {
+            text.set(e.target_unchecked_into::<HtmlTextAreaElement>().value());
+            error.set(None);
+        })
+    };
+
+    let apply = |append: bool| {
+        let text = text.clone();
+        let error = error.clone();
+        let accounts = props.accounts.clone();
+        let update_accounts = props.update_accounts.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+
+            let users = match parse_user_lines(&text) {
+                Ok(users) => users,
+                Err(e) => {
+                    error.set(Some(e));
+                    return;
+                }
+            };
+
+            let mut new_accounts = if append {
+                accounts.clone().unwrap_or_default()
+            } else {
+                vec![]
+            };
+
+            if let Some(user) = users.iter().find(|user| {
+                new_accounts
+                    .iter()
+                    .any(|account| account.username == user.username)
+            }) {
+                error.set(Some(format!(
+                    "{} already has an account on this service",
+                    user.username
+                )));
+                return;
+            }
+
+            new_accounts.extend(users);
+            update_accounts.emit(Some(new_accounts));
+            text.set(String::new());
+        })
+    };
+
+    html! {
+        <details class="bulk-accounts">
+            <summary>{ "Add many accounts at once" }</summary>
+
+            <textarea
+                placeholder="One username,password per line"
+                value={text.to_string()}
+                oninput={on_input}
+            />
+
+            if let Some(e) = &*error {
+                <div class="service-error">{ e }</div>
+            }
+
+            <div class="bulk-accounts-buttons">
+                <a href="#" class="edit-action" onclick={apply(true)}>
+                    { "Append to accounts" }
+                </a>
+
+                <a href="#" class="edit-action" onclick={apply(false)}>
+                    { "Replace accounts" }
+                </a>
+            </div>
+        </details>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct EnvironmentPreviewProps {
+    service: config::ServiceEditor,
+}
+
+/// The environments exactly as they will be written to the generated configuration
+#[function_component]
+fn EnvironmentPreview(props: &EnvironmentPreviewProps) -> Html {
+    let editor_state = use_context::<crate::state::EditorStateContext>().unwrap();
+    let engine_version = editor_state.force_init().0.engine_version;
+
+    let errors = props.service.definition.field_errors(engine_version);
+    let environments = if errors.is_empty() {
+        props
+            .service
+            .definition
+            .environments("", &props.service.name, engine_version)
+            .map_err(|e| e.to_string())
+    } else {
+        Err(errors
+            .iter()
+            .map(|error| format!("check {}: {}", error.environment + 1, error.message))
+            .collect::<Vec<_>>()
+            .join("; "))
+    };
+
+    let rows = match environments {
+        Err(e) => html! {
+            <tr>
+                <td colspan="3" class="environment-preview-error">
+                    { format!("Nothing can be generated until this is fixed: {e}") }
+                </td>
+            </tr>
+        },
+        Ok(environments) if environments.is_empty() => html! {
+            <tr>
+                <td colspan="3">{ "No enabled checks" }</td>
+            </tr>
+        },
+        Ok(environments) => environments
+            .iter()
+            .enumerate()
+            .map(|(i, environment)| {
+                let properties = environment
+                    .properties
+                    .iter()
+                    .map(|property| {
+                        html! {
+                            <div>
+                                <code>{ &property.name }</code>
+                                { ": " }
+                                <code>{ &property.value }</code>
+                            </div>
+                        }
+                    })
+                    .collect::<Html>();
+
+                html! {
+                    <tr key={i}>
+                        <td>{ i + 1 }</td>
+                        <td><code>{ &environment.matching_content }</code></td>
+                        <td>{ properties }</td>
+                    </tr>
+                }
+            })
+            .collect::<Html>(),
+    };
+
+    html! {
+        <div class="environment-preview">
+            <h4>{ "Generated checks" }</h4>
+
+            <table>
+                <thead>
+                    <tr>
+                        <th>{ "#" }</th>
+                        <th>{ "matching_content" }</th>
+                        <th>{ "properties" }</th>
+                    </tr>
+                </thead>
+
+                <tbody>
+                    { rows }
+                </tbody>
+            </table>
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct CopyServicesProps {
+    source: usize,
+    close: Callback<()>,
+    report: Callback<String>,
+}
+
+/// Picks the other machines that should get a copy of every service of a machine
+#[function_component]
+fn CopyServices(props: &CopyServicesProps) -> Html {
+    let editor_state = use_context::<crate::state::EditorStateContext>().unwrap();
+    let config = editor_state.force_init().0;
+
+    let selected = use_state(Vec::<usize>::new);
+
+    let toggle = |target: usize| {
+        let selected = selected.clone();
+
+        Callback::from(move |e: Event| {
+            let checked = e.target_unchecked_into::<HtmlInputElement>().checked();
+            let mut targets = (*selected).clone();
+            targets.retain(|selected| *selected != target);
+            if checked {
+                targets.push(target);
+            }
+            selected.set(targets);
+        })
+    };
+
+    let copy = {
+        let editor_state = editor_state.clone();
+        let machines = config.machines.clone();
+        let selected = selected.clone();
+        let source = props.source;
+        let close = props.close.clone();
+        let report = props.report.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            if selected.is_empty() {
+                return;
+            }
+            let Some(services) = machines.get(source).map(|machine| &machine.services) else {
+                return;
+            };
+
+            let updates = selected
+                .iter()
+                .filter_map(|target| {
+                    let mut machine = machines.get(*target)?.clone();
+                    (machine.add_missing_services(services) > 0).then_some((*target, machine))
+                })
+                .collect::<Vec<_>>();
+            let updated = updates.len();
+            if updated > 0 {
+                editor_state.dispatch(state::EditorMessage::UpdateMachines(updates));
+            }
+
+            report.emit(match updated {
+                0 => "No machines were updated; they already have services with these names"
+                    .to_owned(),
+                1 => "Copied the services to 1 machine".to_owned(),
+                n => format!("Copied the services to {n} machines"),
+            });
+            close.emit(());
+        })
+    };
+
+    let cancel = {
+        let close = props.close.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            close.emit(());
+        })
+    };
+
+    let targets = config
+        .machines
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != props.source)
+        .map(|(i, machine)| {
+            html! {
+                <label key={i}>
+                    <input
+                        type="checkbox"
+                        checked={selected.contains(&i)}
+                        onchange={toggle(i)}
+                    />
+                    if machine.name.is_empty() {
+                        { format!("Unnamed machine {}", i + 1) }
+                    } else {
+                        { &machine.name }
+                    }
+                </label>
+            }
+        })
+        .collect::<Html>();
+
+    html! {
+        <div class="copy-services">
+            <div>{ "Copy every service of this machine to:" }</div>
+
+            <div class="copy-services-targets">
+                { targets }
+            </div>
+
+            <a
+                href="#"
+                class={classes!("edit-action", selected.is_empty().then_some("disabled"))}
+                onclick={copy}
+            >
+                { "Copy services" }
+            </a>
+            { " " }
+            <a href="#" onclick={cancel}>{ "Cancel" }</a>
+        </div>
+    }
+}
+
 #[derive(Properties, PartialEq)]
 struct MachineEditorProps {
-    i: u8,
+    i: usize,
     machine: MachineEditor,
+    drag_item: Callback<Option<DraggedItem>>,
 }
 
 #[function_component]
@@ -1458,15 +2386,27 @@ fn MachineEditorComponent(props: &MachineEditorProps) -> Html {
                 return;
             };
             let mut new_machine = machine.clone();
-            new_machine.name = input.value().clone();
+            new_machine.name = read_trimmed_input(&input);
             editor_state.dispatch(state::EditorMessage::UpdateMachine(i, new_machine));
             editing_name.set(false);
         })
     };
 
     let ip_template_ref = use_node_ref();
+    // What's in the template input while it's being typed in, so that a template that
+    // doesn't suit the IP scheme can be pointed out before it's committed
+    let ip_template_draft = use_state(Option::<String>::default);
+
+    let on_ip_template_input = {
+        let ip_template_draft = ip_template_draft.clone();
+
+        Callback::from(move |e: InputEvent| {
+            ip_template_draft.set(Some(e.target_unchecked_into::<HtmlInputElement>().value()));
+        })
+    };
 
     let on_ip_template_change = {
+        let ip_template_draft = ip_template_draft.clone();
         let ip_template_ref = ip_template_ref.clone();
         let machine_editor_error = machine_editor_error.clone();
         let editor_state = editor_state.clone();
@@ -1481,6 +2421,65 @@ fn MachineEditorComponent(props: &MachineEditorProps) -> Html {
             let mut new_machine = machine.clone();
             new_machine.ip_template = input.value().clone();
             editor_state.dispatch(state::EditorMessage::UpdateMachine(i, new_machine));
+            ip_template_draft.set(None);
+        })
+    };
+
+    let base_host_ref = use_node_ref();
+
+    let on_base_host_change = {
+        let base_host_ref = base_host_ref.clone();
+        let machine_editor_error = machine_editor_error.clone();
+        let editor_state = editor_state.clone();
+        let i = props.i;
+        let machine = props.machine.clone();
+
+        Callback::from(move |_| {
+            machine_editor_error.set(None);
+            let Some(input) = base_host_ref.cast::<HtmlInputElement>() else {
+                return;
+            };
+            let mut new_machine = machine.clone();
+            new_machine.base_host = Some(input.value()).filter(|host| !host.is_empty());
+            editor_state.dispatch(state::EditorMessage::UpdateMachine(i, new_machine));
+        })
+    };
+
+    let group_ref = use_node_ref();
+
+    let on_group_change = {
+        let group_ref = group_ref.clone();
+        let editor_state = editor_state.clone();
+        let i = props.i;
+        let machine = props.machine.clone();
+
+        Callback::from(move |_| {
+            let Some(input) = group_ref.cast::<HtmlInputElement>() else {
+                return;
+            };
+            let mut new_machine = machine.clone();
+            new_machine.group =
+                Some(input.value().trim().to_owned()).filter(|group| !group.is_empty());
+            editor_state.dispatch(state::EditorMessage::UpdateMachine(i, new_machine));
+        })
+    };
+
+    let hostname_ref = use_node_ref();
+
+    let on_hostname_change = {
+        let hostname_ref = hostname_ref.clone();
+        let editor_state = editor_state.clone();
+        let i = props.i;
+        let machine = props.machine.clone();
+
+        Callback::from(move |_| {
+            let Some(input) = hostname_ref.cast::<HtmlInputElement>() else {
+                return;
+            };
+            let mut new_machine = machine.clone();
+            new_machine.hostname_template =
+                Some(input.value().trim().to_owned()).filter(|hostname| !hostname.is_empty());
+            editor_state.dispatch(state::EditorMessage::UpdateMachine(i, new_machine));
         })
     };
 
@@ -1498,14 +2497,15 @@ fn MachineEditorComponent(props: &MachineEditorProps) -> Html {
                 return;
             };
 
-            match input.value().parse::<u8>() {
+            match parse_whole_number(&input.value(), 0, u8::MAX, "The IP multiplier offset") {
                 Ok(offset) => {
+                    machine_editor_error.set(None);
                     let mut new_machine = machine.clone();
                     new_machine.ip_offset = Some(offset);
                     editor_state.dispatch(state::EditorMessage::UpdateMachine(i, new_machine));
                 }
                 Err(e) => {
-                    machine_editor_error.set(Some(format!("Parse error: {e:?}")));
+                    machine_editor_error.set(Some(e));
                 }
             }
         })
@@ -1520,6 +2520,15 @@ fn MachineEditorComponent(props: &MachineEditorProps) -> Html {
         })
     };
 
+    let remove_service = {
+        let editor_state = editor_state.clone();
+        let i = props.i;
+
+        Callback::from(move |service| {
+            editor_state.dispatch(state::EditorMessage::RemoveService(i, service));
+        })
+    };
+
     let ondragover = {
         let editor_state = editor_state.clone();
         let machine_name = props.machine.name.clone();
@@ -1567,24 +2576,105 @@ fn MachineEditorComponent(props: &MachineEditorProps) -> Html {
     let update_services = {
         let editor_state = editor_state.clone();
         let i = props.i;
-        let name = props.machine.name.clone();
-        let ip_offset = props.machine.ip_offset.clone();
-        let ip_template = props.machine.ip_template.clone();
+        let machine = props.machine.clone();
 
         Callback::from(move |new_services| {
             editor_state.dispatch(state::EditorMessage::UpdateMachine(
                 i,
                 MachineEditor {
-                    name: name.clone(),
-                    ip_offset: ip_offset.clone(),
-                    ip_template: ip_template.clone(),
                     services: new_services,
+                    ..machine.clone()
                 },
             ))
         })
     };
 
-    let hovering_class = Some("hovering").filter(|_| {
+    let drag_service = {
+        let drag_item = props.drag_item.clone();
+        let i = props.i;
+
+        Callback::from(move |service: Option<usize>| {
+            drag_item.emit(service.map(|service| DraggedItem::Service(i, service)));
+        })
+    };
+
+    let drag_machine_start = {
+        let drag_item = props.drag_item.clone();
+        let i = props.i;
+
+        Callback::from(move |_| drag_item.emit(Some(DraggedItem::Machine(i))))
+    };
+
+    let drag_machine_end = {
+        let drag_item = props.drag_item.clone();
+
+        Callback::from(move |_| drag_item.emit(None))
+    };
+
+    let missing_host = !props.machine.services.is_empty()
+        && config.ip_generator.uses_templates()
+        && !props.machine.uses_hostname()
+        && config.ip_template_for(&props.machine).trim().is_empty();
+
+    let template_mismatch = config.ip_generator.template_mismatch(
+        &props.machine.name,
+        ip_template_draft
+            .as_deref()
+            .unwrap_or(&props.machine.ip_template),
+        config.blue_teams.first().map_or(1, |team| team.id),
+    );
+
+    let fix_template = {
+        let editor_state = editor_state.clone();
+        let ip_template_draft = ip_template_draft.clone();
+        let i = props.i;
+        let machine = props.machine.clone();
+        let fixed = template_mismatch
+            .as_ref()
+            .and_then(|mismatch| mismatch.fixed.clone());
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            let Some(fixed) = &fixed else {
+                return;
+            };
+
+            editor_state.dispatch(state::EditorMessage::UpdateMachine(
+                i,
+                MachineEditor {
+                    ip_template: fixed.clone(),
+                    ..machine.clone()
+                },
+            ));
+            ip_template_draft.set(None);
+        })
+    };
+
+    let copying_services = use_state(bool::default);
+    let copy_report = use_state(Option::<String>::default);
+
+    let start_copying_services = {
+        let copying_services = copying_services.clone();
+        let copy_report = copy_report.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            copy_report.set(None);
+            copying_services.set(true);
+        })
+    };
+
+    let stop_copying_services = {
+        let copying_services = copying_services.clone();
+        Callback::from(move |_| copying_services.set(false))
+    };
+
+    let report_copy = {
+        let copy_report = copy_report.clone();
+        Callback::from(move |report| copy_report.set(Some(report)))
+    };
+
+    let hovering_class = Some("hovering").filter(|_| {
         editor_state
             .force_init()
             .2
@@ -1593,7 +2683,13 @@ fn MachineEditorComponent(props: &MachineEditorProps) -> Html {
     });
 
     html! {
-        <div {ondragover} {ondragleave} {ondrop} class={classes!("machine", hovering_class)}>
+        <div
+            id={format!("machine-{}", props.i)}
+            {ondragover}
+            {ondragleave}
+            {ondrop}
+            class={classes!("machine", hovering_class)}
+        >
             <div class="machine-header">
                 <div class="machine-name">
                     if *editing_name || props.machine.name.is_empty() {
@@ -1611,33 +2707,113 @@ fn MachineEditorComponent(props: &MachineEditorProps) -> Html {
                     }
                 </div>
 
-                <a href="#" onclick={delete_machine}>
-                    { "Delete machine" }
-                </a>
+                <div class="machine-header-buttons">
+                    <span
+                        class="drag-handle"
+                        draggable="true"
+                        ondragstart={drag_machine_start}
+                        ondragend={drag_machine_end}
+                    >
+                        { "Drag to trash" }
+                    </span>
+
+                    if !props.machine.services.is_empty() && config.machines.len() > 1 {
+                        <a href="#" class="edit-action" onclick={start_copying_services}>
+                            { "Copy services to..." }
+                        </a>
+                    }
+
+                    <a href="#" class="edit-action" onclick={delete_machine}>
+                        { "Delete machine" }
+                    </a>
+                </div>
             </div>
 
+            if *copying_services {
+                <CopyServices
+                    source={props.i}
+                    close={stop_copying_services}
+                    report={report_copy}
+                />
+            } else if let Some(report) = &*copy_report {
+                <div class="copy-services">{ report }</div>
+            }
+
             if let Some(err) = &*machine_editor_error {
                 <div class="machine-error">
                     {err}
                 </div>
+            } else if missing_host {
+                <div class="machine-error">
+                    { config::ConversionError::EmptyHost(props.machine.name.clone()).to_string() }
+                </div>
+            } else if let Some(mismatch) = &template_mismatch {
+                <div class="machine-error">
+                    { mismatch.error.to_string() }
+                    if let Some(fixed) = &mismatch.fixed {
+                        { " " }
+                        <a href="#" class="template-fix" onclick={fix_template}>
+                            { format!("Use {fixed} instead") }
+                        </a>
+                    }
+                </div>
             }
 
             <div class="machine-body">
                 <div class="machine-properties">
-                    <div class="machine-property">
-                        <div class="machine-property-name">
-                            if matches!(config.ip_generator, config::IpGeneratorScheme::OneTeam) {
+                    if let config::IpGeneratorScheme::Cidr { base, prefix } = &config.ip_generator {
+                        <div class="machine-property">
+                            <div class="machine-property-name">
                                 { "IP address:" }
-                            } else {
-                                { "IP template:" }
-                            }
+                            </div>
+
+                            <div class="machine-property-value">
+                                { format!("Assigned in order from {base}/{prefix}") }
+                            </div>
+                        </div>
+                    } else {
+                        <div class="machine-property">
+                            <div class="machine-property-name">
+                                if matches!(config.ip_generator, config::IpGeneratorScheme::OneTeam) {
+                                    { "IP address:" }
+                                } else {
+                                    { "IP template:" }
+                                }
+                            </div>
+
+                            <div class="machine-property-value">
+                                <input
+                                    class={classes!((missing_host || template_mismatch.is_some()).then_some("invalid"))}
+                                    value={props.machine.ip_template.clone()}
+                                    placeholder={config.ip_template_for(&props.machine)}
+                                    ref={ip_template_ref}
+                                    oninput={on_ip_template_input}
+                                    onchange={on_ip_template_change}
+                                />
+                            </div>
+                        </div>
+                    }
+
+                    <div
+                        class={classes!(
+                            "machine-property",
+                            Some("hidden")
+                                .filter(|_| matches!(
+                                    config.ip_generator,
+                                    config::IpGeneratorScheme::OneTeam | config::IpGeneratorScheme::Cidr { .. }
+                                ))
+                        )}
+                        title="Used with the base template from the IP settings when no IP template is given"
+                    >
+                        <div class="machine-property-name">
+                            { "Host on base template:" }
                         </div>
 
                         <div class="machine-property-value">
                             <input
-                                value={props.machine.ip_template.clone()}
-                                ref={ip_template_ref}
-                                onchange={on_ip_template_change}
+                                value={props.machine.base_host.clone().unwrap_or_default()}
+                                ref={base_host_ref}
+                                onchange={on_base_host_change}
                             />
                         </div>
                     </div>
@@ -1661,12 +2837,50 @@ fn MachineEditorComponent(props: &MachineEditorProps) -> Html {
                             />
                         </div>
                     </div>
+
+                    <div
+                        class="machine-property"
+                        title="Check the services of this machine by name rather than by address, with {id}, or a part between dots that is just X, replaced by the team ID"
+                    >
+                        <div class="machine-property-name">
+                            { "Hostname:" }
+                        </div>
+
+                        <div class="machine-property-value">
+                            <input
+                                value={props.machine.hostname_template.clone().unwrap_or_default()}
+                                placeholder="team{id}-web.comp.local"
+                                ref={hostname_ref}
+                                onchange={on_hostname_change}
+                            />
+                        </div>
+                    </div>
+
+                    <div class="machine-property">
+                        <div class="machine-property-name">
+                            { "Group:" }
+                        </div>
+
+                        <div class="machine-property-value">
+                            <input
+                                value={props.machine.group.clone().unwrap_or_default()}
+                                list="machine-groups"
+                                placeholder="None"
+                                ref={group_ref}
+                                onchange={on_group_change}
+                            />
+                        </div>
+                    </div>
                 </div>
 
                 <div class="machine-services">
                     <MachineServiceListEditor
                         {update_services}
+                        {remove_service}
+                        machine_name={props.machine.name.clone()}
+                        has_host={!missing_host}
                         services={props.machine.services.clone()}
+                        {drag_service}
                     />
                 </div>
             </div>
@@ -1674,6 +2888,301 @@ fn MachineEditorComponent(props: &MachineEditorProps) -> Html {
     }
 }
 
+#[derive(Properties, PartialEq)]
+pub struct MissingOffsetsProps {
+    /// Whether the machines are on the page, so they can be scrolled to directly
+    pub on_machines_page: bool,
+}
+
+/// How many machines the multiplier scheme can't place yet, with a way to get to them
+#[function_component]
+pub fn MissingOffsets(props: &MissingOffsetsProps) -> Html {
+    let editor_state = use_context::<crate::state::EditorStateContext>().unwrap();
+    let missing = editor_state.force_init().0.machines_missing_offsets();
+
+    let last_jumped = use_state(Option::<usize>::default);
+
+    if missing.is_empty() {
+        return html! {};
+    }
+
+    let onclick = {
+        let editor_state = editor_state.clone();
+        let on_machines_page = props.on_machines_page;
+        let missing = missing.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+
+            if !on_machines_page {
+                editor_state.dispatch(state::EditorMessage::ChangeToView(
+                    state::CurrentView::Machines,
+                ));
+                return;
+            }
+
+            let next = missing
+                .iter()
+                .copied()
+                .find(|i| Some(*i) > *last_jumped)
+                .unwrap_or(missing[0]);
+            last_jumped.set(Some(next));
+
+            if let Some(machine) = window()
+                .and_then(|window| window.document())
+                .and_then(|document| document.get_element_by_id(&format!("machine-{next}")))
+            {
+                machine.scroll_into_view();
+            }
+        })
+    };
+
+    html! {
+        <div class="missing-offsets">
+            { match missing.len() {
+                1 => "1 machine still needs an IP offset before addresses can be generated. ".to_owned(),
+                n => format!("{n} machines still need an IP offset before addresses can be generated. "),
+            } }
+
+            <a href="#" {onclick}>
+                if props.on_machines_page {
+                    { "Jump to the next one" }
+                } else {
+                    { "Go to machines" }
+                }
+            </a>
+        </div>
+    }
+}
+
+/// The machines the multiplier scheme would give the same addresses because they share
+/// an IP offset, with a way to move all but one of each to a free offset
+#[function_component]
+pub fn DuplicateOffsets() -> Html {
+    let editor_state = use_context::<crate::state::EditorStateContext>().unwrap();
+    let config = editor_state.force_init().0;
+    let duplicates = config.duplicate_offsets();
+
+    let report = use_state(Option::<String>::default);
+
+    let renumber = {
+        let editor_state = editor_state.clone();
+        let config = config.clone();
+        let report = report.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+
+            let mut config = config.clone();
+            let moved = config.renumber_duplicate_offsets();
+
+            for i in &moved {
+                editor_state.dispatch(state::EditorMessage::UpdateMachine(
+                    *i,
+                    config.machines[*i].clone(),
+                ));
+            }
+
+            report.set(Some(if moved.is_empty() {
+                "No machines were moved; every offset below the multiplier is taken".to_owned()
+            } else {
+                format!(
+                    "Moved {}",
+                    moved
+                        .iter()
+                        .map(|i| {
+                            let machine = &config.machines[*i];
+                            format!(
+                                "{} to offset {}",
+                                machine.name,
+                                machine.ip_offset.unwrap_or_default()
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            }));
+        })
+    };
+
+    let dismiss = {
+        let report = report.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            report.set(None);
+        })
+    };
+
+    if duplicates.is_empty() && report.is_none() {
+        return html! {};
+    }
+
+    html! {
+        <div class="missing-offsets">
+            if !duplicates.is_empty() {
+                <div>{ "These machines share an IP offset, so they would be given the same addresses:" }</div>
+
+                <ul>
+                    { for duplicates.iter().map(|machines| html! {
+                        <li>
+                            { format!(
+                                "{} (offset {})",
+                                machines
+                                    .iter()
+                                    .map(|i| config.machines[*i].name.as_str())
+                                    .collect::<Vec<_>>()
+                                    .join(", "),
+                                config.machines[machines[0]].ip_offset.unwrap_or_default()
+                            ) }
+                        </li>
+                    }) }
+                </ul>
+
+                <a href="#" onclick={renumber} title="Keeps the first machine of each group where it is">
+                    { "Move them to free offsets" }
+                </a>
+            }
+
+            if let Some(report) = &*report {
+                <div>
+                    { format!("{report}. ") }
+                    <a href="#" onclick={dismiss}>{ "Dismiss" }</a>
+                </div>
+            }
+        </div>
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum ServiceTableColumn {
+    Name,
+    Port,
+    Points,
+}
+
+/// Every service on every machine as one row each, for adjusting names, ports and
+/// points across the whole environment without opening each machine
+#[function_component]
+fn ServiceTable() -> Html {
+    let editor_state = use_context::<crate::state::EditorStateContext>().unwrap();
+    let config = editor_state.force_init().0;
+
+    let table_error = use_state(Option::<String>::default);
+
+    let update_cell = |machine_index: usize, service_index: usize, column: ServiceTableColumn| {
+        let editor_state = editor_state.clone();
+        let table_error = table_error.clone();
+        let machine = config.machines[machine_index].clone();
+
+        Callback::from(move |e: Event| {
+            let input = e.target_unchecked_into::<HtmlInputElement>();
+            let mut machine = machine.clone();
+            let service = &mut machine.services[service_index];
+
+            let updated = match column {
+                ServiceTableColumn::Name => {
+                    service.name = read_trimmed_input(&input);
+                    Ok(())
+                }
+                ServiceTableColumn::Port => {
+                    read_u16_input(&input, 1, "Port").map(|port| service.port = port)
+                }
+                ServiceTableColumn::Points => {
+                    read_u16_input(&input, 0, "Points").map(|points| service.points = points)
+                }
+            };
+
+            match updated {
+                Ok(()) => {
+                    table_error.set(None);
+                    editor_state
+                        .dispatch(state::EditorMessage::UpdateMachine(machine_index, machine));
+                }
+                Err(e) => table_error.set(Some(e)),
+            }
+        })
+    };
+
+    let rows = config
+        .machines
+        .iter()
+        .enumerate()
+        .flat_map(|(i, machine)| {
+            machine
+                .services
+                .iter()
+                .enumerate()
+                .map(move |(j, service)| (i, machine, j, service))
+        })
+        .map(|(i, machine, j, service)| {
+            html! {
+                <tr key={format!("{i}-{j}")}>
+                    <td>{ &machine.name }</td>
+                    <td>{ service.definition.check_name() }</td>
+                    <td>
+                        <input
+                            value={service.name.clone()}
+                            onchange={update_cell(i, j, ServiceTableColumn::Name)}
+                        />
+                    </td>
+                    <td>
+                        <input
+                            type="number"
+                            min="1"
+                            max="65535"
+                            step="1"
+                            value={service.port.to_string()}
+                            onchange={update_cell(i, j, ServiceTableColumn::Port)}
+                            disabled={service.definition.is_portless()}
+                        />
+                    </td>
+                    <td>
+                        <input
+                            type="number"
+                            min="0"
+                            max="65535"
+                            step="1"
+                            value={service.points.to_string()}
+                            onchange={update_cell(i, j, ServiceTableColumn::Points)}
+                            disabled={service.informational}
+                            title={service.informational.then_some("Informational services are worth 0 points")}
+                        />
+                    </td>
+                </tr>
+            }
+        })
+        .collect::<Vec<_>>();
+
+    html! {
+        <div class="service-table">
+            if let Some(error) = &*table_error {
+                <div class="machine-error">{ error }</div>
+            }
+
+            if rows.is_empty() {
+                <p>{ "No machines have any services yet" }</p>
+            } else {
+                <table>
+                    <thead>
+                        <tr>
+                            <th>{ "Machine" }</th>
+                            <th>{ "Check" }</th>
+                            <th>{ "Service name" }</th>
+                            <th>{ "Port" }</th>
+                            <th>{ "Points" }</th>
+                        </tr>
+                    </thead>
+
+                    <tbody>
+                        { for rows }
+                    </tbody>
+                </table>
+            }
+        </div>
+    }
+}
+
 #[function_component]
 pub fn MachineConfiguration() -> Html {
     let editor_state = use_context::<crate::state::EditorStateContext>().unwrap();
@@ -1691,32 +3200,166 @@ pub fn MachineConfiguration() -> Html {
         let editor_state = editor_state.clone();
 
         Callback::from(move |()| {
-            editor_state.dispatch(state::EditorMessage::StopHoveringOverMachines);
+            editor_state.dispatch(state::EditorMessage::CancelDrag);
         })
     };
 
+    {
+        // A drag that was still going when the page was entered or left, such as one
+        // abandoned by switching pages from the keyboard, shouldn't be dropped later
+        let editor_state = editor_state.clone();
+
+        use_effect_with((), move |_| {
+            editor_state.dispatch(state::EditorMessage::CancelDrag);
+            move || editor_state.dispatch(state::EditorMessage::CancelDrag)
+        });
+    }
+
     let add_machine = {
         let editor_state = editor_state.clone();
 
         Callback::from(move |_| {
-            editor_state.dispatch(state::EditorMessage::AddMachine(MachineEditor {
-                name: "".to_owned(),
-                ip_offset: None,
-                ip_template: "".to_owned(),
-                services: vec![],
-            }));
+            editor_state.dispatch(state::EditorMessage::AddMachine(MachineEditor::default()));
+        })
+    };
+
+    let table_layout = use_state(|| false);
+
+    let toggle_layout = {
+        let table_layout = table_layout.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            table_layout.set(!*table_layout);
+        })
+    };
+
+    let dragged_item = use_state(Option::<DraggedItem>::default);
+
+    let drag_item = {
+        let dragged_item = dragged_item.clone();
+
+        Callback::from(move |item| dragged_item.set(item))
+    };
+
+    let ondragover_trash = Callback::from(|e: DragEvent| e.prevent_default());
+
+    let ondrop_trash = {
+        let editor_state = editor_state.clone();
+        let dragged_item = dragged_item.clone();
+        let machines = config.machines.clone();
+
+        Callback::from(move |e: DragEvent| {
+            e.prevent_default();
+
+            let Some(item) = *dragged_item else {
+                return;
+            };
+            dragged_item.set(None);
+
+            let description = match item {
+                DraggedItem::Machine(i) => format!("the machine '{}'", machines[i].name),
+                DraggedItem::Service(i, service) => format!(
+                    "the service '{}' from '{}'",
+                    machines[i].services[service].name, machines[i].name
+                ),
+            };
+
+            let confirmed = window()
+                .and_then(|window| {
+                    window
+                        .confirm_with_message(&format!("Delete {description}?"))
+                        .ok()
+                })
+                .unwrap_or(false);
+
+            if !confirmed {
+                return;
+            }
+
+            match item {
+                DraggedItem::Machine(i) => {
+                    editor_state.dispatch(state::EditorMessage::RemoveMachine(i));
+                }
+                DraggedItem::Service(i, service) => {
+                    editor_state.dispatch(state::EditorMessage::RemoveService(i, service));
+                }
+            }
         })
     };
 
-    let machine_list = config.machines.iter().enumerate().map(|(i, machine)| {
-        let i: u8 = i.try_into().unwrap();
+    let mut groups = Vec::<Option<&str>>::new();
+    for machine in &config.machines {
+        let group = machine.group.as_deref();
+        if group.is_some() && !groups.contains(&group) {
+            groups.push(group);
+        }
+    }
+    let group_names = groups.iter().flatten().copied().collect::<Vec<_>>();
+    let is_grouped = !groups.is_empty();
+    if !is_grouped
+        || config
+            .machines
+            .iter()
+            .any(|machine| machine.group.is_none())
+    {
+        groups.push(None);
+    }
+
+    let collapsed_groups = use_state(Vec::<Option<String>>::new);
+
+    let machine_list = groups.iter().map(|group| {
+        let machines = config
+            .machines
+            .iter()
+            .enumerate()
+            .filter(|(_, machine)| machine.group.as_deref() == *group)
+            .map(|(i, machine)| {
+                html! {
+                    <MachineEditorComponent
+                        key={i}
+                        {i}
+                        machine={machine.clone()}
+                        drag_item={drag_item.clone()}
+                    />
+                }
+            })
+            .collect::<Html>();
+
+        if !is_grouped {
+            return machines;
+        }
+
+        let group = group.map(str::to_owned);
+        let collapsed = collapsed_groups.contains(&group);
+
+        let toggle_group = {
+            let collapsed_groups = collapsed_groups.clone();
+            let group = group.clone();
+
+            Callback::from(move |e: MouseEvent| {
+                e.prevent_default();
+                let mut new_collapsed = (*collapsed_groups).clone();
+                if collapsed {
+                    new_collapsed.retain(|other| *other != group);
+                } else {
+                    new_collapsed.push(group.clone());
+                }
+                collapsed_groups.set(new_collapsed);
+            })
+        };
 
         html! {
-            <MachineEditorComponent
-                key={i}
-                {i}
-                machine={machine.clone()}
-            />
+            <div key={group.clone().unwrap_or_default()} class="machine-group">
+                <a href="#" class="machine-group-header" onclick={toggle_group}>
+                    { if collapsed { "▸ " } else { "▾ " } }
+                    { group.clone().unwrap_or_else(|| "Ungrouped".to_owned()) }
+                </a>
+
+                if !collapsed {
+                    { machines }
+                }
+            </div>
         }
     });
 
@@ -1745,6 +3388,14 @@ pub fn MachineConfiguration() -> Html {
                     oninput={set_name}
                     placeholder="Search services..."
                 />
+
+                <div class="category-legend">
+                    { for config::ServiceCategory::ALL.iter().map(|category| html! {
+                        <span class={category.class()}>
+                            <span class="category-tag">{ category.label() }</span>
+                        </span>
+                    }) }
+                </div>
             </div>
 
             <div class="service-list">
@@ -1756,14 +3407,43 @@ pub fn MachineConfiguration() -> Html {
             </div>
 
             <div class="machine-list-header">
-                <a href="#" onclick={add_machine}>
+                <a href="#" class="edit-action" onclick={add_machine}>
                     { "Add machine" }
                 </a>
+
+                <a href="#" class="layout-toggle" onclick={toggle_layout}>
+                    if *table_layout {
+                        { "Show machines as cards" }
+                    } else {
+                        { "Show all services as a table" }
+                    }
+                </a>
             </div>
 
             <div class="machine-list">
-                { for machine_list }
+                <MissingOffsets on_machines_page=true />
+                <DuplicateOffsets />
+                <ScanImport />
+                <InventoryImport />
+
+                if *table_layout {
+                    <ServiceTable />
+                } else {
+                    { for machine_list }
+                }
             </div>
+
+            <datalist id="machine-groups">
+                { for group_names.iter().map(|group| html! {
+                    <option value={group.to_string()} />
+                }) }
+            </datalist>
+
+            if dragged_item.is_some() {
+                <div class="trash" ondragover={ondragover_trash} ondrop={ondrop_trash}>
+                    { "Drop here to delete" }
+                </div>
+            }
         </main>
     }
 }
@@ -15,22 +15,46 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use gloo_events::EventListener;
+use gloo_timers::callback::Timeout;
+use wasm_bindgen::JsCast;
+use web_sys::{window, HtmlElement, HtmlInputElement, KeyboardEvent};
 use yew::prelude::*;
 
+use scoringengine_editor::{config, worker};
 use state::{CurrentView, EditingState, EditorMessage, EditorStateContext};
 
-mod config;
-mod error;
+mod accounts;
+mod bulk;
+mod compare;
+mod components;
+mod export;
 mod state;
 
+mod flags;
 mod input;
+mod inventory;
 mod ipsettings;
 mod machines;
+mod nmap;
 mod output;
+mod passwords;
+mod replace;
+mod review;
+mod tour;
 mod users;
+mod warnings;
+mod whats_new;
+
+#[derive(Properties, PartialEq)]
+struct NavBarProps {
+    /// The page the tour is currently pointing at, if it is running
+    tour_view: Option<CurrentView>,
+    start_tour: Callback<()>,
+}
 
 #[function_component]
-fn NavBar() -> Html {
+fn NavBar(props: &NavBarProps) -> Html {
     let editor_state = use_context::<EditorStateContext>().unwrap();
 
     let (allow_others, current_view) = &match editor_state.state {
@@ -47,23 +71,78 @@ fn NavBar() -> Html {
         }};
     }
 
+    let tour_class =
+        |view: CurrentView| (props.tour_view == Some(view)).then_some("tour-highlight");
+
     macro_rules! class_currently_selected {
         ($view:expr) => {{
             if *current_view == $view {
-                classes!(Some("selected"))
+                classes!(Some("selected"), tour_class($view))
             } else if !allow_others {
-                classes!(Some("inactive"))
+                classes!(Some("inactive"), tour_class($view))
             } else {
-                classes!()
+                classes!(tour_class($view))
             }
         }};
     }
 
-    let input_class = classes!(if *current_view == CurrentView::Input {
-        Some("selected")
-    } else {
-        None
-    });
+    let go_to_input = {
+        let editor_state = editor_state.clone();
+        let leaving = *current_view != CurrentView::Input;
+
+        Callback::from(move |_: MouseEvent| {
+            if leaving && !editor_state.confirm_leaving() {
+                return;
+            }
+            editor_state.dispatch(EditorMessage::ChangeToView(CurrentView::Input))
+        })
+    };
+
+    let input_class = classes!(
+        (*current_view == CurrentView::Input).then_some("selected"),
+        tour_class(CurrentView::Input)
+    );
+
+    let start_tour = {
+        let start_tour = props.start_tour.clone();
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            start_tour.emit(());
+        })
+    };
+
+    let set_autosave = {
+        let editor_state = editor_state.clone();
+        Callback::from(move |e: Event| {
+            editor_state.dispatch(EditorMessage::SetAutosave(
+                e.target_unchecked_into::<HtmlInputElement>().checked(),
+            ));
+        })
+    };
+
+    let save_now = {
+        let editor_state = editor_state.clone();
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            editor_state.dispatch(EditorMessage::SaveNow);
+        })
+    };
+
+    let undo = {
+        let editor_state = editor_state.clone();
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            editor_state.dispatch(EditorMessage::Undo);
+        })
+    };
+
+    let redo = {
+        let editor_state = editor_state.clone();
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            editor_state.dispatch(EditorMessage::Redo);
+        })
+    };
 
     let error_message = if !allow_others {
         "Please input a configuration file to edit"
@@ -75,7 +154,7 @@ fn NavBar() -> Html {
         <nav>
             <ul>
                 <li class={input_class}>
-                    <a href="#" onclick={define_view_change_callback!(state::CurrentView::Input)}>
+                    <a href="#" onclick={go_to_input}>
                         { "Input config" }
                     </a>
                 </li>
@@ -94,11 +173,46 @@ fn NavBar() -> Html {
                         { "IP Settings" }
                     </a>
                 </li>
+                <li class={class_currently_selected!(state::CurrentView::Accounts)} title={error_message}>
+                    <a href="#" onclick={define_view_change_callback!(state::CurrentView::Accounts)}>
+                        { "Accounts" }
+                    </a>
+                </li>
+                <li class={class_currently_selected!(state::CurrentView::Review)} title={error_message}>
+                    <a href="#" onclick={define_view_change_callback!(state::CurrentView::Review)}>
+                        { "Review" }
+                    </a>
+                </li>
                 <li class={class_currently_selected!(state::CurrentView::Output)} title={error_message}>
                     <a href="#" onclick={define_view_change_callback!(state::CurrentView::Output)}>
                         { "Generated config" }
                     </a>
                 </li>
+                <li class="autosave">
+                    <label title="Turn off to keep changes out of this browser's storage until they're saved">
+                        <input type="checkbox" checked={editor_state.autosave} onchange={set_autosave} />
+                        { "Autosave" }
+                    </label>
+
+                    if editor_state.unsaved_changes {
+                        <a href="#" class="unsaved" onclick={save_now} title="There are changes that haven't been saved to this browser yet">
+                            { "Save" }
+                        </a>
+                    }
+                </li>
+                <li class="history">
+                    <a href="#" class={classes!(editor_state.undo.is_empty().then_some("disabled"))} onclick={undo}>
+                        { "Undo" }
+                    </a>
+                    <a href="#" class={classes!(editor_state.redo.is_empty().then_some("disabled"))} onclick={redo}>
+                        { "Redo" }
+                    </a>
+                </li>
+                <li class="help">
+                    <a href="#" onclick={start_tour}>
+                        { "Show the tour" }
+                    </a>
+                </li>
             </ul>
         </nav>
     }
@@ -110,41 +224,350 @@ fn MainContent() -> Html {
 
     use state::CurrentView::*;
 
+    if editor_state.config_name().is_none() {
+        return html! {
+            <input::InitEditor />
+        };
+    }
+
+    // Any control that edits the configuration is disabled through the fieldset,
+    // and the links that add or remove things are hidden using its class
+    let locked = |editor: Html| {
+        if editor_state.is_locked() {
+            html! {
+                <fieldset
+                    class="locked-config"
+                    disabled=true
+                    title="This configuration is locked; unlock it from the input page to edit it"
+                >
+                    { editor }
+                </fieldset>
+            }
+        } else {
+            editor
+        }
+    };
+
     match &editor_state.state {
-        EditingState::Initializing { .. } => html! {
+        EditingState::Initializing => html! {
             <input::InitEditor />
         },
         EditingState::HasConfig { current_view, .. } => match current_view {
             Input => html! {
                 <input::InitEditor />
             },
-            IpSettings => html! {
+            IpSettings => locked(html! {
                 <ipsettings::IpSettingsEditor />
-            },
-            Teams => html! {
+            }),
+            Teams => locked(html! {
                 <users::TeamsEditor />
+            }),
+            Accounts => locked(html! {
+                <accounts::AccountsReview />
+            }),
+            Review => html! {
+                <review::ConfigurationReview />
             },
             Output => html! {
                 <output::ConfigurationOutput />
             },
-            Machines => html! {
+            Machines => locked(html! {
                 <machines::MachineConfiguration />
-            },
+            }),
         },
     }
 }
 
+#[function_component]
+fn HealthNotice() -> Html {
+    let editor_state = use_context::<EditorStateContext>().unwrap();
+
+    let Some(notice) = &editor_state.notice else {
+        return html! {};
+    };
+
+    let dismiss = {
+        let editor_state = editor_state.clone();
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            editor_state.dispatch(EditorMessage::DismissNotice);
+        })
+    };
+
+    let class = classes!(
+        "health-notice",
+        (!notice.problems.is_empty()).then_some("has-problems")
+    );
+
+    html! {
+        <div {class}>
+            <h4>{ notice.summary() }</h4>
+
+            if !notice.problems.is_empty() {
+                <ul>
+                    { for notice.problems.iter().map(|problem| html! {
+                        <li>{ problem }</li>
+                    }) }
+                </ul>
+            }
+
+            <a href="#" onclick={dismiss}>{ "Dismiss" }</a>
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct ToastMessageProps {
+    toast: state::Toast,
+}
+
+#[function_component]
+fn ToastMessage(props: &ToastMessageProps) -> Html {
+    let editor_state = use_context::<EditorStateContext>().unwrap();
+    let id = props.toast.id;
+
+    {
+        let editor_state = editor_state.clone();
+        // Errors stay up a little longer, as they usually need reading in full
+        let millis = match props.toast.severity {
+            state::Severity::Info => 4_000,
+            state::Severity::Warning => 6_000,
+            state::Severity::Error => 8_000,
+        };
+
+        use_effect_with(id, move |id| {
+            let id = *id;
+            let timeout = Timeout::new(millis, move || {
+                editor_state.dispatch(EditorMessage::DismissToast(id));
+            });
+
+            move || drop(timeout)
+        });
+    }
+
+    let dismiss = Callback::from(move |e: MouseEvent| {
+        e.prevent_default();
+        editor_state.dispatch(EditorMessage::DismissToast(id));
+    });
+
+    let (class, label) = match props.toast.severity {
+        state::Severity::Info => ("info", "Note"),
+        state::Severity::Warning => ("warning", "Warning"),
+        state::Severity::Error => ("error", "Error"),
+    };
+
+    html! {
+        <div class={classes!("toast", class)} role="status">
+            <strong>{ label } { ": " }</strong>
+            { &props.toast.message }
+            <a href="#" class="toast-dismiss" onclick={dismiss} title="Dismiss">{ "×" }</a>
+        </div>
+    }
+}
+
+/// Messages from the editor, which go away on their own after a few seconds but can be
+/// looked back over afterwards
+#[function_component]
+fn Toasts() -> Html {
+    let editor_state = use_context::<EditorStateContext>().unwrap();
+    let toasts = &editor_state.toasts;
+
+    if toasts.shown.is_empty() && toasts.history.is_empty() {
+        return html! {};
+    }
+
+    html! {
+        <div class="toasts">
+            { for toasts.shown.iter().map(|toast| html! {
+                <ToastMessage key={toast.id} toast={toast.clone()} />
+            }) }
+
+            if !toasts.history.is_empty() {
+                <details class="toast-history">
+                    <summary>{ format!("Earlier messages ({})", toasts.history.len()) }</summary>
+
+                    <ul>
+                        { for toasts.history.iter().map(|toast| html! {
+                            <li class={match toast.severity {
+                                state::Severity::Info => "info",
+                                state::Severity::Warning => "warning",
+                                state::Severity::Error => "error",
+                            }}>
+                                { &toast.message }
+                            </li>
+                        }) }
+                    </ul>
+                </details>
+            }
+        </div>
+    }
+}
+
+/// Adds whatever the current view lists, from a floating button or by pressing N
+/// anywhere other than a text field
+#[function_component]
+fn QuickAdd() -> Html {
+    let editor_state = use_context::<EditorStateContext>().unwrap();
+
+    let (current_view, new_team) = match &editor_state.state {
+        EditingState::HasConfig { current_view, .. } if !editor_state.is_locked() => (
+            Some(*current_view),
+            Some(users::new_blue_team(editor_state.force_init().0)),
+        ),
+        _ => (None, None),
+    };
+
+    let add = match (current_view, new_team.clone()) {
+        (Some(CurrentView::Machines), _) => Some(("Add a machine (N)", {
+            let editor_state = editor_state.clone();
+            Callback::from(move |()| {
+                editor_state.dispatch(EditorMessage::AddMachine(Default::default()));
+            })
+        })),
+        (Some(CurrentView::Teams), Some(team)) => Some(("Add a blue team (N)", {
+            let editor_state = editor_state.clone();
+            Callback::from(move |()| {
+                editor_state.dispatch(EditorMessage::AddBlueTeam(team.clone()));
+            })
+        })),
+        _ => None,
+    };
+
+    {
+        let add = add.as_ref().map(|(_, add)| add.clone());
+
+        use_effect_with((current_view, new_team), move |_| {
+            let listener = add.and_then(|add| {
+                let document = window()?.document()?;
+
+                Some(EventListener::new(&document, "keydown", move |event| {
+                    let event = event.unchecked_ref::<KeyboardEvent>();
+                    if !event.key().eq_ignore_ascii_case("n")
+                        || event.ctrl_key()
+                        || event.meta_key()
+                        || event.alt_key()
+                    {
+                        return;
+                    }
+
+                    let typing = event
+                        .target()
+                        .and_then(|target| target.dyn_into::<HtmlElement>().ok())
+                        .is_some_and(|target| {
+                            matches!(target.tag_name().as_str(), "INPUT" | "TEXTAREA" | "SELECT")
+                                || target.is_content_editable()
+                        });
+                    if typing {
+                        return;
+                    }
+
+                    event.prevent_default();
+                    add.emit(());
+                }))
+            });
+
+            move || drop(listener)
+        });
+    }
+
+    let Some((title, add)) = add else {
+        return html! {};
+    };
+
+    let onclick = Callback::from(move |e: MouseEvent| {
+        e.prevent_default();
+        add.emit(());
+    });
+
+    html! {
+        <a href="#" class="quick-add" {title} {onclick}>{ "+" }</a>
+    }
+}
+
+#[function_component]
+fn RecentlyDeleted() -> Html {
+    let editor_state = use_context::<EditorStateContext>().unwrap();
+
+    if editor_state.deleted.is_empty() {
+        return html! {};
+    }
+
+    let items = editor_state
+        .deleted
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(i, item)| {
+            let restore = {
+                let editor_state = editor_state.clone();
+                Callback::from(move |e: MouseEvent| {
+                    e.prevent_default();
+                    editor_state.dispatch(EditorMessage::RestoreDeleted(i));
+                })
+            };
+
+            html! {
+                <li key={i}>
+                    { "Deleted " }
+                    { item.description() }
+                    { " " }
+                    <a href="#" onclick={restore}>{ "Restore" }</a>
+                </li>
+            }
+        });
+
+    html! {
+        <div class="recently-deleted">
+            <h4>{ "Recently deleted" }</h4>
+
+            <ul>
+                { for items }
+            </ul>
+        </div>
+    }
+}
+
 #[function_component]
 fn App() -> Html {
+    let tour_step = use_state(|| (!tour::seen()).then_some(0usize));
+
+    let set_tour_step = {
+        let tour_step = tour_step.clone();
+        Callback::from(move |step| tour_step.set(step))
+    };
+
+    let start_tour = {
+        let tour_step = tour_step.clone();
+        Callback::from(move |()| tour_step.set(Some(0)))
+    };
+
+    let tour_view = tour_step
+        .and_then(|step| tour::STEPS.get(step))
+        .map(|(view, _)| *view);
+
     html! {
         <state::EditorStateProvider>
             <header>
                 <h2>{ "Scoring Engine Configuration Editor" }</h2>
             </header>
 
-            <NavBar />
+            <NavBar {tour_view} {start_tour} />
+
+            if let Some(step) = *tour_step {
+                <tour::Tour {step} set_step={set_tour_step} />
+            } else {
+                <whats_new::WhatsNew />
+            }
 
             <MainContent />
+
+            <HealthNotice />
+
+            <Toasts />
+
+            <RecentlyDeleted />
+
+            <QuickAdd />
         </state::EditorStateProvider>
     }
 }
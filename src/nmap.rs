@@ -0,0 +1,275 @@
+// nmap.rs: Builds machines out of the hosts and open ports found by an Nmap scan
+//
+// Copyright (C) 2023 Andrew Rioux
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use web_sys::HtmlTextAreaElement;
+use yew::prelude::*;
+
+use crate::{
+    config::MachineEditor,
+    machines::palette,
+    state::{EditorMessage, EditorStateContext},
+};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ScannedHost {
+    pub address: String,
+    pub hostname: Option<String>,
+    pub open_ports: Vec<u16>,
+}
+
+impl ScannedHost {
+    fn add_port(&mut self, port: u16) {
+        if !self.open_ports.contains(&port) {
+            self.open_ports.push(port);
+        }
+    }
+}
+
+/// The value of an attribute in the text of an XML tag
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let pattern = format!(" {name}=\"");
+    let start = tag.find(&pattern)? + pattern.len();
+    let end = tag[start..].find('"')? + start;
+
+    Some(&tag[start..end])
+}
+
+/// Reads the hosts out of the XML written by `nmap -oX`
+fn parse_xml(xml: &str) -> Vec<ScannedHost> {
+    let mut hosts = vec![];
+
+    for block in xml.split("</host>") {
+        // Anything before the host's own tag, such as the hints newer releases write
+        // between hosts, isn't part of the host
+        let Some(start) = block.rfind("<host ").or_else(|| block.rfind("<host>")) else {
+            continue;
+        };
+
+        let mut host = ScannedHost {
+            address: String::new(),
+            hostname: None,
+            open_ports: vec![],
+        };
+        let mut port = None;
+
+        for tag in block[start..].split('<') {
+            if tag.starts_with("address ") {
+                let is_ip = matches!(attribute(tag, "addrtype"), Some("ipv4" | "ipv6"));
+                if let Some(address) = attribute(tag, "addr").filter(|_| is_ip) {
+                    host.address = address.to_owned();
+                }
+            } else if tag.starts_with("hostname ") && host.hostname.is_none() {
+                host.hostname = attribute(tag, "name").map(str::to_owned);
+            } else if tag.starts_with("port ") {
+                port = attribute(tag, "portid").and_then(|port| port.parse::<u16>().ok());
+            } else if tag.starts_with("state ") && attribute(tag, "state") == Some("open") {
+                if let Some(port) = port.take() {
+                    host.add_port(port);
+                }
+            }
+        }
+
+        if !host.address.is_empty() {
+            hosts.push(host);
+        }
+    }
+
+    hosts
+}
+
+/// Reads the hosts out of the greppable output written by `nmap -oG`, where each host
+/// has a line like `Host: 10.0.0.5 (web.local)`, a tab, then `Ports: 22/open/tcp//ssh///, ...`
+fn parse_greppable(text: &str) -> Vec<ScannedHost> {
+    let mut hosts: Vec<ScannedHost> = vec![];
+
+    for line in text.lines() {
+        let Some(line) = line.strip_prefix("Host: ") else {
+            continue;
+        };
+
+        let mut words = line.split_whitespace();
+        let Some(address) = words.next() else {
+            continue;
+        };
+        let hostname = words
+            .next()
+            .and_then(|name| name.strip_prefix('('))
+            .and_then(|name| name.strip_suffix(')'))
+            .filter(|name| !name.is_empty());
+
+        let index = match hosts.iter().position(|host| host.address == address) {
+            Some(index) => index,
+            None => {
+                hosts.push(ScannedHost {
+                    address: address.to_owned(),
+                    hostname: None,
+                    open_ports: vec![],
+                });
+                hosts.len() - 1
+            }
+        };
+        let host = &mut hosts[index];
+
+        if host.hostname.is_none() {
+            host.hostname = hostname.map(str::to_owned);
+        }
+
+        let Some((_, ports)) = line.split_once("Ports: ") else {
+            continue;
+        };
+        let ports = ports.split('\t').next().unwrap_or_default();
+
+        for port in ports.split(',') {
+            let mut fields = port.trim().split('/');
+            let (Some(port), Some("open")) = (fields.next(), fields.next()) else {
+                continue;
+            };
+            if let Ok(port) = port.parse::<u16>() {
+                host.add_port(port);
+            }
+        }
+    }
+
+    hosts
+}
+
+/// Reads the hosts out of Nmap output, either XML or greppable
+pub fn parse_scan(text: &str) -> Vec<ScannedHost> {
+    if text.contains("<nmaprun") {
+        parse_xml(text)
+    } else {
+        parse_greppable(text)
+    }
+}
+
+/// A machine for a scanned host, guessing a service for every open port that is the
+/// default port of one of the services in the service list, along with the ports
+/// nothing could be guessed for
+pub fn machine_from_scan(host: &ScannedHost) -> (MachineEditor, Vec<u16>) {
+    let palette = palette();
+    let mut unknown_ports = vec![];
+
+    let services = host
+        .open_ports
+        .iter()
+        .filter_map(|port| {
+            let service = palette
+                .iter()
+                .find(|service| service.port == *port && !service.definition.is_portless())
+                .cloned();
+            if service.is_none() {
+                unknown_ports.push(*port);
+            }
+            service
+        })
+        .collect();
+
+    let name = host
+        .hostname
+        .as_deref()
+        .and_then(|hostname| hostname.split('.').next())
+        .filter(|name| !name.is_empty())
+        .unwrap_or(&host.address);
+
+    let machine = MachineEditor {
+        name: name.to_owned(),
+        services,
+        ip_template: host.address.clone(),
+        ..MachineEditor::default()
+    };
+
+    (machine, unknown_ports)
+}
+
+#[function_component]
+pub fn ScanImport() -> Html {
+    let editor_state = use_context::<EditorStateContext>().unwrap();
+
+    let scan = use_state(String::new);
+
+    let on_input = {
+        let scan = scan.clone();
+        Callback::from(move |e: InputEvent| {
+            scan.set(e.target_unchecked_into::<HtmlTextAreaElement>().value());
+        })
+    };
+
+    let machines = parse_scan(&scan)
+        .iter()
+        .map(machine_from_scan)
+        .collect::<Vec<_>>();
+
+    let add_machines = {
+        let editor_state = editor_state.clone();
+        let machines = machines.clone();
+        let scan = scan.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            for (machine, _) in &machines {
+                editor_state.dispatch(EditorMessage::AddMachine(machine.clone()));
+            }
+            scan.set(String::new());
+        })
+    };
+
+    html! {
+        <details class="scan-import">
+            <summary>{ "Import machines from an Nmap scan" }</summary>
+
+            <textarea
+                placeholder="Paste the output of nmap -oG or nmap -oX"
+                value={scan.to_string()}
+                oninput={on_input}
+            />
+
+            if !scan.trim().is_empty() {
+                if machines.is_empty() {
+                    <p>{ "No hosts were found in the scan" }</p>
+                } else {
+                    <ul>
+                        { for machines.iter().map(|(machine, unknown_ports)| html! {
+                            <li>
+                                <strong>{ format!("{} ({}): ", machine.name, machine.ip_template) }</strong>
+                                if machine.services.is_empty() {
+                                    { "no services guessed" }
+                                } else {
+                                    { machine.services.iter().map(|service| service.name.as_str()).collect::<Vec<_>>().join(", ") }
+                                }
+                                if !unknown_ports.is_empty() {
+                                    <span class="scan-unknown-ports">
+                                        { format!(
+                                            "; nothing guessed for port {}",
+                                            unknown_ports.iter().map(u16::to_string).collect::<Vec<_>>().join(", ")
+                                        ) }
+                                    </span>
+                                }
+                            </li>
+                        }) }
+                    </ul>
+
+                    <a href="#" class="button edit-action" onclick={add_machines}>
+                        { match machines.len() {
+                            1 => "Add 1 machine".to_owned(),
+                            n => format!("Add {n} machines"),
+                        } }
+                    </a>
+                }
+            }
+        </details>
+    }
+}
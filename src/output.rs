@@ -15,10 +15,100 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use web_sys::{window, Document, HtmlElement};
+use std::cell::Cell;
+
+use gloo_timers::callback::Timeout;
+use gloo_worker::Spawnable;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{
+    window, Document, HtmlElement, HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement,
+};
 use yew::prelude::*;
 
-use crate::{config::convert_editor_to_final, error::EditorError};
+use crate::{
+    compare::ChangesSinceExport,
+    components::Modal,
+    config::{
+        convert_editor_to_final, ip_plan, ConfigurationEditor, ConfigurationStats,
+        ConfigurationSummary, EngineVersion,
+    },
+    export,
+    flags::FlagsEditor,
+    review::duplicate_findings,
+    state::{EditorMessage, ExportSnapshot},
+    warnings::collect_warnings,
+    worker::{ConversionRequest, ConversionWorker, Converted, JsonCodec, OutputFormat},
+};
+
+const CONVERSION_WORKER_PATH: &str = "./conversion_worker.js";
+
+/// A file the user asked for, held back until they confirm the export summary
+#[derive(Debug, PartialEq, Clone)]
+struct PendingDownload {
+    description: &'static str,
+    file_name: String,
+    mime_type: &'static str,
+    contents: String,
+    /// The generated configuration being downloaded, remembered as exported once the
+    /// download is confirmed
+    snapshot: Option<ExportSnapshot>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum OutputMode {
+    Yaml,
+    YamlWithProvenance,
+    CliCommand,
+    YamlWithEditorInfo,
+}
+
+/// Shown in place of the export time until the configuration is downloaded or copied
+const NOT_YET_EXPORTED: &str = "when downloaded or copied";
+
+/// The text given out for a converted configuration in the chosen format and mode
+fn output_text(
+    converted: &Converted,
+    config: &ConfigurationEditor,
+    config_name: &str,
+    format: OutputFormat,
+    mode: OutputMode,
+    comment_services: bool,
+    exported_at: &str,
+) -> String {
+    // The comments and document marker are written as YAML text, so they're
+    // left out of JSON
+    let text = match format {
+        OutputFormat::Yaml => {
+            let yaml = if comment_services {
+                export::comment_services(&converted.text, config)
+            } else {
+                converted.text.clone()
+            };
+            format!("---\n{yaml}")
+        }
+        OutputFormat::Json => converted.text.clone(),
+    };
+
+    match mode {
+        OutputMode::Yaml | OutputMode::YamlWithEditorInfo => text,
+        OutputMode::YamlWithProvenance if format == OutputFormat::Json => text,
+        OutputMode::YamlWithProvenance => format!(
+            "{}{text}",
+            export::provenance_header(config_name, exported_at)
+        ),
+        OutputMode::CliCommand => export::cli_command(
+            config.cli_command_template(),
+            &export::file_name(
+                config_name,
+                match format {
+                    OutputFormat::Yaml => "yaml",
+                    OutputFormat::Json => "json",
+                },
+            ),
+            &text,
+        ),
+    }
+}
 
 #[function_component]
 pub fn ConfigurationOutput() -> Html {
@@ -27,14 +117,351 @@ pub fn ConfigurationOutput() -> Html {
 
     let text_display_ref = use_node_ref();
 
-    let result = convert_editor_to_final(config)
-        .map_err(EditorError::Conversion)
-        .and_then(|(conf, _)| serde_yaml::to_string(&conf).map_err(EditorError::Serialize));
+    // Only worked out again when the configuration changes, rather than on every render
+    let warnings = use_memo(config.clone(), collect_warnings);
+    let summary = use_memo(config.clone(), ConfigurationSummary::of);
+    let duplicates = use_memo(config.clone(), |config| duplicate_findings(config).len());
 
-    let onclick = {
-        let text_display_ref = text_display_ref.clone();
+    // None until the worker has answered once; afterwards the latest answer is kept
+    // while newer versions of the configuration are being converted
+    let result = use_state(Option::<Result<Converted, String>>::default);
+    let converting = use_state(|| true);
+    let generation = use_memo((), |_| Cell::new(0u32));
+
+    let conversion_worker = {
+        let result = result.setter();
+        let converting = converting.setter();
+        let generation = generation.clone();
+
+        use_memo((), move |_| {
+            ConversionWorker::spawner()
+                .callback(move |(answered, converted)| {
+                    if answered == generation.get() {
+                        result.set(Some(converted));
+                        converting.set(false);
+                    }
+                })
+                .encoding::<JsonCodec>()
+                .spawn(CONVERSION_WORKER_PATH)
+        })
+    };
+
+    let output_format = use_state(|| OutputFormat::Yaml);
+    let output_mode = use_state(|| OutputMode::Yaml);
+
+    {
+        let generation = generation.clone();
+        let converting = converting.setter();
+
+        use_effect_with(
+            (
+                config.clone(),
+                *output_format,
+                *output_mode == OutputMode::YamlWithEditorInfo,
+            ),
+            move |(config, format, with_editor_info)| {
+                generation.set(generation.get().wrapping_add(1));
+                converting.set(true);
+                conversion_worker.send(ConversionRequest {
+                    generation: generation.get(),
+                    config: config.clone(),
+                    format: *format,
+                    with_editor_info: *with_editor_info,
+                });
+            },
+        );
+    }
+
+    // Exporting waits for the answer to the latest request, so that what's exported
+    // is never an earlier version of the configuration
+    let converted = match (&*result, *converting) {
+        (Some(Ok(converted)), false) => Some(converted.clone()),
+        _ => None,
+    };
+
+    let set_output_format = |format| {
+        let output_format = output_format.clone();
+        let output_mode = output_mode.clone();
+
+        Callback::from(move |_: Event| {
+            // The provenance header is YAML only, so JSON goes back to the plain
+            // configuration rather than quietly leaving the header out
+            if format == OutputFormat::Json && *output_mode == OutputMode::YamlWithProvenance {
+                output_mode.set(OutputMode::Yaml);
+            }
+            output_format.set(format);
+        })
+    };
+
+    let engine_version_ref = use_node_ref();
+
+    let set_engine_version = {
+        let editor_state = editor_state.clone();
+        let engine_version_ref = engine_version_ref.clone();
 
         Callback::from(move |_| {
+            let Some(select) = engine_version_ref.cast::<HtmlSelectElement>() else {
+                return;
+            };
+            let Some(version) = EngineVersion::ALL.get(select.selected_index() as usize) else {
+                return;
+            };
+            editor_state.dispatch(EditorMessage::UpdateEngineVersion(*version));
+        })
+    };
+
+    let output_mode_ref = use_node_ref();
+
+    let set_output_mode = {
+        let output_mode = output_mode.clone();
+        let output_mode_ref = output_mode_ref.clone();
+
+        Callback::from(move |_| {
+            let Some(select) = output_mode_ref.cast::<HtmlSelectElement>() else {
+                return;
+            };
+            output_mode.set(match select.selected_index() {
+                1 => OutputMode::YamlWithProvenance,
+                2 => OutputMode::CliCommand,
+                3 => OutputMode::YamlWithEditorInfo,
+                _ => OutputMode::Yaml,
+            });
+        })
+    };
+
+    let cli_template_ref = use_node_ref();
+
+    let set_cli_template = {
+        let editor_state = editor_state.clone();
+        let cli_template_ref = cli_template_ref.clone();
+
+        Callback::from(move |_| {
+            let Some(textarea) = cli_template_ref.cast::<HtmlTextAreaElement>() else {
+                return;
+            };
+            editor_state.dispatch(EditorMessage::UpdateCliCommandTemplate(textarea.value()));
+        })
+    };
+
+    let comment_services = use_state(|| false);
+
+    let toggle_comments = {
+        let comment_services = comment_services.clone();
+
+        Callback::from(move |e: Event| {
+            comment_services.set(e.target_unchecked_into::<HtmlInputElement>().checked());
+        })
+    };
+
+    let preview = match &*result {
+        Some(Ok(converted)) => output_text(
+            converted,
+            config,
+            editor_state.config_name().unwrap_or_default(),
+            *output_format,
+            *output_mode,
+            *comment_services,
+            NOT_YET_EXPORTED,
+        ),
+        _ => String::new(),
+    };
+
+    let pending_download = use_state(Option::<PendingDownload>::default);
+
+    let download_output = {
+        let converted = converted.clone();
+        let config = config.clone();
+        let name = editor_state.config_name().unwrap_or_default().to_owned();
+        let output_mode = *output_mode;
+        let output_format = *output_format;
+        let comment_services = *comment_services;
+        let pending_download = pending_download.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+
+            let Some(converted) = &converted else {
+                return;
+            };
+
+            let (extension, mime_type) = match (output_mode, output_format) {
+                (OutputMode::CliCommand, _) => ("sh", "text/x-shellscript"),
+                (_, OutputFormat::Yaml) => ("yaml", "application/yaml"),
+                (_, OutputFormat::Json) => ("json", "application/json"),
+            };
+
+            let exported_at = String::from(js_sys::Date::new_0().to_iso_string());
+
+            pending_download.set(Some(PendingDownload {
+                description: "configuration",
+                file_name: export::file_name(&name, extension),
+                mime_type,
+                contents: output_text(
+                    converted,
+                    &config,
+                    &name,
+                    output_format,
+                    output_mode,
+                    comment_services,
+                    &exported_at,
+                ),
+                snapshot: Some(ExportSnapshot {
+                    exported_at,
+                    config: converted.config.clone(),
+                }),
+            }));
+        })
+    };
+
+    let export_ip_plan = {
+        let config = config.clone();
+        let name = editor_state.config_name().unwrap_or_default().to_owned();
+        let pending_download = pending_download.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+
+            let Ok(plan) = ip_plan(&config) else {
+                return;
+            };
+
+            let rows = plan
+                .into_iter()
+                .map(|entry| {
+                    vec![
+                        entry.team,
+                        entry.machine,
+                        entry.host,
+                        entry
+                            .services
+                            .iter()
+                            .map(|(service, port)| format!("{service}:{port}"))
+                            .collect::<Vec<_>>()
+                            .join("; "),
+                    ]
+                })
+                .collect::<Vec<_>>();
+
+            pending_download.set(Some(PendingDownload {
+                description: "IP plan",
+                file_name: export::file_name(&format!("{name}-ip-plan"), "csv"),
+                mime_type: "text/csv",
+                contents: export::to_csv(&["team", "machine", "host", "services"], &rows),
+                snapshot: None,
+            }));
+        })
+    };
+
+    let export_roster = {
+        let config = config.clone();
+        let name = editor_state.config_name().unwrap_or_default().to_owned();
+        let pending_download = pending_download.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+
+            let Ok((final_config, _)) = convert_editor_to_final(&config) else {
+                return;
+            };
+
+            pending_download.set(Some(PendingDownload {
+                description: "user roster",
+                file_name: export::file_name(&format!("{name}-roster"), "csv"),
+                mime_type: "text/csv",
+                contents: export::to_csv(
+                    &["team", "color", "username", "password"],
+                    &export::roster_rows(&final_config),
+                ),
+                snapshot: None,
+            }));
+        })
+    };
+
+    let export_answer_key = {
+        let config = config.clone();
+        let name = editor_state.config_name().unwrap_or_default().to_owned();
+        let pending_download = pending_download.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+
+            pending_download.set(Some(PendingDownload {
+                description: "answer key",
+                file_name: export::file_name(&format!("{name}-answer-key"), "md"),
+                mime_type: "text/markdown",
+                contents: export::answer_key(&name, &config),
+                snapshot: None,
+            }));
+        })
+    };
+
+    let export_stats = {
+        let config = config.clone();
+        let name = editor_state.config_name().unwrap_or_default().to_owned();
+        let pending_download = pending_download.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+
+            let stats = match serde_json::to_string_pretty(&ConfigurationStats::of(&config)) {
+                Ok(stats) => stats,
+                Err(e) => {
+                    log::error!("Could not serialize summary statistics: {e:?}");
+                    return;
+                }
+            };
+
+            pending_download.set(Some(PendingDownload {
+                description: "summary statistics",
+                file_name: export::file_name(&format!("{name}-stats"), "json"),
+                mime_type: "application/json",
+                contents: stats,
+                snapshot: None,
+            }));
+        })
+    };
+
+    let confirm_download = {
+        let editor_state = editor_state.clone();
+        let pending_download = pending_download.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            let Some(download) = &*pending_download else {
+                return;
+            };
+
+            match export::download(&download.file_name, download.mime_type, &download.contents) {
+                Ok(()) => {
+                    if let Some(snapshot) = &download.snapshot {
+                        editor_state.dispatch(EditorMessage::RecordExport(snapshot.clone()));
+                    }
+                }
+                Err(e) => log::error!("Could not export {}: {e:?}", download.description),
+            }
+            pending_download.set(None);
+        })
+    };
+
+    let close_download = {
+        let pending_download = pending_download.clone();
+
+        Callback::from(move |()| pending_download.set(None))
+    };
+
+    let cancel_download = {
+        let close_download = close_download.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            close_download.emit(());
+        })
+    };
+
+    let select_output = {
+        let text_display_ref = text_display_ref.clone();
+
+        Callback::from(move |()| {
             let range = Document::new().unwrap().create_range().unwrap();
             range
                 .select_node(&text_display_ref.cast::<HtmlElement>().unwrap())
@@ -49,20 +476,276 @@ pub fn ConfigurationOutput() -> Html {
         })
     };
 
+    let copied = use_state(|| false);
+
+    {
+        let copied = copied.clone();
+
+        use_effect_with(*copied, move |shown| {
+            let timeout = shown.then(|| Timeout::new(2_000, move || copied.set(false)));
+
+            move || drop(timeout)
+        });
+    }
+
+    let copy_output = {
+        let editor_state = editor_state.clone();
+        let converted = converted.clone();
+        let config = config.clone();
+        let name = editor_state.config_name().unwrap_or_default().to_owned();
+        let output_mode = *output_mode;
+        let output_format = *output_format;
+        let comment_services = *comment_services;
+        let copied = copied.clone();
+        let select_output = select_output.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+
+            let Some(converted) = &converted else {
+                return;
+            };
+
+            let exported_at = String::from(js_sys::Date::new_0().to_iso_string());
+            let text = output_text(
+                converted,
+                &config,
+                &name,
+                output_format,
+                output_mode,
+                comment_services,
+                &exported_at,
+            );
+
+            // Without the clipboard API, the text is selected for copying by hand
+            let Some(copying) = export::copy_to_clipboard(&text) else {
+                select_output.emit(());
+                return;
+            };
+
+            let snapshot = ExportSnapshot {
+                exported_at,
+                config: converted.config.clone(),
+            };
+            let editor_state = editor_state.clone();
+            let copied = copied.clone();
+            let select_output = select_output.clone();
+            spawn_local(async move {
+                match copying.await {
+                    Ok(_) => {
+                        copied.set(true);
+                        editor_state.dispatch(EditorMessage::RecordExport(snapshot));
+                    }
+                    Err(e) => {
+                        log::error!("Could not copy the configuration: {e:?}");
+                        select_output.emit(());
+                    }
+                }
+            });
+        })
+    };
+
     html! {
         <main id="output">
-            if let Err(err) = &result {
+            if let Some(Err(err)) = &*result {
                 <div id="error">
                 {format!("{}", err)}
                 </div>
             }
 
-            <pre ref={text_display_ref} {onclick}>
-                { "---\n" }
-                if let Ok(yaml) = &result {
-                    {yaml}
-                    { "\n\nflags: []" }
+            if *converting {
+                <div class="output-pending">
+                    { if result.is_none() {
+                        "Converting configuration..."
+                    } else {
+                        "Converting the latest changes; exporting is available once they're done..."
+                    } }
+                </div>
+            }
+
+            <div class="output-settings">
+                <label>{ "Target engine version: " }</label>
+
+                <select ref={engine_version_ref} onchange={set_engine_version}>
+                    { for EngineVersion::ALL.iter().map(|version| html! {
+                        <option selected={*version == config.engine_version}>
+                            { version.name() }
+                        </option>
+                    }) }
+                </select>
+
+                <label>{ "Output: " }</label>
+
+                <select ref={output_mode_ref} onchange={set_output_mode}>
+                    <option selected={*output_mode == OutputMode::Yaml}>{ "Configuration" }</option>
+                    <option
+                        selected={*output_mode == OutputMode::YamlWithProvenance}
+                        disabled={*output_format == OutputFormat::Json}
+                    >
+                        { "Configuration with provenance header (YAML only)" }
+                    </option>
+                    <option selected={*output_mode == OutputMode::CliCommand}>{ "Scoring engine CLI command" }</option>
+                    <option selected={*output_mode == OutputMode::YamlWithEditorInfo}>{ "Configuration that can be opened in the editor again" }</option>
+                </select>
+
+                <label>
+                    <input
+                        type="radio"
+                        name="output-format"
+                        checked={*output_format == OutputFormat::Yaml}
+                        onchange={set_output_format(OutputFormat::Yaml)}
+                    />
+                    { " YAML" }
+                </label>
+
+                <label>
+                    <input
+                        type="radio"
+                        name="output-format"
+                        checked={*output_format == OutputFormat::Json}
+                        onchange={set_output_format(OutputFormat::Json)}
+                    />
+                    { " JSON" }
+                </label>
+
+                <label>
+                    <input
+                        type="checkbox"
+                        checked={*comment_services}
+                        disabled={*output_format == OutputFormat::Json}
+                        onchange={toggle_comments}
+                    />
+                    { " Comment each service with its machine (YAML only)" }
+                </label>
+
+                if *output_mode == OutputMode::CliCommand {
+                    <div class="cli-template">
+                        <label>
+                            { "Command template for this engine version ({path} and {yaml} are filled in; clear to reset):" }
+                        </label>
+
+                        <textarea
+                            ref={cli_template_ref}
+                            value={config.cli_command_template().to_owned()}
+                            disabled={editor_state.is_locked()}
+                            onchange={set_cli_template}
+                        />
+                    </div>
                 }
+            </div>
+
+            <div class="output-actions">
+                <a
+                    href="#"
+                    class={classes!("button", converted.is_none().then_some("disabled"))}
+                    onclick={download_output}
+                >
+                    { "Download configuration" }
+                </a>
+
+                <a
+                    href="#"
+                    class={classes!("button", converted.is_none().then_some("disabled"))}
+                    onclick={copy_output}
+                >
+                    { if *copied { "Copied!" } else { "Copy to clipboard" } }
+                </a>
+
+                <a
+                    href="#"
+                    class={classes!("button", (!matches!(*result, Some(Ok(_)))).then_some("disabled"))}
+                    onclick={export_ip_plan}
+                >
+                    { "Export IP plan (CSV)" }
+                </a>
+
+                <a
+                    href="#"
+                    class={classes!("button", (!matches!(*result, Some(Ok(_)))).then_some("disabled"))}
+                    onclick={export_roster}
+                    title="Every team's usernames and passwords, for handing out login sheets"
+                >
+                    { "Export user roster (CSV)" }
+                </a>
+
+                <a
+                    href="#"
+                    class="button"
+                    onclick={export_answer_key}
+                    title="What each check looks for and what passing it means, for the white team"
+                >
+                    { "Export answer key (Markdown)" }
+                </a>
+
+                <a href="#" class="button" onclick={export_stats}>
+                    { "Export summary statistics (JSON)" }
+                </a>
+            </div>
+
+            <FlagsEditor />
+
+            <ChangesSinceExport
+                current={result
+                    .as_ref()
+                    .and_then(|result| result.as_ref().ok())
+                    .map(|converted| converted.config.clone())}
+                converting={*converting}
+            />
+
+            if !warnings.is_empty() {
+                <div class="warnings">
+                    <h4>{ "Warnings" }</h4>
+
+                    <ul>
+                        { for warnings.iter().map(|warning| html! {
+                            <li>{ warning.to_string() }</li>
+                        }) }
+                    </ul>
+                </div>
+            }
+
+            if let Some(download) = &*pending_download {
+                <Modal class="export-summary" on_close={close_download}>
+                    <h3>{ format!("Export {}?", download.description) }</h3>
+
+                    <dl>
+                        <dt>{ "Blue teams" }</dt>
+                        <dd>{ summary.blue_teams }</dd>
+                        <dt>{ "Machines" }</dt>
+                        <dd>{ summary.machines }</dd>
+                        <dt>{ "Services per team" }</dt>
+                        <dd>{ summary.services_per_team.to_string() }</dd>
+                        <dt>{ "Points per team" }</dt>
+                        <dd>{ summary.points_per_team.to_string() }</dd>
+                        <dt>{ "Warnings" }</dt>
+                        <dd>{ warnings.len() }</dd>
+                        <dt>{ "Duplicates (see Review)" }</dt>
+                        <dd>{ *duplicates }</dd>
+                        if !config.description.is_empty() {
+                            <dt>{ "Network notes" }</dt>
+                            <dd class="config-description">{ &config.description }</dd>
+                        }
+                    </dl>
+
+                    if !warnings.is_empty() {
+                        <ul class="warnings">
+                            { for warnings.iter().map(|warning| html! {
+                                <li>{ warning.to_string() }</li>
+                            }) }
+                        </ul>
+                    }
+
+                    <div class="modal-buttons">
+                        <a href="#" class="button" onclick={confirm_download}>
+                            { format!("Download {}", download.file_name) }
+                        </a>
+                        <a href="#" onclick={cancel_download}>{ "Cancel" }</a>
+                    </div>
+                </Modal>
+            }
+
+            <pre ref={text_display_ref} onclick={select_output.reform(|_| ())}>
+                { preview }
             </pre>
         </main>
     }
@@ -0,0 +1,237 @@
+// passwords.rs: Generates passwords, and rotates every password in a configuration at once
+//
+// Copyright (C) 2023 Andrew Rioux
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use web_sys::window;
+use yew::prelude::*;
+
+use crate::{
+    config::{ConfigurationEditor, User},
+    export,
+    state::{EditorMessage, EditorStateContext},
+};
+
+/// Characters passwords are made of, leaving out ones that are easily confused when
+/// read off a printed sheet, such as 0 and O or 1 and l
+const PASSWORD_CHARACTERS: &[u8] =
+    b"ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz23456789!@#%^*-_=+";
+
+const PASSWORD_LENGTH: usize = 16;
+
+/// The password new accounts start with, which is public knowledge and so must be
+/// replaced before a competition
+pub const DEFAULT_PASSWORD: &str = "Chiapet1!";
+
+/// Generates a random password using the browser's cryptographic random numbers, or
+/// explains why it couldn't
+pub fn generate_password() -> Result<String, String> {
+    let mut password = String::with_capacity(PASSWORD_LENGTH);
+    // The largest multiple of the alphabet size that fits in a byte, so that bytes
+    // past it can be thrown away rather than favoring the start of the alphabet
+    let limit = 256 - 256 % PASSWORD_CHARACTERS.len();
+
+    let crypto = window()
+        .and_then(|window| window.crypto().ok())
+        .ok_or("this browser has no secure random numbers to generate passwords with")?;
+
+    while password.len() < PASSWORD_LENGTH {
+        let mut bytes = [0u8; PASSWORD_LENGTH];
+        if let Err(e) = crypto.get_random_values_with_u8_array(&mut bytes) {
+            log::error!("Could not generate a password: {e:?}");
+            return Err("could not generate a password".to_owned());
+        }
+
+        password.extend(
+            bytes
+                .iter()
+                .map(|byte| usize::from(*byte))
+                .filter(|byte| *byte < limit)
+                .map(|byte| char::from(PASSWORD_CHARACTERS[byte % PASSWORD_CHARACTERS.len()]))
+                .take(PASSWORD_LENGTH - password.len()),
+        );
+    }
+
+    Ok(password)
+}
+
+/// A login whose password was changed by a rotation
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Credential {
+    pub owner: String,
+    pub username: String,
+    pub password: String,
+}
+
+/// Every user whose password a rotation changes, along with who they belong to
+fn users(config: &mut ConfigurationEditor) -> Vec<(String, &mut User)> {
+    let mut users = vec![];
+
+    for team in &mut config.red_white_teams {
+        let owner = format!("team {}", team.name);
+        users.extend(team.users.iter_mut().map(|user| (owner.clone(), user)));
+    }
+
+    for team in &mut config.blue_teams {
+        let owner = format!("blue team {}", team.name);
+        users.extend(team.users.iter_mut().map(|user| (owner.clone(), user)));
+    }
+
+    for machine in &mut config.machines {
+        for service in &mut machine.services {
+            let owner = format!("service {} on machine {}", service.name, machine.name);
+            users.extend(
+                service
+                    .accounts
+                    .iter_mut()
+                    .flatten()
+                    .map(|user| (owner.clone(), user)),
+            );
+        }
+    }
+
+    users
+}
+
+/// Gives every team user and service account a new password from `generate`,
+/// returning the changed configuration along with the new credentials, or the first
+/// error `generate` gives
+pub fn rotate_passwords<E>(
+    config: &ConfigurationEditor,
+    mut generate: impl FnMut() -> Result<String, E>,
+) -> Result<(ConfigurationEditor, Vec<Credential>), E> {
+    let mut config = config.clone();
+
+    let credentials = users(&mut config)
+        .into_iter()
+        .map(|(owner, user)| {
+            user.password = generate()?;
+
+            Ok(Credential {
+                owner,
+                username: user.username.clone(),
+                password: user.password.clone(),
+            })
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok((config, credentials))
+}
+
+#[function_component]
+pub fn RotatePasswords() -> Html {
+    let editor_state = use_context::<EditorStateContext>().unwrap();
+    let (config, _, _, _) = editor_state.force_init();
+
+    let rotated = use_state(Option::<Vec<Credential>>::default);
+
+    let rotate = {
+        let editor_state = editor_state.clone();
+        let config = config.clone();
+        let rotated = rotated.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+
+            let confirmed = window()
+                .and_then(|window| {
+                    window
+                        .confirm_with_message(
+                            "Replace the password of every team user and service account? The old passwords can't be recovered.",
+                        )
+                        .ok()
+                })
+                .unwrap_or(false);
+            if !confirmed {
+                return;
+            }
+
+            let credentials = match rotate_passwords(&config, generate_password) {
+                Ok((_, credentials)) => credentials,
+                Err(e) => {
+                    editor_state.dispatch(EditorMessage::Error(e));
+                    return;
+                }
+            };
+            editor_state.dispatch(EditorMessage::RotatePasswords(
+                credentials
+                    .iter()
+                    .map(|credential| credential.password.clone())
+                    .collect(),
+            ));
+            rotated.set(Some(credentials));
+        })
+    };
+
+    let download = {
+        let rotated = rotated.clone();
+        let name = editor_state.config_name().unwrap_or_default().to_owned();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            let Some(credentials) = &*rotated else {
+                return;
+            };
+
+            let rows = credentials
+                .iter()
+                .map(|credential| {
+                    vec![
+                        credential.owner.clone(),
+                        credential.username.clone(),
+                        credential.password.clone(),
+                    ]
+                })
+                .collect::<Vec<_>>();
+
+            if let Err(e) = export::download(
+                &export::file_name(&format!("{name}-credentials"), "csv"),
+                "text/csv",
+                &export::to_csv(&["owner", "username", "password"], &rows),
+            ) {
+                log::error!("Could not export the new credentials: {e:?}");
+            }
+        })
+    };
+
+    let dismiss = {
+        let rotated = rotated.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            rotated.set(None);
+        })
+    };
+
+    html! {
+        <div class="rotate-passwords">
+            <a href="#" class="edit-action" onclick={rotate}>
+                { "Rotate all passwords" }
+            </a>
+
+            if let Some(credentials) = &*rotated {
+                <div class="rotated-passwords">
+                    { match credentials.len() {
+                        1 => "1 password was replaced. ".to_owned(),
+                        n => format!("{n} passwords were replaced. "),
+                    } }
+                    <a href="#" onclick={download}>{ "Download the new credentials (CSV)" }</a>
+                    { " " }
+                    <a href="#" onclick={dismiss}>{ "Dismiss" }</a>
+                </div>
+            }
+        </div>
+    }
+}
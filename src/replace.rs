@@ -0,0 +1,306 @@
+// replace.rs: Find and replace across all the text of a configuration
+//
+// Copyright (C) 2023 Andrew Rioux
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+use crate::{
+    config::{self, ConfigurationEditor, ServiceDefinition, User},
+    state::{EditorMessage, EditorStateContext},
+};
+
+trait TextFields {
+    fn text_fields(&mut self) -> Vec<(&'static str, &mut String)>;
+}
+
+macro_rules! text_fields {
+    ($($check:ty => ($($field:ident),*)),*) => {
+        $(
+            impl TextFields for $check {
+                fn text_fields(&mut self) -> Vec<(&'static str, &mut String)> {
+                    vec![$((stringify!($field), &mut self.$field)),*]
+                }
+            }
+        )*
+    };
+}
+
+text_fields! {
+    config::DnsCheckInfo => (matching_content, qtype, domain),
+    config::DockerCheckInfo => (matching_content, image),
+    config::ElasticsearchCheckInfo => (matching_content, index, doc_type),
+    config::FtpCheckInfo => (matching_content, remotefilepath, filecontents),
+    config::HttpCheckInfo => (matching_content, useragent, vhost, uri),
+    config::ImapCheckInfo => (matching_content, domain),
+    config::LdapCheckInfo => (matching_content, domain, base_dn),
+    config::SqlCheckInfo => (matching_content, database, command),
+    config::NfsCheckInfo => (matching_content, remotefilepath, filecontents),
+    config::PopCheckInfo => (matching_content, domain),
+    config::SmbCheckInfo => (matching_content, remote_name, share, file, hash),
+    config::SmtpCheckInfo => (matching_content, touser, subject, body),
+    config::RemoteCommandCheckInfo => (matching_content, commands)
+}
+
+fn check_fields<'a, T: TextFields>(
+    location: &str,
+    checks: &'a mut [T],
+) -> Vec<(String, &'a mut String)> {
+    checks
+        .iter_mut()
+        .enumerate()
+        .flat_map(|(i, check)| {
+            check
+                .text_fields()
+                .into_iter()
+                .map(move |(field, value)| (format!("{location} > check {} {field}", i + 1), value))
+        })
+        .collect()
+}
+
+fn user_fields<'a>(location: &str, users: &'a mut [User]) -> Vec<(String, &'a mut String)> {
+    users
+        .iter_mut()
+        .enumerate()
+        .flat_map(|(i, user)| {
+            [
+                (
+                    format!("{location} > user {} username", i + 1),
+                    &mut user.username,
+                ),
+                (
+                    format!("{location} > user {} password", i + 1),
+                    &mut user.password,
+                ),
+            ]
+        })
+        .collect()
+}
+
+fn definition_fields<'a>(
+    location: &str,
+    definition: &'a mut ServiceDefinition,
+) -> Vec<(String, &'a mut String)> {
+    use ServiceDefinition::*;
+
+    match definition {
+        Dns { environment } => check_fields(location, environment),
+        Docker { environment } => check_fields(location, environment),
+        Elasticsearch { environment } => check_fields(location, environment),
+        Ftp { environment } => check_fields(location, environment),
+        Http { environment } | Https { environment } | Wordpress { environment } => {
+            check_fields(location, environment)
+        }
+        Imap { environment } | Imaps { environment } => check_fields(location, environment),
+        Ldap { environment } => check_fields(location, environment),
+        Mssql { environment } | Mysql { environment } | PostgreSql { environment } => {
+            check_fields(location, environment)
+        }
+        Nfs { environment } => check_fields(location, environment),
+        Pop3 { environment } | Pop3s { environment } => check_fields(location, environment),
+        Smb { environment } => check_fields(location, environment),
+        Smtp { environment } | Smtps { environment } => check_fields(location, environment),
+        Ssh { environment } | WinRm { environment } => check_fields(location, environment),
+        Icmp { environment } | Rdp { environment } | Vnc { environment } => environment
+            .iter_mut()
+            .map(|matcher| (format!("{location} > matching_content"), matcher))
+            .collect(),
+    }
+}
+
+/// Every piece of free text in a configuration, along with a description of where it
+/// is; names that select between options, such as the engine version, are left out
+fn text_fields(config: &mut ConfigurationEditor) -> Vec<(String, &mut String)> {
+    let mut fields = vec![
+        ("description".to_owned(), &mut config.description),
+        ("base IP template".to_owned(), &mut config.base_ip_template),
+    ];
+
+    for team in &mut config.red_white_teams {
+        let location = format!("team {}", team.name);
+        fields.extend(user_fields(&location, &mut team.users));
+        fields.push((format!("{location} > name"), &mut team.name));
+    }
+
+    for team in &mut config.blue_teams {
+        let location = format!("blue team {}", team.name);
+        fields.extend(user_fields(&location, &mut team.users));
+        fields.push((format!("{location} > name"), &mut team.name));
+    }
+
+    for machine in &mut config.machines {
+        let location = format!("machine {}", machine.name);
+
+        for service in &mut machine.services {
+            let location = format!("{location} > service {}", service.name);
+            fields.extend(definition_fields(&location, &mut service.definition));
+            if let Some(accounts) = &mut service.accounts {
+                fields.extend(user_fields(&location, accounts));
+            }
+            fields.push((format!("{location} > name"), &mut service.name));
+        }
+
+        fields.push((
+            format!("{location} > IP template"),
+            &mut machine.ip_template,
+        ));
+        if let Some(base_host) = &mut machine.base_host {
+            fields.push((format!("{location} > host on base template"), base_host));
+        }
+        if let Some(hostname) = &mut machine.hostname_template {
+            fields.push((format!("{location} > hostname"), hostname));
+        }
+        if let Some(group) = &mut machine.group {
+            fields.push((format!("{location} > group"), group));
+        }
+        fields.push((format!("{location} > name"), &mut machine.name));
+    }
+
+    fields
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Replacement {
+    pub location: String,
+    pub before: String,
+    pub after: String,
+}
+
+/// Replaces every occurrence of `find` in the text of a configuration, returning the
+/// changed configuration along with each field that changed
+pub fn replace_all(
+    config: &ConfigurationEditor,
+    find: &str,
+    replace: &str,
+) -> (ConfigurationEditor, Vec<Replacement>) {
+    let mut config = config.clone();
+    let mut replacements = vec![];
+
+    if find.is_empty() {
+        return (config, replacements);
+    }
+
+    for (location, value) in text_fields(&mut config) {
+        if !value.contains(find) {
+            continue;
+        }
+
+        let after = value.replace(find, replace);
+        replacements.push(Replacement {
+            location,
+            before: std::mem::replace(value, after.clone()),
+            after,
+        });
+    }
+
+    (config, replacements)
+}
+
+#[function_component]
+pub fn FindReplace() -> Html {
+    let editor_state = use_context::<EditorStateContext>().unwrap();
+    let (config, _, _, _) = editor_state.force_init();
+
+    let find = use_state(String::new);
+    let replace = use_state(String::new);
+
+    let on_find_input = {
+        let find = find.clone();
+        Callback::from(move |e: InputEvent| {
+            find.set(e.target_unchecked_into::<HtmlInputElement>().value());
+        })
+    };
+
+    let on_replace_input = {
+        let replace = replace.clone();
+        Callback::from(move |e: InputEvent| {
+            replace.set(e.target_unchecked_into::<HtmlInputElement>().value());
+        })
+    };
+
+    let (_, replacements) = replace_all(config, &find, &replace);
+
+    let apply = {
+        let editor_state = editor_state.clone();
+        let find = find.clone();
+        let replace = replace.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            if find.is_empty() {
+                return;
+            }
+            editor_state.dispatch(EditorMessage::ReplaceText(
+                find.to_string(),
+                replace.to_string(),
+            ));
+            find.set(String::new());
+        })
+    };
+
+    let locked = editor_state.is_locked();
+
+    html! {
+        <div class="find-replace">
+            <div class="find-replace-inputs">
+                <label>
+                    { "Find: " }
+                    <input value={find.to_string()} oninput={on_find_input} disabled={locked} />
+                </label>
+
+                <label>
+                    { "Replace with: " }
+                    <input value={replace.to_string()} oninput={on_replace_input} disabled={locked} />
+                </label>
+            </div>
+
+            if !find.is_empty() {
+                if replacements.is_empty() {
+                    <p>{ "No matches" }</p>
+                } else {
+                    <table class="review-matrix">
+                        <thead>
+                            <tr>
+                                <th>{ "Location" }</th>
+                                <th>{ "Before" }</th>
+                                <th>{ "After" }</th>
+                            </tr>
+                        </thead>
+
+                        <tbody>
+                            { for replacements.iter().map(|replacement| html! {
+                                <tr>
+                                    <td>{ &replacement.location }</td>
+                                    <td><pre>{ &replacement.before }</pre></td>
+                                    <td><pre>{ &replacement.after }</pre></td>
+                                </tr>
+                            }) }
+                        </tbody>
+                    </table>
+
+                    if !locked {
+                        <a href="#" class="button" onclick={apply}>
+                            { match replacements.len() {
+                                1 => "Replace in 1 field".to_owned(),
+                                n => format!("Replace in {n} fields"),
+                            } }
+                        </a>
+                    }
+                }
+            }
+        </div>
+    }
+}
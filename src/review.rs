@@ -0,0 +1,474 @@
+// review.rs: Reports for looking over a configuration as a whole before it is exported
+//
+// Copyright (C) 2023 Andrew Rioux
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+
+use web_sys::HtmlTextAreaElement;
+use yew::prelude::*;
+
+use crate::{
+    bulk::BulkServiceEdit,
+    compare::LiveComparison,
+    config::{ConfigurationEditor, IpGeneratorScheme, ServiceDefinition},
+    replace::FindReplace,
+    state::{EditorMessage, EditorStateContext},
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
+pub enum Severity {
+    /// The configuration can't be exported until this is fixed
+    Error,
+    /// Exportable, but probably not what was meant
+    Warning,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Finding {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Finding {
+    fn error(message: String) -> Self {
+        Self {
+            severity: Severity::Error,
+            message,
+        }
+    }
+
+    fn warning(message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message,
+        }
+    }
+}
+
+fn group_by<'a, K: Ord>(items: impl Iterator<Item = (K, &'a str)>) -> BTreeMap<K, Vec<&'a str>> {
+    let mut groups = BTreeMap::<K, Vec<&str>>::new();
+    for (key, item) in items {
+        groups.entry(key).or_default().push(item);
+    }
+    groups
+}
+
+/// Names reduced to their letters and digits, so "web-1" and "Web1" compare equal
+fn normalized_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Everything in the configuration that is repeated where it probably shouldn't be,
+/// errors first
+pub fn duplicate_findings(config: &ConfigurationEditor) -> Vec<Finding> {
+    let mut findings = vec![];
+
+    let machine_names = group_by(
+        config
+            .machines
+            .iter()
+            .map(|machine| (machine.name.as_str(), machine.name.as_str())),
+    );
+    for (name, machines) in &machine_names {
+        if machines.len() > 1 {
+            findings.push(Finding::error(format!(
+                "{} machines are named '{name}'",
+                machines.len()
+            )));
+        }
+    }
+
+    let similar_names = group_by(
+        machine_names
+            .keys()
+            .map(|name| (normalized_name(name), *name)),
+    );
+    for machines in similar_names.values() {
+        if machines.len() > 1 {
+            findings.push(Finding::warning(format!(
+                "the machines {} have very similar names",
+                machines.join(", ")
+            )));
+        }
+    }
+
+    for machine in &config.machines {
+        let service_names = group_by(
+            machine
+                .services
+                .iter()
+                .map(|service| (service.name.as_str(), service.name.as_str())),
+        );
+        for (name, services) in service_names {
+            if services.len() > 1 {
+                findings.push(Finding::error(format!(
+                    "machine {} has {} services named '{name}'",
+                    machine.name,
+                    services.len()
+                )));
+            }
+        }
+
+        let service_ports = group_by(
+            machine
+                .services
+                .iter()
+                .filter(|service| service.port != 0)
+                .map(|service| (service.port, service.name.as_str())),
+        );
+        for (port, services) in service_ports {
+            if services.len() > 1 {
+                findings.push(Finding::warning(format!(
+                    "the services {} on machine {} all use port {port}",
+                    services.join(", "),
+                    machine.name
+                )));
+            }
+        }
+    }
+
+    let blue_ids = group_by(
+        config
+            .blue_teams
+            .iter()
+            .map(|team| (team.id, team.name.as_str())),
+    );
+    for (id, teams) in blue_ids {
+        if teams.len() > 1 {
+            findings.push(Finding::error(format!(
+                "the blue teams {} share the ID {id}",
+                teams.join(", ")
+            )));
+        }
+    }
+
+    let team_names = group_by(
+        config
+            .red_white_teams
+            .iter()
+            .map(|team| team.name.as_str())
+            .chain(config.blue_teams.iter().map(|team| team.name.as_str()))
+            .filter(|name| !name.is_empty())
+            .map(|name| (name, name)),
+    );
+    for (name, teams) in team_names {
+        if teams.len() > 1 {
+            findings.push(Finding::error(format!(
+                "{} teams are named '{name}'",
+                teams.len()
+            )));
+        }
+    }
+
+    let usernames = group_by(
+        config
+            .blue_teams
+            .iter()
+            .flat_map(|team| {
+                team.users
+                    .iter()
+                    .map(move |user| (user, team.name.as_str()))
+            })
+            .chain(config.red_white_teams.iter().flat_map(|team| {
+                team.users
+                    .iter()
+                    .map(move |user| (user, team.name.as_str()))
+            }))
+            .map(|(user, team)| (user.username.as_str(), team)),
+    );
+    for (username, mut teams) in usernames {
+        if teams.len() < 2 {
+            continue;
+        }
+
+        teams.dedup();
+        if teams.len() > 1 {
+            findings.push(Finding::error(format!(
+                "the username {username} is used by the teams {}",
+                teams.join(", ")
+            )));
+        } else {
+            findings.push(Finding::warning(format!(
+                "the username {username} is listed more than once in team {}",
+                teams[0]
+            )));
+        }
+    }
+
+    if let IpGeneratorScheme::ReplaceXWithIdTimesMultiplierPlusOffset { .. } = config.ip_generator {
+        let offsets = group_by(config.machines.iter().filter_map(|machine| {
+            machine
+                .ip_offset
+                .map(|offset| (offset, machine.name.as_str()))
+        }));
+        for (offset, machines) in offsets {
+            if machines.len() > 1 {
+                findings.push(Finding::error(format!(
+                    "the machines {} share the IP offset {offset}",
+                    machines.join(", ")
+                )));
+            }
+        }
+    }
+
+    findings.sort_by_key(|finding| finding.severity);
+    findings
+}
+
+#[function_component]
+fn TeamCoverage() -> Html {
+    let editor_state = use_context::<EditorStateContext>().unwrap();
+    let config = editor_state.force_init().0;
+
+    if config.blue_teams.is_empty() || config.machines.is_empty() {
+        return html! {
+            <p>{ "Add blue teams and machines to see which services each team is scored on." }</p>
+        };
+    }
+
+    let rows = config.machines.iter().flat_map(|machine| {
+        machine.services.iter().map(|service| {
+            let asymmetric = config
+                .blue_teams
+                .iter()
+                .any(|team| service.excluded_teams.contains(&team.id));
+
+            html! {
+                <tr class={classes!(asymmetric.then_some("asymmetric"))}>
+                    <th>{ format!("{} / {}", machine.name, service.name) }</th>
+
+                    { for config.blue_teams.iter().map(|team| {
+                        if service.excluded_teams.contains(&team.id) {
+                            html! { <td class="excluded" title="Not scored">{ "✗" }</td> }
+                        } else {
+                            html! { <td class="included" title="Scored">{ "✓" }</td> }
+                        }
+                    }) }
+                </tr>
+            }
+        })
+    });
+
+    html! {
+        <table class="review-matrix">
+            <thead>
+                <tr>
+                    <th>{ "Machine / service" }</th>
+
+                    { for config.blue_teams.iter().map(|team| html! {
+                        <th>{ format!("{} ({})", team.name, team.id) }</th>
+                    }) }
+                </tr>
+            </thead>
+
+            <tbody>
+                { for rows }
+            </tbody>
+        </table>
+    }
+}
+
+/// Services still checking for the matcher the editor fills in for their type, which
+/// is valid but may not be what the organizers want to score on
+pub fn default_matcher_findings(config: &ConfigurationEditor) -> Vec<Finding> {
+    let mut findings = vec![];
+
+    for machine in &config.machines {
+        for service in &machine.services {
+            let Some(default) = service.definition.default_matching_content() else {
+                continue;
+            };
+
+            // Checks that can't be converted are already reported as errors elsewhere
+            let Ok(environments) = service.definition.environments(
+                &machine.name,
+                &service.name,
+                config.engine_version,
+            ) else {
+                continue;
+            };
+
+            let using_default = environments
+                .iter()
+                .filter(|environment| environment.matching_content == default)
+                .count();
+
+            if using_default == 0 {
+                continue;
+            }
+
+            let synthesized = matches!(
+                &service.definition,
+                ServiceDefinition::Icmp { environment: None }
+                    | ServiceDefinition::Rdp { environment: None }
+                    | ServiceDefinition::Vnc { environment: None }
+            );
+
+            findings.push(Finding::warning(if synthesized {
+                format!(
+                    "the service {} on machine {} has no matcher of its own, so it checks for the default '{default}'",
+                    service.name, machine.name
+                )
+            } else {
+                format!(
+                    "the service {} on machine {} checks for the default '{default}' in {using_default} of {} checks",
+                    service.name,
+                    machine.name,
+                    environments.len()
+                )
+            }));
+        }
+    }
+
+    findings
+}
+
+#[derive(Properties, PartialEq)]
+struct FindingListProps {
+    findings: Vec<Finding>,
+    empty: &'static str,
+}
+
+#[function_component]
+fn FindingList(props: &FindingListProps) -> Html {
+    if props.findings.is_empty() {
+        return html! {
+            <p>{ props.empty }</p>
+        };
+    }
+
+    html! {
+        <ul class="findings">
+            { for props.findings.iter().map(|finding| {
+                let (class, label) = match finding.severity {
+                    Severity::Error => ("error", "Error"),
+                    Severity::Warning => ("warning", "Warning"),
+                };
+
+                html! {
+                    <li class={class}>
+                        <strong>{ label } { ": " }</strong>
+                        { &finding.message }
+                    </li>
+                }
+            }) }
+        </ul>
+    }
+}
+
+#[function_component]
+pub fn ConfigurationReview() -> Html {
+    let editor_state = use_context::<EditorStateContext>().unwrap();
+    let config = editor_state.force_init().0;
+
+    let set_description = {
+        let editor_state = editor_state.clone();
+
+        Callback::from(move |e: Event| {
+            let description = e.target_unchecked_into::<HtmlTextAreaElement>().value();
+            editor_state.dispatch(EditorMessage::UpdateDescription(description));
+        })
+    };
+
+    html! {
+        <main id="review">
+            <section>
+                <h3>{ "Network notes" }</h3>
+
+                <p>
+                    { "The intended topology and scoring rationale, kept with the configuration for the people editing it. These notes are never part of the generated configuration." }
+                </p>
+
+                <textarea
+                    class="config-description"
+                    value={config.description.clone()}
+                    onchange={set_description}
+                    disabled={editor_state.is_locked()}
+                    placeholder="e.g. Each team gets a /24; the web server depends on the database for its login page"
+                />
+            </section>
+
+            <section>
+                <h3>{ "Team coverage" }</h3>
+
+                <p>
+                    { "Every blue team is scored on the same services unless services exclude them. Rows that differ between teams are highlighted so you can confirm the imbalance is intended." }
+                </p>
+
+                <TeamCoverage />
+            </section>
+
+            <section>
+                <h3>{ "Duplicates" }</h3>
+
+                <p>
+                    { "Names, ports, IDs, usernames and offsets that repeat, all at once rather than one conversion error at a time. Errors must be fixed before exporting; warnings are worth a second look." }
+                </p>
+
+                <FindingList
+                    findings={duplicate_findings(config)}
+                    empty="No duplicates found."
+                />
+            </section>
+
+            <section>
+                <h3>{ "Default matchers" }</h3>
+
+                <p>
+                    { "Services whose checks still look for the matching content the editor fills in for their type. Defaults are valid, but make sure they are what should be scored." }
+                </p>
+
+                <FindingList
+                    findings={default_matcher_findings(config)}
+                    empty="Every check has its own matching content."
+                />
+            </section>
+
+            <section>
+                <h3>{ "Compare with the running engine" }</h3>
+
+                <p>
+                    { "Paste the configuration a scoring engine is currently running to see which teams and services differ from what this editor would generate." }
+                </p>
+
+                <LiveComparison />
+            </section>
+
+            <section>
+                <h3>{ "Find and replace" }</h3>
+
+                <p>
+                    { "Replaces text in every name, address, account and check field at once, such as a domain when reusing a configuration for a new event. Every field that would change is listed before anything is replaced." }
+                </p>
+
+                <FindReplace />
+            </section>
+
+            <section>
+                <h3>{ "Change every service of a type" }</h3>
+
+                <p>
+                    { "Sets the port or points of every service using the same check, such as moving every web server to another port. Services with the port or points locked keep theirs." }
+                </p>
+
+                <BulkServiceEdit />
+            </section>
+        </main>
+    }
+}
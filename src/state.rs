@@ -15,28 +15,308 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::rc::Rc;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    rc::Rc,
+};
 
-use gloo_storage::{LocalStorage, Storage};
+use gloo_events::EventListener;
+use gloo_storage::{errors::StorageError, LocalStorage, SessionStorage, Storage};
 use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsCast;
+use web_sys::{window, StorageEvent};
 use yew::prelude::*;
 
 use crate::config::{
-    BlueTeamEditor, ConfigurationEditor, IpGeneratorScheme, MachineEditor, RedWhiteTeamEditor,
-    ServiceEditor,
+    BlueTeamEditor, BulkServiceChange, ConfigurationEditor, EngineVersion, FinalConfiguration,
+    Flag, IpGeneratorScheme, MachineEditor, RedWhiteTeamEditor, ServiceDefinition, ServiceEditor,
+    User,
 };
+use crate::passwords::rotate_passwords;
+use crate::replace::replace_all;
+use crate::warnings::{conversion_error_count, HealthReport};
 
 const STORAGE_KEY: &str = "stored_configurations";
 
+/// Where whatever was stored is copied when some of it can't be read, before the
+/// configurations that could be read are saved over it
+const UNREADABLE_STORAGE_KEY: &str = "stored_configurations_unreadable";
+
+/// Kept for the session only, so that autosave is back on for whoever uses the
+/// browser next
+const AUTOSAVE_DISABLED_KEY: &str = "autosave_disabled";
+
+/// Set once someone asks not to be warned about leaving a configuration with errors
+const LEAVE_WARNING_DISABLED_KEY: &str = "leave_warning_disabled";
+
+/// Whether this browser has kept configurations from an earlier visit
+pub fn has_stored_configurations() -> bool {
+    LocalStorage::raw()
+        .get_item(STORAGE_KEY)
+        .ok()
+        .flatten()
+        .is_some_and(|stored| stored.trim() != "[]")
+}
+
+/// Whether to ask before leaving a configuration that can't be converted yet
+pub fn leave_warning_enabled() -> bool {
+    !LocalStorage::get::<bool>(LEAVE_WARNING_DISABLED_KEY).unwrap_or(false)
+}
+
+pub fn set_leave_warning_enabled(enabled: bool) {
+    let result = if enabled {
+        LocalStorage::delete(LEAVE_WARNING_DISABLED_KEY);
+        Ok(())
+    } else {
+        LocalStorage::set(LEAVE_WARNING_DISABLED_KEY, true)
+    };
+    if let Err(e) = result {
+        log::error!("Could not remember the warning setting: {e:?}");
+    }
+}
+
 fn save_changes(state: EditorState) -> EditorState {
-    let _ = LocalStorage::set(STORAGE_KEY, state.configs.clone());
-    state
+    if !state.autosave {
+        return EditorState {
+            unsaved_changes: true,
+            ..state
+        };
+    }
+
+    match LocalStorage::set(STORAGE_KEY, state.configs.clone()) {
+        Ok(()) => EditorState {
+            unsaved_changes: false,
+            ..state
+        },
+        Err(e) => save_failed(state, e),
+    }
+}
+
+/// Keeps the changes in memory, still marked unsaved so that saving can be tried again
+/// once there's room, and says why they didn't make it to storage
+fn save_failed(state: EditorState, error: StorageError) -> EditorState {
+    log::error!("Could not save the configurations: {error:?}");
+
+    EditorState {
+        toasts: state.toasts.with(
+            Severity::Error,
+            "could not save: storage full or unavailable",
+        ),
+        unsaved_changes: true,
+        ..state
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct StoredConfigurations {
     pub name: String,
     pub config: ConfigurationEditor,
+    /// Locked configurations can be viewed and exported, but not changed
+    #[serde(default)]
+    pub locked: bool,
+    #[serde(default)]
+    pub last_export: Option<ExportSnapshot>,
+}
+
+/// A stored configuration as the first versions of the editor saved it, before
+/// configurations could be locked or remember their last export
+#[derive(Deserialize)]
+struct StoredConfigurationsV1 {
+    name: String,
+    config: ConfigurationEditorV1,
+}
+
+#[derive(Deserialize)]
+struct ConfigurationEditorV1 {
+    red_white_teams: Vec<RedWhiteTeamEditor>,
+    blue_teams: Vec<BlueTeamEditor>,
+    machines: Vec<MachineEditorV1>,
+    ip_generator: IpGeneratorScheme,
+}
+
+#[derive(Deserialize)]
+struct MachineEditorV1 {
+    name: String,
+    services: Vec<ServiceEditorV1>,
+    ip_template: String,
+    #[serde(default)]
+    ip_offset: Option<u8>,
+}
+
+/// Check definitions have only ever gained fields with defaults since, so they're
+/// read as they are now
+#[derive(Deserialize)]
+struct ServiceEditorV1 {
+    name: String,
+    port: u16,
+    points: u16,
+    definition: ServiceDefinition,
+    #[serde(default)]
+    accounts: Option<Vec<User>>,
+}
+
+impl From<StoredConfigurationsV1> for StoredConfigurations {
+    fn from(stored: StoredConfigurationsV1) -> Self {
+        let config = stored.config;
+
+        StoredConfigurations {
+            name: stored.name,
+            config: ConfigurationEditor {
+                red_white_teams: config.red_white_teams,
+                blue_teams: config.blue_teams,
+                machines: config
+                    .machines
+                    .into_iter()
+                    .map(MachineEditor::from)
+                    .collect(),
+                ip_generator: config.ip_generator,
+                // The Elasticsearch check still needed a document type then
+                engine_version: EngineVersion::Legacy,
+                base_ip_template: String::new(),
+                cli_command_templates: BTreeMap::new(),
+                description: String::new(),
+                flags: vec![],
+            },
+            locked: false,
+            last_export: None,
+        }
+    }
+}
+
+impl From<MachineEditorV1> for MachineEditor {
+    fn from(machine: MachineEditorV1) -> Self {
+        MachineEditor {
+            name: machine.name,
+            services: machine
+                .services
+                .into_iter()
+                .map(ServiceEditor::from)
+                .collect(),
+            ip_template: machine.ip_template,
+            ip_offset: machine.ip_offset,
+            base_host: None,
+            group: None,
+            hostname_template: None,
+        }
+    }
+}
+
+impl From<ServiceEditorV1> for ServiceEditor {
+    fn from(service: ServiceEditorV1) -> Self {
+        ServiceEditor {
+            name: service.name,
+            port: service.port,
+            points: service.points,
+            definition: service.definition,
+            accounts: service.accounts,
+            inline_accounts: false,
+            excluded_teams: BTreeSet::new(),
+            allow_unusual_port: false,
+            interval: None,
+            timeout: None,
+            informational: false,
+            expected_result: String::new(),
+            locked_fields: BTreeSet::new(),
+        }
+    }
+}
+
+/// Reads one stored configuration as it's saved now, or failing that as the first
+/// versions of the editor saved it
+fn migrate_stored_configuration(
+    stored: serde_json::Value,
+) -> Result<StoredConfigurations, serde_json::Error> {
+    serde_json::from_value::<StoredConfigurations>(stored.clone()).or_else(|e| {
+        serde_json::from_value::<StoredConfigurationsV1>(stored)
+            .map(StoredConfigurations::from)
+            .map_err(|_| e)
+    })
+}
+
+/// Reads what was stored one configuration at a time, so that one which can't be read
+/// doesn't lose the others. Gives back the configurations that could be read along
+/// with those that couldn't, as they were stored. Fails only when what was stored
+/// isn't a list of configurations at all
+fn read_stored_configurations(
+    stored: &str,
+) -> Result<(Vec<StoredConfigurations>, Vec<serde_json::Value>), serde_json::Error> {
+    if let Ok(configs) = serde_json::from_str::<Vec<StoredConfigurations>>(stored) {
+        return Ok((configs, vec![]));
+    }
+
+    let mut configs = vec![];
+    let mut unreadable = vec![];
+
+    for stored in serde_json::from_str::<Vec<serde_json::Value>>(stored)? {
+        match migrate_stored_configuration(stored.clone()) {
+            Ok(config) => configs.push(config),
+            Err(e) => {
+                log::error!("Could not read a stored configuration: {e}");
+                unreadable.push(stored);
+            }
+        }
+    }
+
+    Ok((configs, unreadable))
+}
+
+/// Reads the stored configurations, bringing any saved by older versions of the editor
+/// up to date. Those that can't be read are kept under their own key, since the ones
+/// that could be read are saved over them with the next change, and come back with a
+/// message saying so. Gives back what was stored if none of it could be read
+fn load_stored_configurations() -> Result<(Vec<StoredConfigurations>, Option<String>), String> {
+    let Ok(Some(stored)) = LocalStorage::raw().get_item(STORAGE_KEY) else {
+        return Ok((vec![], None));
+    };
+
+    let (configs, unreadable) = read_stored_configurations(&stored).map_err(|e| {
+        log::error!("Could not read the stored configurations: {e}");
+        stored.clone()
+    })?;
+
+    if unreadable.is_empty() {
+        return Ok((configs, None));
+    }
+
+    let names = unreadable
+        .iter()
+        .map(|stored| {
+            stored
+                .get("name")
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or("(unnamed)")
+                .to_owned()
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    Ok((configs, Some(set_aside(unreadable, &names))))
+}
+
+/// Keeps what couldn't be read under its own key, adding to anything set aside before,
+/// and says so in a message about `what` couldn't be read
+fn set_aside(unreadable: Vec<serde_json::Value>, what: &str) -> String {
+    let mut set_aside =
+        LocalStorage::get::<Vec<serde_json::Value>>(UNREADABLE_STORAGE_KEY).unwrap_or_default();
+    set_aside.extend(unreadable);
+
+    match LocalStorage::set(UNREADABLE_STORAGE_KEY, set_aside) {
+        Ok(()) => format!(
+            "could not read the stored configurations {what}; they have been kept under '{UNREADABLE_STORAGE_KEY}' in this browser's storage"
+        ),
+        Err(e) => {
+            log::error!("Could not set aside unreadable configurations: {e:?}");
+            format!("could not read the stored configurations {what}")
+        }
+    }
+}
+
+/// The generated configuration as it was when it was last exported, so the next
+/// export can show what has changed since
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct ExportSnapshot {
+    pub exported_at: String,
+    pub config: FinalConfiguration,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -45,47 +325,234 @@ pub enum CurrentView {
     Teams,
     Machines,
     IpSettings,
+    Accounts,
+    Review,
     Output,
 }
 
+/// Something removed from the configuration being edited, kept for the rest of the
+/// session along with where it was so it can be put back
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum DeletedItem {
+    RedWhiteTeam(usize, RedWhiteTeamEditor),
+    BlueTeam(usize, BlueTeamEditor),
+    Machine(usize, MachineEditor),
+    /// Kept with the name of its machine, which may have moved by the time it's put back
+    Service(String, usize, ServiceEditor),
+}
+
+impl DeletedItem {
+    pub fn description(&self) -> String {
+        match self {
+            Self::RedWhiteTeam(_, team) => format!("team '{}'", team.name),
+            Self::BlueTeam(_, team) => format!("blue team '{}'", team.name),
+            Self::Machine(_, machine) => format!("machine '{}'", machine.name),
+            Self::Service(_, _, service) => format!("service '{}'", service.name),
+        }
+    }
+}
+
 pub enum EditorMessage {
-    EditConfigName(String, u8),
-    FinishInit(u8),
-    DeleteConfig(u8),
+    EditConfigName(String, usize),
+    FinishInit(usize),
+    DeleteConfig(usize),
+    SetLocked(usize, bool),
+    RecordExport(ExportSnapshot),
+    MergeInto(usize),
     CreateNew(String),
-    Copy(String, u8),
+    Import(String, ConfigurationEditor),
+    Copy(String, usize),
     ChangeToView(CurrentView),
     UpdateIpSettings(IpGeneratorScheme),
+    UpdateBaseIpTemplate(String),
+    UpdateEngineVersion(EngineVersion),
+    UpdateCliCommandTemplate(String),
+    UpdateDescription(String),
+    UpdateFlags(Vec<Flag>),
+    ReplaceText(String, String),
+    /// Changes every service using the named check, apart from those with the field locked
+    BulkChangeServices(String, BulkServiceChange),
+    /// New passwords for every user, in the order `rotate_passwords` visits them
+    RotatePasswords(Vec<String>),
     Error(String),
+    DismissToast(u32),
+    DismissNotice,
+    ReloadFromStorage(Vec<StoredConfigurations>),
+    /// Turning autosave back on saves anything still unsaved
+    SetAutosave(bool),
+    SaveNow,
     AddRedWhiteTeam(RedWhiteTeamEditor),
-    EditRedWhiteTeam(u8, RedWhiteTeamEditor),
-    RemoveRedWhiteTeam(u8),
+    EditRedWhiteTeam(usize, RedWhiteTeamEditor),
+    RemoveRedWhiteTeam(usize),
     AddBlueTeam(BlueTeamEditor),
-    EditBlueTeam(u8, BlueTeamEditor),
-    RemoveBlueTeam(u8),
+    EditBlueTeam(usize, BlueTeamEditor),
+    RemoveBlueTeam(usize),
     AddMachine(MachineEditor),
-    UpdateMachine(u8, MachineEditor),
-    RemoveMachine(u8),
-    DropService(u8),
+    UpdateMachine(usize, MachineEditor),
+    /// Updates several machines as a single change, which is undone all at once
+    UpdateMachines(Vec<(usize, MachineEditor)>),
+    RemoveMachine(usize),
+    RemoveService(usize, usize),
+    RestoreDeleted(usize),
+    DropService(usize),
     PickupService(ServiceEditor),
-    HoverOverMachine(u8),
+    HoverOverMachine(usize),
     StopHoveringOverMachines,
+    /// Forgets any service being dragged along with the machine it's over, for when
+    /// a drag ends without a drop or the machines page is left partway through one
+    CancelDrag,
+    Undo,
+    Redo,
+    /// Adds every configuration of a backup to the library, renaming any whose name
+    /// is already taken rather than overwriting it
+    ReplaceAllConfigs(Vec<StoredConfigurations>),
+}
+
+impl EditorMessage {
+    /// The stored configuration a message refers to by index, if any
+    fn config_index(&self) -> Option<usize> {
+        match self {
+            Self::EditConfigName(_, i)
+            | Self::FinishInit(i)
+            | Self::DeleteConfig(i)
+            | Self::SetLocked(i, _)
+            | Self::MergeInto(i)
+            | Self::Copy(_, i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    /// The stored configuration a message would change, given the one currently
+    /// being edited, so that locked configurations can be left alone
+    fn changed_config(&self, current: Option<usize>) -> Option<usize> {
+        match self {
+            Self::EditConfigName(_, i) | Self::DeleteConfig(i) => Some(*i),
+            Self::UpdateIpSettings(_)
+            | Self::UpdateBaseIpTemplate(_)
+            | Self::UpdateEngineVersion(_)
+            | Self::UpdateCliCommandTemplate(_)
+            | Self::UpdateDescription(_)
+            | Self::UpdateFlags(_)
+            | Self::ReplaceText(..)
+            | Self::BulkChangeServices(..)
+            | Self::RotatePasswords(_)
+            | Self::MergeInto(_)
+            | Self::AddRedWhiteTeam(_)
+            | Self::EditRedWhiteTeam(..)
+            | Self::RemoveRedWhiteTeam(_)
+            | Self::AddBlueTeam(_)
+            | Self::EditBlueTeam(..)
+            | Self::RemoveBlueTeam(_)
+            | Self::AddMachine(_)
+            | Self::UpdateMachine(..)
+            | Self::UpdateMachines(_)
+            | Self::RemoveMachine(_)
+            | Self::RemoveService(..)
+            | Self::RestoreDeleted(_)
+            | Self::DropService(_)
+            | Self::Undo
+            | Self::Redo => current,
+            _ => None,
+        }
+    }
+
+    /// Whether the configurations as they were before the message can be gone back to;
+    /// going back and forward through them is not itself a change to go back on
+    fn is_undoable(&self, current: Option<usize>) -> bool {
+        matches!(self, Self::ReplaceAllConfigs(_) | Self::Copy(..))
+            || !matches!(self, Self::Undo | Self::Redo) && self.changed_config(current).is_some()
+    }
+
+    /// Whether the message starts working on a different set of changes, which the
+    /// changes made before can't be undone from
+    fn clears_history(&self) -> bool {
+        matches!(
+            self,
+            Self::FinishInit(_)
+                | Self::CreateNew(_)
+                | Self::Import(..)
+                | Self::ReloadFromStorage(_)
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// A short message shown over the page until it's dismissed or times out
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Toast {
+    pub id: u32,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// The messages being shown, along with the ones already dismissed so they can be
+/// looked back over
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Toasts {
+    pub shown: Vec<Toast>,
+    pub history: Vec<Toast>,
+    next_id: u32,
+}
+
+/// How many dismissed messages are kept around to look back over
+const TOAST_HISTORY_LENGTH: usize = 50;
+
+/// How many changes can be undone, as each keeps a copy of every stored configuration
+const UNDO_HISTORY_LENGTH: usize = 50;
+
+impl Toasts {
+    pub fn with(&self, severity: Severity, message: impl Into<String>) -> Toasts {
+        let mut toasts = self.clone();
+        toasts.shown.push(Toast {
+            id: self.next_id,
+            severity,
+            message: message.into(),
+        });
+        toasts.next_id = self.next_id.wrapping_add(1);
+        toasts
+    }
+
+    pub fn dismiss(&self, id: u32) -> Toasts {
+        let mut toasts = self.clone();
+        if let Some(i) = toasts.shown.iter().position(|toast| toast.id == id) {
+            toasts.history.insert(0, toasts.shown.remove(i));
+            toasts.history.truncate(TOAST_HISTORY_LENGTH);
+        }
+        toasts
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct EditorState {
-    pub error: Option<String>,
+    pub toasts: Toasts,
+    pub notice: Option<HealthReport>,
+    /// Cleared whenever a different configuration is opened
+    pub deleted: Vec<DeletedItem>,
     pub configs: Vec<StoredConfigurations>,
     pub state: EditingState,
+    /// Whether changes are written to storage as they're made, rather than only when
+    /// saved by hand
+    pub autosave: bool,
+    pub unsaved_changes: bool,
+    /// The stored configurations as they were before each change, most recent last
+    pub undo: Vec<Rc<Vec<StoredConfigurations>>>,
+    /// The stored configurations as they were before each undo, most recent last
+    pub redo: Vec<Rc<Vec<StoredConfigurations>>>,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum EditingState {
     Initializing,
     HasConfig {
-        config: u8,
+        config: usize,
         current_view: CurrentView,
-        currently_hovered_machine_name: Option<u8>,
+        currently_hovered_machine_name: Option<usize>,
         service_to_drop: Box<Option<ServiceEditor>>,
     },
 }
@@ -97,27 +564,130 @@ impl EditorState {
     ) -> (
         &ConfigurationEditor,
         &CurrentView,
-        Option<u8>,
+        Option<usize>,
         Option<&ServiceEditor>,
     ) {
         match &self.state {
-            EditingState::Initializing { .. } => panic!("forced init on uninit state"),
+            EditingState::Initializing => panic!("forced init on uninit state"),
             EditingState::HasConfig {
                 config,
                 current_view,
                 currently_hovered_machine_name,
                 service_to_drop,
             } => (
-                &(self.configs[*config as usize].config),
-                &current_view,
-                currently_hovered_machine_name.clone(),
+                &(self.configs[*config].config),
+                current_view,
+                *currently_hovered_machine_name,
                 service_to_drop.as_ref().as_ref(),
             ),
         }
     }
 
-    pub fn error(&self) -> Option<&str> {
-        self.error.as_deref()
+    pub fn config_name(&self) -> Option<&str> {
+        match &self.state {
+            EditingState::Initializing => None,
+            EditingState::HasConfig { config, .. } => {
+                self.configs.get(*config).map(|stored| stored.name.as_str())
+            }
+        }
+    }
+
+    pub fn last_export(&self) -> Option<&ExportSnapshot> {
+        match &self.state {
+            EditingState::Initializing => None,
+            EditingState::HasConfig { config, .. } => self
+                .configs
+                .get(*config)
+                .and_then(|stored| stored.last_export.as_ref()),
+        }
+    }
+
+    /// Asks before leaving the configuration being edited while it still can't be
+    /// converted, unless that warning was turned off; returns whether to go ahead
+    pub fn confirm_leaving(&self) -> bool {
+        if !leave_warning_enabled() {
+            return true;
+        }
+
+        let EditingState::HasConfig { config, .. } = &self.state else {
+            return true;
+        };
+        let Some(stored) = self.configs.get(*config) else {
+            return true;
+        };
+
+        let message = match conversion_error_count(&stored.config) {
+            0 => return true,
+            1 => format!(
+                "'{}' still has 1 error keeping it from being converted. Leave it anyway?",
+                stored.name
+            ),
+            n => format!(
+                "'{}' still has {n} errors keeping it from being converted. Leave it anyway?",
+                stored.name
+            ),
+        };
+
+        window()
+            .and_then(|window| window.confirm_with_message(&message).ok())
+            .unwrap_or(true)
+    }
+
+    /// The configurations of an undo or redo, keeping whether each is locked and when
+    /// it was last exported as they are now, since neither is undone
+    fn restored(&self, snapshot: &[StoredConfigurations]) -> Vec<StoredConfigurations> {
+        snapshot
+            .iter()
+            .cloned()
+            .map(|mut stored| {
+                if let Some(current) = self.configs.iter().find(|c| c.name == stored.name) {
+                    stored.locked = current.locked;
+                    stored.last_export = current.last_export.clone();
+                }
+                stored
+            })
+            .collect()
+    }
+
+    /// Where the configuration being edited is among other configurations, following
+    /// it by name, or back to the input page if it isn't there
+    fn follow(&self, configs: &[StoredConfigurations]) -> EditingState {
+        let EditingState::HasConfig {
+            config,
+            current_view,
+            currently_hovered_machine_name,
+            service_to_drop,
+        } = &self.state
+        else {
+            return EditingState::Initializing;
+        };
+
+        let name = &self.configs[*config].name;
+        let index = configs
+            .get(*config)
+            .filter(|stored| stored.name == *name)
+            .map(|_| *config)
+            .or_else(|| configs.iter().position(|stored| stored.name == *name));
+
+        match index {
+            Some(config) => EditingState::HasConfig {
+                config,
+                current_view: *current_view,
+                currently_hovered_machine_name: *currently_hovered_machine_name,
+                service_to_drop: service_to_drop.clone(),
+            },
+            None => EditingState::Initializing,
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        match &self.state {
+            EditingState::Initializing => false,
+            EditingState::HasConfig { config, .. } => self
+                .configs
+                .get(*config)
+                .is_some_and(|stored| stored.locked),
+        }
     }
 }
 
@@ -127,10 +697,127 @@ impl Reducible for EditorState {
     type Action = EditorMessage;
 
     fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let current = match &self.state {
+            EditingState::HasConfig { config, .. } => Some(*config),
+            EditingState::Initializing => None,
+        };
+        let undoable = action.is_undoable(current);
+        let clears_history = action.clears_history();
+        let history_step = matches!(action, EditorMessage::Undo | EditorMessage::Redo);
+
+        let before = self.clone();
+        let after = self.apply(action);
+
+        // Undo and redo leave whether configurations are locked and when they were last
+        // exported as they are, so changing only those doesn't get in their way. Any
+        // other change that isn't kept track of would be lost by going back over it
+        let untracked_change = !history_step
+            && !undoable
+            && (after.configs.len() != before.configs.len()
+                || after
+                    .configs
+                    .iter()
+                    .zip(&before.configs)
+                    .any(|(a, b)| a.name != b.name || a.config != b.config));
+
+        if after.configs == before.configs {
+            after
+        } else if clears_history || untracked_change {
+            EditorState {
+                undo: vec![],
+                redo: vec![],
+                ..(*after).clone()
+            }
+            .into()
+        } else if undoable {
+            let mut undo = after.undo.clone();
+            undo.push(Rc::new(before.configs.clone()));
+            if undo.len() > UNDO_HISTORY_LENGTH {
+                undo.remove(0);
+            }
+
+            EditorState {
+                undo,
+                redo: vec![],
+                ..(*after).clone()
+            }
+            .into()
+        } else {
+            after
+        }
+    }
+}
+
+impl EditorState {
+    /// Turns away a message whose index no longer points at anything, e.g. after the
+    /// configuration was reloaded from another tab
+    fn no_longer_exists(&self, what: &str) -> Rc<Self> {
+        EditorState {
+            toasts: self
+                .toasts
+                .with(Severity::Error, format!("that {what} no longer exists")),
+            ..self.clone()
+        }
+        .into()
+    }
+
+    fn apply(self: Rc<Self>, action: EditorMessage) -> Rc<Self> {
+        // Configurations can disappear underneath an index, e.g. when deleted from
+        // another tab sharing the same storage, so indices are checked before use
+        if let EditingState::HasConfig { config, .. } = &self.state {
+            if *config >= self.configs.len() {
+                return Rc::new(EditorState {
+                    toasts: self.toasts.with(
+                        Severity::Error,
+                        "the configuration being edited no longer exists; it may have been deleted in another tab",
+                    ),
+                    deleted: vec![],
+                    state: EditingState::Initializing,
+                    ..(*self).clone()
+                })
+                .apply(action);
+            }
+        }
+
+        if action
+            .config_index()
+            .is_some_and(|i| i >= self.configs.len())
+        {
+            return EditorState {
+                toasts: self
+                    .toasts
+                    .with(Severity::Error, "that configuration no longer exists"),
+                ..(*self).clone()
+            }
+            .into();
+        }
+
+        let current = match &self.state {
+            EditingState::HasConfig { config, .. } => Some(*config),
+            EditingState::Initializing => None,
+        };
+        if let Some(locked) = action
+            .changed_config(current)
+            .and_then(|i| self.configs.get(i))
+            .filter(|stored| stored.locked)
+        {
+            return EditorState {
+                toasts: self.toasts.with(
+                    Severity::Warning,
+                    format!(
+                        "the configuration '{}' is locked; unlock it before making changes",
+                        locked.name
+                    ),
+                ),
+                ..(*self).clone()
+            }
+            .into();
+        }
+
         match (&self.state, action) {
             (_, EditorMessage::EditConfigName(n, i)) => {
                 let mut cconfigs = self.configs.clone();
-                cconfigs[i as usize].name = n;
+                cconfigs[i].name = n;
 
                 save_changes(EditorState {
                     configs: cconfigs,
@@ -140,18 +827,92 @@ impl Reducible for EditorState {
             }
             (_, EditorMessage::DeleteConfig(i)) => {
                 let mut cconfigs = self.configs.clone();
-                cconfigs.remove(i as usize);
+                cconfigs.remove(i);
+
+                let state = match &self.state {
+                    EditingState::HasConfig { config, .. } if *config == i => {
+                        EditingState::Initializing
+                    }
+                    EditingState::HasConfig {
+                        config,
+                        current_view,
+                        currently_hovered_machine_name,
+                        service_to_drop,
+                    } if *config > i => EditingState::HasConfig {
+                        config: config - 1,
+                        current_view: *current_view,
+                        currently_hovered_machine_name: *currently_hovered_machine_name,
+                        service_to_drop: service_to_drop.clone(),
+                    },
+                    state => state.clone(),
+                };
+
+                let deleted = match state {
+                    EditingState::Initializing => vec![],
+                    _ => self.deleted.clone(),
+                };
 
                 save_changes(EditorState {
                     configs: cconfigs,
+                    deleted,
+                    state,
+                    ..(*self).clone()
+                })
+                .into()
+            }
+            (_, EditorMessage::SetLocked(i, locked)) => {
+                let mut cconfigs = self.configs.clone();
+                cconfigs[i].locked = locked;
+
+                save_changes(EditorState {
+                    configs: cconfigs,
+                    ..(*self).clone()
+                })
+                .into()
+            }
+            (EditingState::HasConfig { config, .. }, EditorMessage::RecordExport(snapshot)) => {
+                let mut cconfigs = self.configs.clone();
+                cconfigs[*config].last_export = Some(snapshot);
+
+                save_changes(EditorState {
+                    configs: cconfigs,
+                    ..(*self).clone()
+                })
+                .into()
+            }
+            (EditingState::HasConfig { config, .. }, EditorMessage::MergeInto(i)) => {
+                if *config == i {
+                    return EditorState {
+                        toasts: self
+                            .toasts
+                            .with(Severity::Error, "a configuration can't be merged into itself"),
+                        ..(*self).clone()
+                    }
+                    .into();
+                }
+
+                let mut cconfigs = self.configs.clone();
+                let changes = cconfigs[*config].config.merge(&self.configs[i].config);
+
+                let mut notice = HealthReport::check("Merged", &cconfigs[*config].config);
+                notice.problems.splice(0..0, changes);
+
+                save_changes(EditorState {
+                    configs: cconfigs,
+                    notice: Some(notice),
                     ..(*self).clone()
                 })
                 .into()
             }
             (_, EditorMessage::Copy(name, i)) => {
                 let mut cconfigs = self.configs.clone();
-                let config = self.configs[i as usize].clone().config;
-                cconfigs.push(StoredConfigurations { name, config });
+                let config = self.configs[i].clone().config;
+                cconfigs.push(StoredConfigurations {
+                    name,
+                    config,
+                    locked: false,
+                    last_export: None,
+                });
 
                 save_changes(EditorState {
                     configs: cconfigs,
@@ -159,31 +920,58 @@ impl Reducible for EditorState {
                 })
                 .into()
             }
-            (_, EditorMessage::FinishInit(i)) => EditorState {
-                state: EditingState::HasConfig {
-                    config: i,
-                    current_view: CurrentView::Machines,
-                    currently_hovered_machine_name: None,
-                    service_to_drop: Box::new(None),
-                },
-                ..(*self).clone()
+            (_, EditorMessage::FinishInit(i)) => {
+                let report = HealthReport::check("Opened", &self.configs[i].config);
+
+                EditorState {
+                    notice: (!report.problems.is_empty()).then_some(report),
+                    deleted: vec![],
+                    state: EditingState::HasConfig {
+                        config: i,
+                        current_view: CurrentView::Machines,
+                        currently_hovered_machine_name: None,
+                        service_to_drop: Box::new(None),
+                    },
+                    ..(*self).clone()
+                }
+                .into()
             }
-            .into(),
-            (_, EditorMessage::CreateNew(name)) => {
+            (_, EditorMessage::Import(name, config)) => {
+                let report = HealthReport::check("Imported", &config);
                 let mut cconfigs = self.configs.clone();
                 cconfigs.push(StoredConfigurations {
                     name,
-                    config: ConfigurationEditor {
-                        red_white_teams: vec![],
-                        blue_teams: vec![],
-                        machines: vec![],
-                        ip_generator: IpGeneratorScheme::OneTeam,
+                    config,
+                    locked: false,
+                    last_export: None,
+                });
+                save_changes(EditorState {
+                    configs: cconfigs,
+                    notice: Some(report),
+                    deleted: vec![],
+                    state: EditingState::HasConfig {
+                        config: self.configs.len(),
+                        current_view: CurrentView::Machines,
+                        currently_hovered_machine_name: None,
+                        service_to_drop: Box::new(None),
                     },
+                    ..(*self).clone()
+                })
+                .into()
+            }
+            (_, EditorMessage::CreateNew(name)) => {
+                let mut cconfigs = self.configs.clone();
+                cconfigs.push(StoredConfigurations {
+                    name,
+                    config: ConfigurationEditor::default(),
+                    locked: false,
+                    last_export: None,
                 });
                 save_changes(EditorState {
                     configs: cconfigs,
+                    deleted: vec![],
                     state: EditingState::HasConfig {
-                        config: self.configs.len() as u8,
+                        config: self.configs.len(),
                         current_view: CurrentView::Machines,
                         currently_hovered_machine_name: None,
                         service_to_drop: Box::new(None),
@@ -209,7 +997,134 @@ impl Reducible for EditorState {
                 EditorMessage::UpdateIpSettings(new_ip_settings),
             ) => {
                 let mut cconfigs = self.configs.clone();
-                cconfigs[*config as usize].config.ip_generator = new_ip_settings;
+                cconfigs[*config].config.ip_generator = new_ip_settings;
+                save_changes(EditorState {
+                    configs: cconfigs,
+                    ..(*self).clone()
+                })
+                .into()
+            }
+            (EditingState::HasConfig { config, .. }, EditorMessage::ReplaceText(find, replace)) => {
+                let mut cconfigs = self.configs.clone();
+                cconfigs[*config].config =
+                    replace_all(&cconfigs[*config].config, &find, &replace).0;
+                save_changes(EditorState {
+                    configs: cconfigs,
+                    ..(*self).clone()
+                })
+                .into()
+            }
+            (
+                EditingState::HasConfig { config, .. },
+                EditorMessage::BulkChangeServices(check_name, change),
+            ) => {
+                let mut cconfigs = self.configs.clone();
+                let (_, skipped) = cconfigs[*config]
+                    .config
+                    .bulk_change_services(&check_name, change);
+
+                let toasts = match skipped {
+                    0 => self.toasts.clone(),
+                    1 => self
+                        .toasts
+                        .with(Severity::Info, "1 locked service was left alone"),
+                    n => self
+                        .toasts
+                        .with(Severity::Info, format!("{n} locked services were left alone")),
+                };
+
+                save_changes(EditorState {
+                    configs: cconfigs,
+                    toasts,
+                    ..(*self).clone()
+                })
+                .into()
+            }
+            (EditingState::HasConfig { config, .. }, EditorMessage::RotatePasswords(passwords)) => {
+                let mut passwords = passwords.into_iter();
+                let mut cconfigs = self.configs.clone();
+                let rotated = rotate_passwords(&cconfigs[*config].config, || {
+                    passwords.next().ok_or(())
+                });
+
+                // The accounts changed since the passwords were generated for them
+                let (Ok((rotated, _)), None) = (rotated, passwords.next()) else {
+                    return EditorState {
+                        toasts: self.toasts.with(
+                            Severity::Error,
+                            "the accounts changed while their passwords were being rotated; no passwords were changed",
+                        ),
+                        ..(*self).clone()
+                    }
+                    .into();
+                };
+                cconfigs[*config].config = rotated;
+                save_changes(EditorState {
+                    configs: cconfigs,
+                    ..(*self).clone()
+                })
+                .into()
+            }
+            (
+                EditingState::HasConfig { config, .. },
+                EditorMessage::UpdateBaseIpTemplate(base_ip_template),
+            ) => {
+                let mut cconfigs = self.configs.clone();
+                cconfigs[*config].config.base_ip_template = base_ip_template;
+                save_changes(EditorState {
+                    configs: cconfigs,
+                    ..(*self).clone()
+                })
+                .into()
+            }
+            (
+                EditingState::HasConfig { config, .. },
+                EditorMessage::UpdateEngineVersion(engine_version),
+            ) => {
+                let mut cconfigs = self.configs.clone();
+                cconfigs[*config].config.engine_version = engine_version;
+                save_changes(EditorState {
+                    configs: cconfigs,
+                    ..(*self).clone()
+                })
+                .into()
+            }
+            (
+                EditingState::HasConfig { config, .. },
+                EditorMessage::UpdateCliCommandTemplate(template),
+            ) => {
+                let mut cconfigs = self.configs.clone();
+                let edited = &mut cconfigs[*config].config;
+                let version = edited.engine_version;
+
+                if template.trim().is_empty() || template == version.default_cli_command_template()
+                {
+                    edited.cli_command_templates.remove(&version);
+                } else {
+                    edited.cli_command_templates.insert(version, template);
+                }
+
+                save_changes(EditorState {
+                    configs: cconfigs,
+                    ..(*self).clone()
+                })
+                .into()
+            }
+            (
+                EditingState::HasConfig { config, .. },
+                EditorMessage::UpdateDescription(description),
+            ) => {
+                let mut cconfigs = self.configs.clone();
+                cconfigs[*config].config.description = description;
+                save_changes(EditorState {
+                    configs: cconfigs,
+                    ..(*self).clone()
+                })
+                .into()
+            }
+            (EditingState::HasConfig { config, .. }, EditorMessage::UpdateFlags(flags)) => {
+                let mut cconfigs = self.configs.clone();
+                cconfigs[*config].config.flags = flags;
                 save_changes(EditorState {
                     configs: cconfigs,
                     ..(*self).clone()
@@ -217,13 +1132,129 @@ impl Reducible for EditorState {
                 .into()
             }
             (_, EditorMessage::Error(e)) => EditorState {
-                error: Some(e),
+                toasts: self.toasts.with(Severity::Error, e),
+                ..(*self).clone()
+            }
+            .into(),
+            (_, EditorMessage::DismissToast(id)) => EditorState {
+                toasts: self.toasts.dismiss(id),
+                ..(*self).clone()
+            }
+            .into(),
+            (_, EditorMessage::SetAutosave(autosave)) => {
+                let result = if autosave {
+                    SessionStorage::delete(AUTOSAVE_DISABLED_KEY);
+                    Ok(())
+                } else {
+                    SessionStorage::set(AUTOSAVE_DISABLED_KEY, true)
+                };
+                if let Err(e) = result {
+                    log::error!("Could not remember the autosave setting: {e:?}");
+                }
+
+                let state = EditorState {
+                    autosave,
+                    ..(*self).clone()
+                };
+
+                if autosave && state.unsaved_changes {
+                    save_changes(state).into()
+                } else {
+                    state.into()
+                }
+            }
+            (_, EditorMessage::SaveNow) => {
+                match LocalStorage::set(STORAGE_KEY, self.configs.clone()) {
+                    Ok(()) => EditorState {
+                        toasts: self
+                            .toasts
+                            .with(Severity::Info, "changes were saved to this browser"),
+                        unsaved_changes: false,
+                        ..(*self).clone()
+                    },
+                    Err(e) => save_failed((*self).clone(), e),
+                }
+                .into()
+            }
+            // Taking what another tab saved would throw away the changes made here
+            // that haven't been saved yet
+            (_, EditorMessage::ReloadFromStorage(_)) if self.unsaved_changes => EditorState {
+                toasts: self.toasts.with(
+                    Severity::Warning,
+                    "another tab saved changes to these configurations; saving here will overwrite them",
+                ),
+                ..(*self).clone()
+            }
+            .into(),
+            (_, EditorMessage::ReloadFromStorage(configs)) => {
+                let EditingState::HasConfig {
+                    config,
+                    current_view,
+                    currently_hovered_machine_name,
+                    service_to_drop,
+                } = &self.state
+                else {
+                    return EditorState {
+                        configs,
+                        ..(*self).clone()
+                    }
+                    .into();
+                };
+
+                // Follow the configuration being edited by name, in case the other tab
+                // added or removed configurations before it
+                let editing = &self.configs[*config];
+                let Some(new_index) = configs
+                    .get(*config)
+                    .filter(|stored| stored.name == editing.name)
+                    .map(|_| *config)
+                    .or_else(|| {
+                        configs
+                            .iter()
+                            .position(|stored| stored.name == editing.name)
+                    })
+                else {
+                    return EditorState {
+                        configs,
+                        toasts: self.toasts.with(Severity::Error, format!(
+                            "the configuration '{}' was removed in another tab",
+                            editing.name
+                        )),
+                        deleted: vec![],
+                        state: EditingState::Initializing,
+                        ..(*self).clone()
+                    }
+                    .into();
+                };
+
+                let notice = (configs[new_index] != *editing).then(|| {
+                    HealthReport::check(
+                        "Replaced by changes made in another tab",
+                        &configs[new_index].config,
+                    )
+                });
+
+                EditorState {
+                    notice: notice.or_else(|| self.notice.clone()),
+                    state: EditingState::HasConfig {
+                        config: new_index,
+                        current_view: *current_view,
+                        currently_hovered_machine_name: *currently_hovered_machine_name,
+                        service_to_drop: service_to_drop.clone(),
+                    },
+                    configs,
+                    ..(*self).clone()
+                }
+                .into()
+            }
+            (_, EditorMessage::DismissNotice) => EditorState {
+                notice: None,
                 ..(*self).clone()
             }
             .into(),
             (EditingState::HasConfig { config, .. }, EditorMessage::AddRedWhiteTeam(team)) => {
                 let mut cconfigs = self.configs.clone();
-                cconfigs[*config as usize].config.red_white_teams.push(team);
+                cconfigs[*config].config.red_white_teams.push(team);
                 save_changes(EditorState {
                     configs: cconfigs,
                     ..(*self).clone()
@@ -232,7 +1263,7 @@ impl Reducible for EditorState {
             }
             (EditingState::HasConfig { config, .. }, EditorMessage::AddBlueTeam(team)) => {
                 let mut cconfigs = self.configs.clone();
-                cconfigs[*config as usize].config.blue_teams.push(team);
+                cconfigs[*config].config.blue_teams.push(team);
                 save_changes(EditorState {
                     configs: cconfigs,
                     ..(*self).clone()
@@ -244,7 +1275,10 @@ impl Reducible for EditorState {
                 EditorMessage::EditRedWhiteTeam(ind, team),
             ) => {
                 let mut cconfigs = self.configs.clone();
-                cconfigs[*config as usize].config.red_white_teams[ind as usize] = team;
+                let Some(edited) = cconfigs[*config].config.red_white_teams.get_mut(ind) else {
+                    return self.no_longer_exists("team");
+                };
+                *edited = team;
 
                 save_changes(EditorState {
                     configs: cconfigs,
@@ -254,7 +1288,10 @@ impl Reducible for EditorState {
             }
             (EditingState::HasConfig { config, .. }, EditorMessage::EditBlueTeam(ind, team)) => {
                 let mut cconfigs = self.configs.clone();
-                cconfigs[*config as usize].config.blue_teams[ind as usize] = team;
+                let Some(edited) = cconfigs[*config].config.blue_teams.get_mut(ind) else {
+                    return self.no_longer_exists("team");
+                };
+                *edited = team;
 
                 save_changes(EditorState {
                     configs: cconfigs,
@@ -264,31 +1301,39 @@ impl Reducible for EditorState {
             }
             (EditingState::HasConfig { config, .. }, EditorMessage::RemoveRedWhiteTeam(team)) => {
                 let mut cconfigs = self.configs.clone();
-                cconfigs[*config as usize]
-                    .config
-                    .red_white_teams
-                    .remove(team as usize);
+                let teams = &mut cconfigs[*config].config.red_white_teams;
+                if team >= teams.len() {
+                    return self.no_longer_exists("team");
+                }
+                let removed = teams.remove(team);
+                let mut deleted = self.deleted.clone();
+                deleted.push(DeletedItem::RedWhiteTeam(team, removed));
                 save_changes(EditorState {
                     configs: cconfigs,
+                    deleted,
                     ..(*self).clone()
                 })
                 .into()
             }
             (EditingState::HasConfig { config, .. }, EditorMessage::RemoveBlueTeam(team)) => {
                 let mut cconfigs = self.configs.clone();
-                cconfigs[*config as usize]
-                    .config
-                    .blue_teams
-                    .remove(team as usize);
+                let teams = &mut cconfigs[*config].config.blue_teams;
+                if team >= teams.len() {
+                    return self.no_longer_exists("team");
+                }
+                let removed = teams.remove(team);
+                let mut deleted = self.deleted.clone();
+                deleted.push(DeletedItem::BlueTeam(team, removed));
                 save_changes(EditorState {
                     configs: cconfigs,
+                    deleted,
                     ..(*self).clone()
                 })
                 .into()
             }
             (EditingState::HasConfig { config, .. }, EditorMessage::AddMachine(machine)) => {
                 let mut cconfigs = self.configs.clone();
-                cconfigs[*config as usize].config.machines.push(machine);
+                cconfigs[*config].config.machines.push(machine);
                 save_changes(EditorState {
                     configs: cconfigs,
                     ..(*self).clone()
@@ -300,7 +1345,25 @@ impl Reducible for EditorState {
                 EditorMessage::UpdateMachine(ind, machine),
             ) => {
                 let mut cconfigs = self.configs.clone();
-                cconfigs[*config as usize].config.machines[ind as usize] = machine;
+                let Some(edited) = cconfigs[*config].config.machines.get_mut(ind) else {
+                    return self.no_longer_exists("machine");
+                };
+                *edited = machine;
+                save_changes(EditorState {
+                    configs: cconfigs,
+                    ..(*self).clone()
+                })
+                .into()
+            }
+            (EditingState::HasConfig { config, .. }, EditorMessage::UpdateMachines(updates)) => {
+                let mut cconfigs = self.configs.clone();
+                let machines = &mut cconfigs[*config].config.machines;
+                if updates.iter().any(|(ind, _)| *ind >= machines.len()) {
+                    return self.no_longer_exists("machine");
+                }
+                for (ind, machine) in updates {
+                    machines[ind] = machine;
+                }
                 save_changes(EditorState {
                     configs: cconfigs,
                     ..(*self).clone()
@@ -309,12 +1372,86 @@ impl Reducible for EditorState {
             }
             (EditingState::HasConfig { config, .. }, EditorMessage::RemoveMachine(ind)) => {
                 let mut cconfigs = self.configs.clone();
-                cconfigs[*config as usize]
-                    .config
-                    .machines
-                    .remove(ind as usize);
+                let machines = &mut cconfigs[*config].config.machines;
+                if ind >= machines.len() {
+                    return self.no_longer_exists("machine");
+                }
+                let removed = machines.remove(ind);
+                let mut deleted = self.deleted.clone();
+                deleted.push(DeletedItem::Machine(ind, removed));
                 save_changes(EditorState {
                     configs: cconfigs,
+                    deleted,
+                    ..(*self).clone()
+                })
+                .into()
+            }
+            (
+                EditingState::HasConfig { config, .. },
+                EditorMessage::RemoveService(machine, service),
+            ) => {
+                let mut cconfigs = self.configs.clone();
+                let Some(machine) = cconfigs[*config].config.machines.get_mut(machine) else {
+                    return self.no_longer_exists("machine");
+                };
+                if service >= machine.services.len() {
+                    return self.no_longer_exists("service");
+                }
+                let removed = machine.services.remove(service);
+                let mut deleted = self.deleted.clone();
+                deleted.push(DeletedItem::Service(machine.name.clone(), service, removed));
+                save_changes(EditorState {
+                    configs: cconfigs,
+                    deleted,
+                    ..(*self).clone()
+                })
+                .into()
+            }
+            (EditingState::HasConfig { config, .. }, EditorMessage::RestoreDeleted(ind)) => {
+                let Some(item) = self.deleted.get(ind).cloned() else {
+                    return self;
+                };
+                let mut cconfigs = self.configs.clone();
+                let edited = &mut cconfigs[*config].config;
+
+                // Anything deleted since may have shifted indices, so restore at the
+                // original position where it still exists and at the end otherwise
+                match item {
+                    DeletedItem::RedWhiteTeam(i, team) => {
+                        let i = i.min(edited.red_white_teams.len());
+                        edited.red_white_teams.insert(i, team);
+                    }
+                    DeletedItem::BlueTeam(i, team) => {
+                        let i = i.min(edited.blue_teams.len());
+                        edited.blue_teams.insert(i, team);
+                    }
+                    DeletedItem::Machine(i, machine) => {
+                        let i = i.min(edited.machines.len());
+                        edited.machines.insert(i, machine);
+                    }
+                    DeletedItem::Service(machine_name, i, service) => {
+                        let Some(machine) =
+                            edited.machines.iter_mut().find(|m| m.name == machine_name)
+                        else {
+                            return EditorState {
+                                toasts: self.toasts.with(Severity::Error, format!(
+                                    "the machine '{machine_name}' the service '{}' was on no longer exists",
+                                    service.name
+                                )),
+                                ..(*self).clone()
+                            }
+                            .into();
+                        };
+                        let i = i.min(machine.services.len());
+                        machine.services.insert(i, service);
+                    }
+                }
+
+                let mut deleted = self.deleted.clone();
+                deleted.remove(ind);
+                save_changes(EditorState {
+                    configs: cconfigs,
+                    deleted,
                     ..(*self).clone()
                 })
                 .into()
@@ -323,16 +1460,26 @@ impl Reducible for EditorState {
                 EditingState::HasConfig {
                     config,
                     current_view,
-                    currently_hovered_machine_name,
                     service_to_drop,
+                    ..
                 },
                 EditorMessage::DropService(ind),
             ) => match *service_to_drop.clone() {
                 Some(service) => {
                     let mut cconfigs = self.configs.clone();
-                    cconfigs[*config as usize].config.machines[ind as usize]
-                        .services
-                        .push(service);
+                    let Some(machine) = cconfigs[*config].config.machines.get_mut(ind) else {
+                        return EditorState {
+                            state: EditingState::HasConfig {
+                                service_to_drop: Box::new(None),
+                                config: *config,
+                                current_view: *current_view,
+                                currently_hovered_machine_name: None,
+                            },
+                            ..(*self).clone()
+                        }
+                        .into();
+                    };
+                    machine.services.push(service);
 
                     save_changes(EditorState {
                         configs: cconfigs,
@@ -340,7 +1487,7 @@ impl Reducible for EditorState {
                             service_to_drop: Box::new(None),
                             config: *config,
                             current_view: *current_view,
-                            currently_hovered_machine_name: currently_hovered_machine_name.clone(),
+                            currently_hovered_machine_name: None,
                         },
                         ..(*self).clone()
                     })
@@ -351,7 +1498,7 @@ impl Reducible for EditorState {
                         service_to_drop: Box::new(None),
                         config: *config,
                         current_view: *current_view,
-                        currently_hovered_machine_name: currently_hovered_machine_name.clone(),
+                        currently_hovered_machine_name: None,
                     },
                     ..(*self).clone()
                 }
@@ -369,7 +1516,7 @@ impl Reducible for EditorState {
                 state: EditingState::HasConfig {
                     config: *config,
                     current_view: *current_view,
-                    currently_hovered_machine_name: currently_hovered_machine_name.clone(),
+                    currently_hovered_machine_name: *currently_hovered_machine_name,
                     service_to_drop: Box::new(Some(service_to_drop)),
                 },
                 ..(*self).clone()
@@ -411,8 +1558,98 @@ impl Reducible for EditorState {
                 ..(*self).clone()
             }
             .into(),
+            (
+                EditingState::HasConfig {
+                    config,
+                    current_view,
+                    ..
+                },
+                EditorMessage::CancelDrag,
+            ) => EditorState {
+                state: EditingState::HasConfig {
+                    config: *config,
+                    current_view: *current_view,
+                    currently_hovered_machine_name: None,
+                    service_to_drop: Box::new(None),
+                },
+                ..(*self).clone()
+            }
+            .into(),
+
+            (_, EditorMessage::Undo) => {
+                let mut undo = self.undo.clone();
+                let Some(previous) = undo.pop() else {
+                    return self;
+                };
+                let mut redo = self.redo.clone();
+                redo.push(Rc::new(self.configs.clone()));
+
+                let configs = self.restored(&previous);
+                save_changes(EditorState {
+                    state: self.follow(&configs),
+                    configs,
+                    // Removed items are put back by position, which may no longer hold
+                    deleted: vec![],
+                    undo,
+                    redo,
+                    ..(*self).clone()
+                })
+                .into()
+            }
+            (_, EditorMessage::Redo) => {
+                let mut redo = self.redo.clone();
+                let Some(next) = redo.pop() else {
+                    return self;
+                };
+                let mut undo = self.undo.clone();
+                undo.push(Rc::new(self.configs.clone()));
+
+                let configs = self.restored(&next);
+                save_changes(EditorState {
+                    state: self.follow(&configs),
+                    configs,
+                    deleted: vec![],
+                    undo,
+                    redo,
+                    ..(*self).clone()
+                })
+                .into()
+            }
+
+            (_, EditorMessage::ReplaceAllConfigs(backup)) => {
+                let mut cconfigs = self.configs.clone();
+                let mut renamed = 0;
 
-            (EditingState::Initializing { .. }, _) => self, // misconfigured case, shouldn't happen
+                for mut stored in backup {
+                    let name = stored.name.clone();
+                    let mut suffix = 2;
+                    while cconfigs.iter().any(|c| c.name == stored.name) {
+                        stored.name = format!("{name} ({suffix})");
+                        suffix += 1;
+                    }
+                    if stored.name != name {
+                        renamed += 1;
+                    }
+                    cconfigs.push(stored);
+                }
+
+                let imported = cconfigs.len() - self.configs.len();
+                let message = match renamed {
+                    0 => format!("imported {imported} configurations from the backup"),
+                    _ => format!(
+                        "imported {imported} configurations from the backup, renaming {renamed} whose names were taken"
+                    ),
+                };
+
+                save_changes(EditorState {
+                    configs: cconfigs,
+                    toasts: self.toasts.with(Severity::Info, message),
+                    ..(*self).clone()
+                })
+                .into()
+            }
+
+            (EditingState::Initializing, _) => self, // misconfigured case, shouldn't happen
         }
     }
 }
@@ -426,19 +1663,351 @@ pub struct EditorStateProviderProps {
 #[function_component]
 pub fn EditorStateProvider(props: &EditorStateProviderProps) -> Html {
     let state = use_reducer(|| {
-        let configs =
-            LocalStorage::get::<Vec<StoredConfigurations>>(STORAGE_KEY).unwrap_or_default();
+        let (configs, load_error) = match load_stored_configurations() {
+            Ok(loaded) => loaded,
+            Err(stored) => (
+                vec![],
+                Some(set_aside(vec![serde_json::Value::String(stored)], "at all")),
+            ),
+        };
+
+        let toasts = match load_error {
+            Some(e) => Toasts::default().with(Severity::Error, e),
+            None => Toasts::default(),
+        };
 
         EditorState {
             configs,
-            error: None,
+            toasts,
+            notice: None,
+            deleted: vec![],
             state: EditingState::Initializing,
+            autosave: !SessionStorage::get::<bool>(AUTOSAVE_DISABLED_KEY).unwrap_or(false),
+            unsaved_changes: false,
+            undo: vec![],
+            redo: vec![],
         }
     });
 
+    {
+        let state = state.clone();
+
+        use_effect_with((), move |_| {
+            let listener = window().map(|window| {
+                EventListener::new(&window, "storage", move |event| {
+                    let Some(event) = event.dyn_ref::<StorageEvent>() else {
+                        return;
+                    };
+                    if event.key().as_deref() != Some(STORAGE_KEY) {
+                        return;
+                    }
+
+                    match load_stored_configurations() {
+                        Ok((configs, load_error)) => {
+                            state.dispatch(EditorMessage::ReloadFromStorage(configs));
+                            if let Some(e) = load_error {
+                                state.dispatch(EditorMessage::Error(e));
+                            }
+                        }
+                        Err(_) => state.dispatch(EditorMessage::Error(
+                            "could not load configurations saved by another tab".to_owned(),
+                        )),
+                    }
+                })
+            });
+
+            move || drop(listener)
+        });
+    }
+
     html! {
         <ContextProvider<EditorStateContext> context={state}>
             {props.children.clone()}
         </ContextProvider<EditorStateContext>>
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Configurations as the first versions of the editor stored them
+    const BASELINE_BLOB: &str = r#"[
+        {
+            "name": "Regionals",
+            "config": {
+                "red_white_teams": [
+                    {"name": "White", "users": [{"username": "white", "password": "pw"}], "white_team": true}
+                ],
+                "blue_teams": [
+                    {"id": 1, "name": "Team 1", "users": [{"username": "team1", "password": "pw"}]}
+                ],
+                "machines": [
+                    {
+                        "name": "web",
+                        "ip_template": "10.X.1.10",
+                        "ip_offset": null,
+                        "services": [
+                            {
+                                "name": "http",
+                                "port": 80,
+                                "points": 100,
+                                "accounts": null,
+                                "definition": {
+                                    "type": "Http",
+                                    "environment": [
+                                        {"matching_content": "Welcome", "useragent": "ua", "vhost": "www", "uri": "/"}
+                                    ]
+                                }
+                            },
+                            {
+                                "name": "es",
+                                "port": 9200,
+                                "points": 100,
+                                "accounts": null,
+                                "definition": {
+                                    "type": "Elasticsearch",
+                                    "environment": [
+                                        {"matching_content": "ok", "index": "logs", "doc_type": "entry"}
+                                    ]
+                                }
+                            },
+                            {
+                                "name": "ping",
+                                "port": 0,
+                                "points": 50,
+                                "accounts": null,
+                                "definition": {"type": "Icmp", "environment": null}
+                            }
+                        ]
+                    }
+                ],
+                "ip_generator": {"scheme": "ReplaceXWithId"}
+            }
+        }
+    ]"#;
+
+    #[test]
+    fn baseline_blob_loads() {
+        let (configs, unreadable) = read_stored_configurations(BASELINE_BLOB).unwrap();
+
+        assert!(unreadable.is_empty());
+        assert_eq!(configs.len(), 1);
+
+        let stored = &configs[0];
+        assert_eq!(stored.name, "Regionals");
+        assert!(!stored.locked);
+        assert_eq!(stored.last_export, None);
+        assert_eq!(stored.config.engine_version, EngineVersion::Legacy);
+        assert_eq!(stored.config.blue_teams[0].name, "Team 1");
+        assert_eq!(stored.config.machines[0].services.len(), 3);
+    }
+
+    #[test]
+    fn baseline_migration_matches_current_defaults() {
+        let migrated = serde_json::from_str::<Vec<StoredConfigurationsV1>>(BASELINE_BLOB)
+            .unwrap()
+            .into_iter()
+            .map(StoredConfigurations::from)
+            .collect::<Vec<_>>();
+
+        let read = serde_json::from_str::<Vec<StoredConfigurations>>(BASELINE_BLOB).unwrap();
+
+        assert_eq!(migrated, read);
+    }
+
+    #[test]
+    fn unreadable_configurations_are_kept_apart() {
+        let blob = format!(
+            r#"[{}, {{"name": "Broken", "config": {{"machines": 3}}}}]"#,
+            BASELINE_BLOB
+                .trim()
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+        );
+
+        let (configs, unreadable) = read_stored_configurations(&blob).unwrap();
+
+        assert_eq!(configs.len(), 1);
+        assert_eq!(configs[0].name, "Regionals");
+        assert_eq!(unreadable.len(), 1);
+        assert_eq!(unreadable[0]["name"], "Broken");
+    }
+
+    #[test]
+    fn storage_that_isnt_a_list_fails() {
+        assert!(read_stored_configurations(r#"{"name": "Regionals"}"#).is_err());
+    }
+
+    /// Editing the first of the given configurations, without touching storage
+    fn editing(configs: Vec<StoredConfigurations>) -> Rc<EditorState> {
+        Rc::new(EditorState {
+            toasts: Toasts::default(),
+            notice: None,
+            deleted: vec![],
+            configs,
+            state: EditingState::HasConfig {
+                config: 0,
+                current_view: CurrentView::Machines,
+                currently_hovered_machine_name: None,
+                service_to_drop: Box::new(None),
+            },
+            autosave: false,
+            unsaved_changes: false,
+            undo: vec![],
+            redo: vec![],
+        })
+    }
+
+    fn stored(name: &str) -> StoredConfigurations {
+        StoredConfigurations {
+            name: name.to_owned(),
+            config: ConfigurationEditor::default(),
+            locked: false,
+            last_export: None,
+        }
+    }
+
+    #[test]
+    fn copies_can_be_undone_and_redone() {
+        let state = editing(vec![stored("Original")])
+            .reduce(EditorMessage::Copy("Copy".to_owned(), 0))
+            .reduce(EditorMessage::UpdateDescription("Changed".to_owned()));
+        assert_eq!(state.configs.len(), 2);
+
+        // Going back over the edit leaves the copy alone
+        let state = state.reduce(EditorMessage::Undo);
+        assert_eq!(state.configs.len(), 2);
+        assert_eq!(state.configs[0].config.description, "");
+
+        let state = state.reduce(EditorMessage::Undo);
+        assert_eq!(state.configs.len(), 1);
+
+        let state = state.reduce(EditorMessage::Redo);
+        assert_eq!(state.configs.len(), 2);
+        assert_eq!(state.configs[1].name, "Copy");
+    }
+
+    fn with_machines(names: &[&str]) -> StoredConfigurations {
+        let service = ServiceEditor::from(ServiceEditorV1 {
+            name: "ping".to_owned(),
+            port: 0,
+            points: 100,
+            definition: ServiceDefinition::Icmp { environment: None },
+            accounts: None,
+        });
+
+        let mut stored = stored("Machines");
+        stored.config.machines = names
+            .iter()
+            .map(|name| MachineEditor {
+                name: name.to_string(),
+                services: vec![service.clone()],
+                ..Default::default()
+            })
+            .collect();
+        stored
+    }
+
+    #[test]
+    fn deleted_services_go_back_to_their_machine() {
+        let state = editing(vec![with_machines(&["web", "db"])])
+            .reduce(EditorMessage::RemoveService(1, 0))
+            .reduce(EditorMessage::RemoveMachine(0))
+            .reduce(EditorMessage::RestoreDeleted(0));
+
+        let machines = &state.configs[0].config.machines;
+        assert_eq!(machines.len(), 1);
+        assert_eq!(machines[0].name, "db");
+        assert_eq!(machines[0].services.len(), 1);
+    }
+
+    #[test]
+    fn deleted_services_of_removed_machines_stay_deleted() {
+        let state = editing(vec![with_machines(&["web", "db"])])
+            .reduce(EditorMessage::RemoveService(1, 0))
+            .reduce(EditorMessage::RemoveMachine(1))
+            .reduce(EditorMessage::RestoreDeleted(0));
+
+        let machines = &state.configs[0].config.machines;
+        assert_eq!(machines.len(), 1);
+        assert_eq!(machines[0].services.len(), 1);
+        assert_eq!(state.toasts.shown.len(), 1);
+    }
+
+    #[test]
+    fn stale_indices_are_turned_away() {
+        let state = editing(vec![with_machines(&["web"])])
+            .reduce(EditorMessage::UpdateMachine(1, MachineEditor::default()))
+            .reduce(EditorMessage::RemoveMachine(1))
+            .reduce(EditorMessage::RemoveService(0, 1))
+            .reduce(EditorMessage::EditBlueTeam(
+                0,
+                BlueTeamEditor {
+                    id: 1,
+                    name: "Team 1".to_owned(),
+                    users: vec![],
+                },
+            ));
+
+        assert_eq!(state.configs[0], with_machines(&["web"]));
+        assert_eq!(
+            state
+                .toasts
+                .shown
+                .iter()
+                .map(|toast| toast.message.as_str())
+                .collect::<Vec<_>>(),
+            [
+                "that machine no longer exists",
+                "that machine no longer exists",
+                "that service no longer exists",
+                "that team no longer exists",
+            ]
+        );
+    }
+
+    #[test]
+    fn rotations_for_other_accounts_are_rejected() {
+        let mut original = stored("Accounts");
+        original.config.blue_teams = vec![BlueTeamEditor {
+            id: 1,
+            name: "Team 1".to_owned(),
+            users: vec![User {
+                username: "team1".to_owned(),
+                password: "old".to_owned(),
+            }],
+        }];
+
+        for passwords in [vec![], vec!["a".to_owned(), "b".to_owned()]] {
+            let state =
+                editing(vec![original.clone()]).reduce(EditorMessage::RotatePasswords(passwords));
+
+            assert_eq!(state.configs[0], original);
+            assert_eq!(state.toasts.shown.len(), 1);
+        }
+
+        let state = editing(vec![original.clone()])
+            .reduce(EditorMessage::RotatePasswords(vec!["new".to_owned()]));
+        assert_eq!(
+            state.configs[0].config.blue_teams[0].users[0].password,
+            "new"
+        );
+    }
+
+    #[test]
+    fn updating_several_machines_is_one_undo_step() {
+        let original = with_machines(&["web", "db", "mail"]);
+        let mut db = original.config.machines[1].clone();
+        let mut mail = original.config.machines[2].clone();
+        db.ip_template = "10.X.1.20".to_owned();
+        mail.ip_template = "10.X.1.30".to_owned();
+
+        let state = editing(vec![original.clone()])
+            .reduce(EditorMessage::UpdateMachines(vec![(1, db), (2, mail)]));
+        assert_eq!(state.configs[0].config.machines[2].ip_template, "10.X.1.30");
+
+        let state = state.reduce(EditorMessage::Undo);
+        assert_eq!(state.configs[0], original);
+    }
+}
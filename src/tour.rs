@@ -0,0 +1,130 @@
+// tour.rs: A walk through the steps of building a configuration for first time users
+//
+// Copyright (C) 2023 Andrew Rioux
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use gloo_storage::{LocalStorage, Storage};
+use yew::prelude::*;
+
+use crate::state::{CurrentView, EditingState, EditorMessage, EditorStateContext};
+
+const TOUR_SEEN_KEY: &str = "tour_seen";
+
+/// Each stop of the tour, in the order a configuration is usually built
+pub const STEPS: &[(CurrentView, &str)] = &[
+    (
+        CurrentView::Input,
+        "Start here: create a configuration, or pick one saved in this browser to keep editing it.",
+    ),
+    (
+        CurrentView::Teams,
+        "Add the red and white teams, then one blue team per competing team, each with the users they log in as.",
+    ),
+    (
+        CurrentView::Machines,
+        "Add the machines every blue team gets, and drag the services the engine should check onto them.",
+    ),
+    (
+        CurrentView::IpSettings,
+        "Choose how each blue team's addresses are worked out from the machine templates.",
+    ),
+    (
+        CurrentView::Review,
+        "Look over team coverage and anything duplicated before exporting.",
+    ),
+    (
+        CurrentView::Output,
+        "Copy or download the configuration for the scoring engine.",
+    ),
+];
+
+/// Whether the tour has already been finished or skipped in this browser
+pub fn seen() -> bool {
+    LocalStorage::get::<bool>(TOUR_SEEN_KEY).unwrap_or(false)
+}
+
+#[derive(Properties, PartialEq)]
+pub struct TourProps {
+    pub step: usize,
+    pub set_step: Callback<Option<usize>>,
+}
+
+#[function_component]
+pub fn Tour(props: &TourProps) -> Html {
+    let editor_state = use_context::<EditorStateContext>().unwrap();
+
+    let Some((view, explanation)) = STEPS.get(props.step) else {
+        return html! {};
+    };
+
+    let has_config = matches!(editor_state.state, EditingState::HasConfig { .. });
+
+    let finish = {
+        let set_step = props.set_step.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            if let Err(e) = LocalStorage::set(TOUR_SEEN_KEY, true) {
+                log::error!("Could not remember the tour was seen: {e:?}");
+            }
+            set_step.emit(None);
+        })
+    };
+
+    let go_to = |step: usize| {
+        let set_step = props.set_step.clone();
+        let editor_state = editor_state.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+
+            // Without a configuration open only the input page can be shown, so the
+            // tour just points at the other pages
+            if let Some((view, _)) = STEPS.get(step).filter(|_| has_config) {
+                editor_state.dispatch(EditorMessage::ChangeToView(*view));
+            }
+            set_step.emit(Some(step));
+        })
+    };
+
+    let is_last = props.step + 1 == STEPS.len();
+
+    html! {
+        <div class="tour">
+            <h4>{ format!("Step {} of {}", props.step + 1, STEPS.len()) }</h4>
+
+            <p>{ explanation }</p>
+
+            if *view != CurrentView::Input && !has_config {
+                <p class="tour-aside">
+                    { "This page opens once a configuration is being edited." }
+                </p>
+            }
+
+            <div class="tour-buttons">
+                if props.step > 0 {
+                    <a href="#" onclick={go_to(props.step - 1)}>{ "Back" }</a>
+                }
+
+                if is_last {
+                    <a href="#" class="button" onclick={finish.clone()}>{ "Done" }</a>
+                } else {
+                    <a href="#" class="button" onclick={go_to(props.step + 1)}>{ "Next" }</a>
+                    <a href="#" onclick={finish}>{ "Skip tour" }</a>
+                }
+            </div>
+        </div>
+    }
+}
@@ -21,7 +21,9 @@ use web_sys::HtmlInputElement;
 use yew::prelude::*;
 
 use crate::{
-    config::{BlueTeamEditor, RedWhiteTeamEditor, User},
+    config::{scored_service_count, BlueTeamEditor, ConfigurationEditor, RedWhiteTeamEditor, User},
+    machines::{parse_whole_number, read_trimmed_input},
+    passwords::RotatePasswords,
     state::{self, EditorMessage},
 };
 
@@ -51,7 +53,7 @@ fn UserEditorComponent(props: &UserEditorProps) -> Html {
 
         Callback::from(move |_| {
             if let Some(input) = username_ref.cast::<HtmlInputElement>() {
-                let value = input.value();
+                let value = read_trimmed_input(&input);
 
                 update_user.emit((value, password.to_string()));
             }
@@ -110,12 +112,18 @@ fn UserEditorComponent(props: &UserEditorProps) -> Html {
                 </div>
             </div>
 
+            if props.username.is_empty() || props.password.is_empty() {
+                <div class="user-error">
+                    { "This user needs both a username and a password to log in" }
+                </div>
+            }
+
             <div class="form-row">
                 <div class="form-block">
                 </div>
 
                 <div class="form-block button-box">
-                    <a href="#" onclick={delete_user_onclick}>
+                    <a href="#" class="edit-action" onclick={delete_user_onclick}>
                         { "Delete user" }
                     </a>
                 </div>
@@ -174,7 +182,7 @@ fn UserListEditor(props: &UserListEditorProps) -> Html {
     });
 
     html! {
-        <div class={classes!("user-editor-list", Some("border").filter(|_| props.users.len() > 0))}>
+        <div class={classes!("user-editor-list", Some("border").filter(|_| !props.users.is_empty()))}>
             { for user_list }
         </div>
     }
@@ -204,7 +212,7 @@ fn RedWhiteTeamEditorComponent(props: &RedWhiteTeamEditorProps) -> Html {
             let Some(input) = name_ref.cast::<HtmlInputElement>() else {
                 return;
             };
-            let value = input.value();
+            let value = read_trimmed_input(&input);
 
             update_team.emit((value.into(), users.clone(), white_team));
         })
@@ -298,7 +306,7 @@ fn RedWhiteTeamEditorComponent(props: &RedWhiteTeamEditorProps) -> Html {
                     </div>
 
                     <div class="form-block button-box">
-                        <a href="#" onclick={delete_team}>
+                        <a href="#" class="edit-action" onclick={delete_team}>
                             { "Delete team" }
                         </a>
                     </div>
@@ -312,7 +320,7 @@ fn RedWhiteTeamEditorComponent(props: &RedWhiteTeamEditorProps) -> Html {
                     </div>
 
                     <div class="form-block button-box">
-                        <a href="#" onclick={add_user}>
+                        <a href="#" class="edit-action" onclick={add_user}>
                             { "Add user" }
                         </a>
                     </div>
@@ -332,6 +340,8 @@ struct BlueTeamEditorProps {
     id: u8,
     name: AttrValue,
     users: Rc<Vec<UserProps>>,
+    /// How many services the team is scored on, or why that can't be worked out yet
+    service_count: Result<usize, AttrValue>,
     modify_blue_team: Callback<(AttrValue, Rc<Vec<UserProps>>, u8)>,
     delete_team: Callback<()>,
 }
@@ -353,7 +363,7 @@ fn BlueTeamEditorComponent(props: &BlueTeamEditorProps) -> Html {
             let Some(input) = name_ref.cast::<HtmlInputElement>() else {
                 return;
             };
-            let value = input.value();
+            let value = read_trimmed_input(&input);
 
             update_team.emit((value.into(), users.clone(), id));
         })
@@ -372,13 +382,13 @@ fn BlueTeamEditorComponent(props: &BlueTeamEditorProps) -> Html {
             };
             let value = input.value();
 
-            match value.parse::<u8>() {
+            match parse_whole_number(&value, 0, u8::MAX, "The team ID") {
                 Ok(id) => {
                     id_input_state.set((id.to_string().into(), None));
 
                     update_team.emit((name.clone(), users.clone(), id));
                 }
-                Err(e) => id_input_state.set((value.into(), Some(format!("Parse error: {e:?}")))),
+                Err(e) => id_input_state.set((value.into(), Some(e))),
             }
         })
     };
@@ -453,12 +463,35 @@ fn BlueTeamEditorComponent(props: &BlueTeamEditorProps) -> Html {
                     </div>
                 </div>
 
+                <div class="form-row service-count border">
+                    <div class="form-block">
+                        { "Scored on" }
+                    </div>
+
+                    <div class="form-block">
+                        { match &props.service_count {
+                            Ok(0) => html! {
+                                <span class="no-services" title="This team has nothing to score; add machines with services, or score more services for it">
+                                    { "0 services" }
+                                </span>
+                            },
+                            Ok(1) => html! { "1 service" },
+                            Ok(count) => html! { format!("{count} services") },
+                            Err(e) => html! {
+                                <span class="unknown-services" title={e.clone()}>
+                                    { "Unknown until the services are fixed" }
+                                </span>
+                            },
+                        } }
+                    </div>
+                </div>
+
                 <div class="form-row delete-team">
                     <div class="form-block">
                     </div>
 
                     <div class="form-block button-box">
-                        <a href="#" onclick={delete_team}>
+                        <a href="#" class="edit-action" onclick={delete_team}>
                             { "Delete team" }
                         </a>
                     </div>
@@ -472,7 +505,7 @@ fn BlueTeamEditorComponent(props: &BlueTeamEditorProps) -> Html {
                     </div>
 
                     <div class="form-block button-box">
-                        <a href="#" onclick={add_user}>
+                        <a href="#" class="edit-action" onclick={add_user}>
                             { "Add user" }
                         </a>
                     </div>
@@ -487,6 +520,22 @@ fn BlueTeamEditorComponent(props: &BlueTeamEditorProps) -> Html {
     }
 }
 
+/// An empty blue team, numbered after the highest team ID already in use
+pub fn new_blue_team(config: &ConfigurationEditor) -> BlueTeamEditor {
+    let highest_id = config
+        .blue_teams
+        .iter()
+        .map(|team| team.id)
+        .max()
+        .unwrap_or(0);
+
+    BlueTeamEditor {
+        id: highest_id.saturating_add(1),
+        name: "".into(),
+        users: vec![],
+    }
+}
+
 #[function_component]
 pub fn TeamsEditor() -> Html {
     let editor_state = use_context::<crate::state::EditorStateContext>().unwrap();
@@ -494,8 +543,6 @@ pub fn TeamsEditor() -> Html {
     let red_white_teams = config.red_white_teams.clone();
     let blue_teams = config.blue_teams.clone();
 
-    let new_team_id = blue_teams.iter().map(|team| team.id).max().unwrap_or(0);
-
     let add_new_red_white_team = {
         let editor_state = editor_state.clone();
 
@@ -511,13 +558,10 @@ pub fn TeamsEditor() -> Html {
     let add_new_blue_team = {
         let editor_state = editor_state.clone();
 
+        let new_team = new_blue_team(config);
+
         Callback::from(move |_| {
-            log::info!("{new_team_id}");
-            editor_state.dispatch(state::EditorMessage::AddBlueTeam(BlueTeamEditor {
-                id: new_team_id + 1,
-                name: "".into(),
-                users: vec![],
-            }));
+            editor_state.dispatch(state::EditorMessage::AddBlueTeam(new_team.clone()));
         })
     };
 
@@ -528,7 +572,7 @@ pub fn TeamsEditor() -> Html {
             Callback::from(
                 move |(name, users, white_team): (AttrValue, Rc<Vec<UserProps>>, bool)| {
                     editor_state.dispatch(EditorMessage::EditRedWhiteTeam(
-                        i.try_into().unwrap(),
+                        i,
                         RedWhiteTeamEditor {
                             name: name.to_string(),
                             users: users
@@ -548,9 +592,7 @@ pub fn TeamsEditor() -> Html {
         let delete_team = {
             let editor_state = editor_state.clone();
 
-            Callback::from(move |_| {
-                editor_state.dispatch(EditorMessage::RemoveRedWhiteTeam(i.try_into().unwrap()))
-            })
+            Callback::from(move |_| editor_state.dispatch(EditorMessage::RemoveRedWhiteTeam(i)))
         };
 
         let users: Rc<Vec<UserProps>> = team
@@ -586,7 +628,7 @@ pub fn TeamsEditor() -> Html {
             Callback::from(
                 move |(name, users, id): (AttrValue, Rc<Vec<UserProps>>, u8)| {
                     editor_state.dispatch(EditorMessage::EditBlueTeam(
-                        i.try_into().unwrap(),
+                        i,
                         BlueTeamEditor {
                             name: name.to_string(),
                             users: users
@@ -606,9 +648,7 @@ pub fn TeamsEditor() -> Html {
         let delete_team = {
             let editor_state = editor_state.clone();
 
-            Callback::from(move |_| {
-                editor_state.dispatch(EditorMessage::RemoveBlueTeam(i.try_into().unwrap()))
-            })
+            Callback::from(move |_| editor_state.dispatch(EditorMessage::RemoveBlueTeam(i)))
         };
 
         let users: Rc<Vec<UserProps>> = team
@@ -623,12 +663,16 @@ pub fn TeamsEditor() -> Html {
 
         let name: AttrValue = team.name.clone().into();
 
+        let service_count =
+            scored_service_count(config, team).map_err(|e| AttrValue::from(e.to_string()));
+
         html! {
             <li key={team.id}>
                 <BlueTeamEditorComponent
                     key={team.id}
                     {name}
                     {users}
+                    {service_count}
                     id={team.id}
                     {modify_blue_team}
                     {delete_team}
@@ -639,13 +683,15 @@ pub fn TeamsEditor() -> Html {
 
     html! {
         <main id="teams">
+            <RotatePasswords />
+
             <div class="red-white-team-list">
                 <div>
                     <h2>{ "Red and white teams" }</h2>
 
                     <div class="form-submit">
                         <div class="form-submit-button button-box">
-                            <a href="#" onclick={add_new_red_white_team}>
+                            <a href="#" class="edit-action" onclick={add_new_red_white_team}>
                                 { "Add new team" }
                             </a>
                         </div>
@@ -663,7 +709,7 @@ pub fn TeamsEditor() -> Html {
 
                     <div class="form-submit">
                         <div class="form-submit-button button-box">
-                            <a href="#" onclick={add_new_blue_team}>
+                            <a href="#" class="edit-action" onclick={add_new_blue_team}>
                                 { "Add new team" }
                             </a>
                         </div>
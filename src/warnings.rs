@@ -0,0 +1,566 @@
+// warnings.rs: Advisories about configurations that are valid, but likely mistaken
+//
+// Copyright (C) 2023 Andrew Rioux
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{fmt::Display, net::Ipv4Addr};
+
+use crate::{
+    config::{
+        convert_editor_to_final, ConfigurationEditor, ConversionError, MachineEditor,
+        ServiceDefinition,
+    },
+    machines::default_service,
+    passwords::DEFAULT_PASSWORD,
+};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ConfigurationWarning {
+    UnknownMailDomain(String, String, String),
+    ServiceNotScoredForTeams(String, String, Vec<String>),
+    UnusualPort(String, String, u16, u16),
+    ZeroPoints(String, String),
+    UntrimmedName(String, String),
+    PortlessServiceHasPort(String, String, u16),
+    SecureServiceOnPlaintextPort(String, String, u16),
+    ServiceHasNoPort(String, String),
+    ImplausibleMatcher(String, String, String, &'static str),
+    NoMailboxForSmtp(String, String),
+    DefaultAccountPassword(String, String, String),
+}
+
+impl Display for ConfigurationWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownMailDomain(machine, service, domain) => {
+                write!(
+                    f,
+                    "the service {service} on machine {machine} sends mail to {domain}, which doesn't match any machine in this configuration"
+                )
+            }
+            Self::ServiceNotScoredForTeams(machine, service, teams) => {
+                write!(
+                    f,
+                    "the service {service} on machine {machine} is not scored for {}, unlike the other teams",
+                    teams.join(", ")
+                )
+            }
+            Self::UnusualPort(machine, service, port, conventional) => {
+                write!(
+                    f,
+                    "the service {service} on machine {machine} uses port {port} rather than the conventional {conventional}; it may have been copied from another service"
+                )
+            }
+            Self::UntrimmedName(location, name) => {
+                write!(
+                    f,
+                    "{location} '{name}' has leading or trailing whitespace, so it won't match '{}'",
+                    name.trim()
+                )
+            }
+            Self::PortlessServiceHasPort(machine, service, port) => {
+                write!(
+                    f,
+                    "the service {service} on machine {machine} is set to port {port}, but its check doesn't use a port; it should be left at 0"
+                )
+            }
+            Self::SecureServiceOnPlaintextPort(machine, service, port) => {
+                write!(
+                    f,
+                    "the service {service} on machine {machine} checks over TLS, but port {port} is the plaintext port of the protocol"
+                )
+            }
+            Self::ServiceHasNoPort(machine, service) => {
+                write!(
+                    f,
+                    "the service {service} on machine {machine} is set to port 0, which its check can't connect to"
+                )
+            }
+            Self::ImplausibleMatcher(machine, service, matcher, reason) => {
+                write!(
+                    f,
+                    "the service {service} on machine {machine} matches '{matcher}', which {reason}; matchers are freeform, so this may be intended"
+                )
+            }
+            Self::NoMailboxForSmtp(machine, service) => {
+                write!(
+                    f,
+                    "the service {service} on machine {machine} sends mail, but no IMAP or POP3 service on that machine or on the recipient's checks a mailbox that could receive it"
+                )
+            }
+            Self::DefaultAccountPassword(machine, service, username) => {
+                write!(
+                    f,
+                    "the account '{username}' of the service {service} on machine {machine} still has the default password {DEFAULT_PASSWORD}"
+                )
+            }
+            Self::ZeroPoints(machine, service) => {
+                write!(
+                    f,
+                    "the service {service} on machine {machine} is worth 0 points; mark it as informational if it is only there for monitoring"
+                )
+            }
+        }
+    }
+}
+
+/// Checks whether the domain of an email address plausibly refers to one of the
+/// machines in the configuration, either by name or by address
+fn mail_domain_is_known(config: &ConfigurationEditor, domain: &str) -> bool {
+    config
+        .machines
+        .iter()
+        .any(|machine| mail_domain_refers_to(config, machine, domain))
+}
+
+fn mail_domain_refers_to(
+    config: &ConfigurationEditor,
+    machine: &MachineEditor,
+    domain: &str,
+) -> bool {
+    let domain = domain
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_lowercase();
+    let name = machine.name.to_lowercase();
+    let host = config.ip_template_for(machine).to_lowercase();
+
+    (!host.is_empty() && host == domain)
+        || (!name.is_empty() && domain.split('.').any(|label| label == name))
+}
+
+fn mail_domain_warnings(config: &ConfigurationEditor) -> Vec<ConfigurationWarning> {
+    let mut warnings = vec![];
+
+    for machine in &config.machines {
+        for service in &machine.services {
+            let (ServiceDefinition::Smtp { environment }
+            | ServiceDefinition::Smtps { environment }) = &service.definition
+            else {
+                continue;
+            };
+
+            for check in environment.iter().filter(|check| !check.disabled) {
+                let Some((_, domain)) = check.touser.rsplit_once('@') else {
+                    continue;
+                };
+
+                if domain.is_empty() || mail_domain_is_known(config, domain) {
+                    continue;
+                }
+
+                warnings.push(ConfigurationWarning::UnknownMailDomain(
+                    machine.name.clone(),
+                    service.name.clone(),
+                    domain.to_owned(),
+                ));
+            }
+        }
+    }
+
+    warnings
+}
+
+fn has_mailbox_service(machine: &MachineEditor) -> bool {
+    machine.services.iter().any(|service| {
+        matches!(
+            service.definition,
+            ServiceDefinition::Imap { .. }
+                | ServiceDefinition::Imaps { .. }
+                | ServiceDefinition::Pop3 { .. }
+                | ServiceDefinition::Pop3s { .. }
+        )
+    })
+}
+
+/// Mail sent by an SMTP check can only be confirmed as delivered if something reads
+/// the mailbox it went to, which is usually an IMAP or POP3 service on the same machine
+/// or on the machine the recipient's domain refers to
+fn mailbox_warnings(config: &ConfigurationEditor) -> Vec<ConfigurationWarning> {
+    let mut warnings = vec![];
+
+    for machine in &config.machines {
+        if has_mailbox_service(machine) {
+            continue;
+        }
+
+        for service in &machine.services {
+            let (ServiceDefinition::Smtp { environment }
+            | ServiceDefinition::Smtps { environment }) = &service.definition
+            else {
+                continue;
+            };
+
+            let recipient_has_mailbox = environment
+                .iter()
+                .filter(|check| !check.disabled)
+                .filter_map(|check| check.touser.rsplit_once('@'))
+                .any(|(_, domain)| {
+                    config.machines.iter().any(|other| {
+                        has_mailbox_service(other) && mail_domain_refers_to(config, other, domain)
+                    })
+                });
+
+            if !recipient_has_mailbox {
+                warnings.push(ConfigurationWarning::NoMailboxForSmtp(
+                    machine.name.clone(),
+                    service.name.clone(),
+                ));
+            }
+        }
+    }
+
+    warnings
+}
+
+fn team_coverage_warnings(config: &ConfigurationEditor) -> Vec<ConfigurationWarning> {
+    let mut warnings = vec![];
+
+    for machine in &config.machines {
+        for service in &machine.services {
+            let excluded = config
+                .blue_teams
+                .iter()
+                .filter(|team| service.excluded_teams.contains(&team.id))
+                .map(|team| team.name.clone())
+                .collect::<Vec<_>>();
+
+            if !excluded.is_empty() {
+                warnings.push(ConfigurationWarning::ServiceNotScoredForTeams(
+                    machine.name.clone(),
+                    service.name.clone(),
+                    excluded,
+                ));
+            }
+        }
+    }
+
+    warnings
+}
+
+/// The port the unencrypted version of a TLS check conventionally uses, which is
+/// easily left in place when copying the plaintext service
+fn plaintext_port(definition: &ServiceDefinition) -> Option<u16> {
+    match definition {
+        ServiceDefinition::Https { .. } => Some(80),
+        ServiceDefinition::Imaps { .. } => Some(143),
+        ServiceDefinition::Pop3s { .. } => Some(110),
+        ServiceDefinition::Smtps { .. } => Some(25),
+        _ => None,
+    }
+}
+
+fn port_warnings(config: &ConfigurationEditor) -> Vec<ConfigurationWarning> {
+    let mut warnings = vec![];
+
+    for machine in &config.machines {
+        for service in &machine.services {
+            let conventional = default_service(&service.definition).port;
+
+            if service.definition.is_portless() {
+                if service.port != 0 {
+                    warnings.push(ConfigurationWarning::PortlessServiceHasPort(
+                        machine.name.clone(),
+                        service.name.clone(),
+                        service.port,
+                    ));
+                }
+                continue;
+            }
+
+            if service.port == 0 {
+                warnings.push(ConfigurationWarning::ServiceHasNoPort(
+                    machine.name.clone(),
+                    service.name.clone(),
+                ));
+                continue;
+            }
+
+            if service.allow_unusual_port || service.port == conventional {
+                continue;
+            }
+
+            if plaintext_port(&service.definition) == Some(service.port) {
+                warnings.push(ConfigurationWarning::SecureServiceOnPlaintextPort(
+                    machine.name.clone(),
+                    service.name.clone(),
+                    service.port,
+                ));
+                continue;
+            }
+
+            warnings.push(ConfigurationWarning::UnusualPort(
+                machine.name.clone(),
+                service.name.clone(),
+                service.port,
+                conventional,
+            ));
+        }
+    }
+
+    warnings
+}
+
+fn zero_point_warnings(config: &ConfigurationEditor) -> Vec<ConfigurationWarning> {
+    config
+        .machines
+        .iter()
+        .flat_map(|machine| {
+            machine
+                .services
+                .iter()
+                .filter(|service| service.points == 0 && !service.informational)
+                .map(|service| {
+                    ConfigurationWarning::ZeroPoints(machine.name.clone(), service.name.clone())
+                })
+        })
+        .collect()
+}
+
+fn untrimmed_name_warnings(config: &ConfigurationEditor) -> Vec<ConfigurationWarning> {
+    let mut names = vec![];
+
+    for team in &config.red_white_teams {
+        names.push(("the team name".to_owned(), &team.name));
+        names.extend(team.users.iter().map(|user| {
+            (
+                format!("a username of the team {}", team.name),
+                &user.username,
+            )
+        }));
+    }
+
+    for team in &config.blue_teams {
+        names.push(("the team name".to_owned(), &team.name));
+        names.extend(team.users.iter().map(|user| {
+            (
+                format!("a username of the team {}", team.name),
+                &user.username,
+            )
+        }));
+    }
+
+    for machine in &config.machines {
+        names.push(("the machine name".to_owned(), &machine.name));
+
+        for service in &machine.services {
+            names.push((
+                format!("the name of a service on machine {}", machine.name),
+                &service.name,
+            ));
+            names.extend(service.accounts.iter().flatten().map(|user| {
+                (
+                    format!(
+                        "an account username of the service {} on machine {}",
+                        service.name, machine.name
+                    ),
+                    &user.username,
+                )
+            }));
+        }
+    }
+
+    names
+        .into_iter()
+        .filter(|(_, name)| name.trim().len() != name.len())
+        .map(|(location, name)| ConfigurationWarning::UntrimmedName(location, name.clone()))
+        .collect()
+}
+
+/// Accounts added in the editor start with a well known password, which is easily
+/// forgotten about once the username is filled in
+fn default_password_warnings(config: &ConfigurationEditor) -> Vec<ConfigurationWarning> {
+    let mut warnings = vec![];
+
+    for machine in &config.machines {
+        for service in &machine.services {
+            warnings.extend(
+                service
+                    .accounts
+                    .iter()
+                    .flatten()
+                    .filter(|user| user.password == DEFAULT_PASSWORD)
+                    .map(|user| {
+                        ConfigurationWarning::DefaultAccountPassword(
+                            machine.name.clone(),
+                            service.name.clone(),
+                            user.username.clone(),
+                        )
+                    }),
+            );
+        }
+    }
+
+    warnings
+}
+
+/// Text that only turns up in the output of one kind of check, for spotting matchers
+/// pasted in from a service of another type
+const CHECK_OUTPUT_MARKERS: &[(&str, &str, &str)] = &[
+    (
+        "packets transmitted",
+        "ICMP",
+        "looks like it was copied from an ICMP check",
+    ),
+    (
+        "ACCOUNT FOUND",
+        "VNC",
+        "looks like it was copied from a VNC check",
+    ),
+    (
+        "200 OK",
+        "HTTP",
+        "looks like it was copied from an HTTP check",
+    ),
+];
+
+fn check_family(definition: &ServiceDefinition) -> Option<&'static str> {
+    match definition {
+        ServiceDefinition::Icmp { .. } => Some("ICMP"),
+        ServiceDefinition::Vnc { .. } => Some("VNC"),
+        ServiceDefinition::Http { .. }
+        | ServiceDefinition::Https { .. }
+        | ServiceDefinition::Wordpress { .. } => Some("HTTP"),
+        _ => None,
+    }
+}
+
+/// Why a matcher is unlikely to turn up in the output of the given check, if it is
+fn implausible_matcher_reason(
+    definition: &ServiceDefinition,
+    matcher: &str,
+) -> Option<&'static str> {
+    if matcher.is_empty() {
+        return None;
+    }
+
+    let family = check_family(definition);
+
+    if family == Some("ICMP") && !matcher.to_lowercase().contains("packet") {
+        return Some("doesn't mention packets, unlike the output of ping");
+    }
+
+    if family == Some("HTTP") && matcher.parse::<Ipv4Addr>().is_ok() {
+        return Some("looks like a DNS answer rather than part of a web page");
+    }
+
+    CHECK_OUTPUT_MARKERS
+        .iter()
+        .find(|(marker, marker_family, _)| {
+            Some(*marker_family) != family && matcher.contains(marker)
+        })
+        .map(|(_, _, reason)| *reason)
+}
+
+fn implausible_matcher_warnings(config: &ConfigurationEditor) -> Vec<ConfigurationWarning> {
+    let mut warnings = vec![];
+
+    for machine in &config.machines {
+        for service in &machine.services {
+            // Checks that can't be converted are already reported as errors elsewhere
+            let Ok(environments) = service.definition.environments(
+                &machine.name,
+                &service.name,
+                config.engine_version,
+            ) else {
+                continue;
+            };
+
+            let mut matchers = environments
+                .into_iter()
+                .map(|environment| environment.matching_content)
+                .collect::<Vec<_>>();
+            matchers.dedup();
+
+            for matcher in matchers {
+                if let Some(reason) = implausible_matcher_reason(&service.definition, &matcher) {
+                    warnings.push(ConfigurationWarning::ImplausibleMatcher(
+                        machine.name.clone(),
+                        service.name.clone(),
+                        matcher,
+                        reason,
+                    ));
+                }
+            }
+        }
+    }
+
+    warnings
+}
+
+pub fn collect_warnings(config: &ConfigurationEditor) -> Vec<ConfigurationWarning> {
+    [
+        mail_domain_warnings(config),
+        team_coverage_warnings(config),
+        port_warnings(config),
+        zero_point_warnings(config),
+        untrimmed_name_warnings(config),
+        implausible_matcher_warnings(config),
+        mailbox_warnings(config),
+        default_password_warnings(config),
+    ]
+    .concat()
+}
+
+/// How many problems keep a configuration from being converted. Conversion stops at
+/// the first problem it finds, so every unfinished check is counted on top of it to
+/// give a sense of how much work is left
+pub fn conversion_error_count(config: &ConfigurationEditor) -> usize {
+    let Err(error) = convert_editor_to_final(config) else {
+        return 0;
+    };
+
+    let field_errors = config
+        .machines
+        .iter()
+        .flat_map(|machine| &machine.services)
+        .map(|service| service.definition.field_errors(config.engine_version).len())
+        .sum::<usize>();
+
+    match error {
+        ConversionError::ServiceNotFullyConfigured(..) => field_errors.max(1),
+        _ => field_errors + 1,
+    }
+}
+
+/// A snapshot of how healthy a configuration was when it was brought into the editor,
+/// so problems are known before any editing starts
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct HealthReport {
+    pub action: &'static str,
+    pub problems: Vec<String>,
+}
+
+impl HealthReport {
+    pub fn check(action: &'static str, config: &ConfigurationEditor) -> Self {
+        let mut problems = vec![];
+
+        if let Err(e) = convert_editor_to_final(config) {
+            problems.push(e.to_string());
+        }
+
+        problems.extend(collect_warnings(config).iter().map(ToString::to_string));
+
+        Self { action, problems }
+    }
+
+    pub fn summary(&self) -> String {
+        match self.problems.len() {
+            0 => format!("{} with no validation problems", self.action),
+            1 => format!("{} with 1 validation warning", self.action),
+            n => format!("{} with {n} validation warnings", self.action),
+        }
+    }
+}
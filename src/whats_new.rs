@@ -0,0 +1,113 @@
+// whats_new.rs: Tells returning users what changed since the last version they used
+//
+// Copyright (C) 2023 Andrew Rioux
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use gloo_storage::{LocalStorage, Storage};
+use yew::prelude::*;
+
+use crate::{state, tour};
+
+const LAST_SEEN_VERSION_KEY: &str = "last_seen_version";
+
+const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The notes for each release, newest first
+pub const RELEASES: &[(&str, &[&str])] = &[(
+    "0.2.0",
+    &[
+        "Services can set their own check interval and timeout, or be marked informational so they aren't scored.",
+        "Machines can be imported from an Nmap scan, and a machine's services can be copied onto other machines.",
+        "Every team and service account password can be rotated at once, with the new credentials downloaded as a CSV.",
+        "The output page can export a roster of every team's users, summary statistics, and the changes since the last export.",
+        "Services are color coded by category, and the checks a service generates can be previewed while editing it.",
+    ],
+)];
+
+/// The numbered parts of a version, so that 0.10.0 sorts after 0.9.0
+fn version_parts(version: &str) -> Vec<u32> {
+    version
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+fn remember_current_version() {
+    if let Err(e) = LocalStorage::set(LAST_SEEN_VERSION_KEY, CURRENT_VERSION) {
+        log::error!("Could not remember the editor version was seen: {e:?}");
+    }
+}
+
+/// The releases newer than the version last used in this browser
+fn unseen_releases() -> Vec<(&'static str, &'static [&'static str])> {
+    let Ok(last_seen) = LocalStorage::get::<String>(LAST_SEEN_VERSION_KEY) else {
+        // Someone without any configurations who hasn't even seen the tour is new
+        // rather than returning, and has nothing to catch up on; anyone else used a
+        // version from before this panel
+        if !tour::seen() && !state::has_stored_configurations() {
+            remember_current_version();
+            return vec![];
+        }
+        return RELEASES.to_vec();
+    };
+
+    let last_seen = version_parts(&last_seen);
+    let current = version_parts(CURRENT_VERSION);
+
+    RELEASES
+        .iter()
+        .filter(|(version, _)| {
+            let version = version_parts(version);
+            version > last_seen && version <= current
+        })
+        .copied()
+        .collect()
+}
+
+#[function_component]
+pub fn WhatsNew() -> Html {
+    let releases = use_state(unseen_releases);
+
+    let dismiss = {
+        let releases = releases.clone();
+
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            remember_current_version();
+            releases.set(vec![]);
+        })
+    };
+
+    if releases.is_empty() {
+        return html! {};
+    }
+
+    html! {
+        <div class="whats-new">
+            <h4>{ "What's new" }</h4>
+
+            { for releases.iter().map(|(version, notes)| html! {
+                <>
+                    <h5>{ format!("Version {version}") }</h5>
+                    <ul>
+                        { for notes.iter().map(|note| html! { <li>{ note }</li> }) }
+                    </ul>
+                </>
+            }) }
+
+            <a href="#" class="button" onclick={dismiss}>{ "Got it" }</a>
+        </div>
+    }
+}
@@ -0,0 +1,119 @@
+// worker.rs: Conversion of configurations on a Web Worker, away from the UI thread
+//
+// Copyright (C) 2023 Andrew Rioux
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use gloo_worker::{Codec, HandlerId, Worker, WorkerScope};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+
+use crate::{
+    config::{convert_editor_to_final, Configuration, ConfigurationEditor, FinalConfiguration},
+    error::EditorError,
+};
+
+/// Passes messages to and from the worker as JSON; the default bincode encoding
+/// can't represent the internally tagged enums used throughout the configuration
+pub struct JsonCodec;
+
+impl Codec for JsonCodec {
+    fn encode<I>(input: I) -> JsValue
+    where
+        I: Serialize,
+    {
+        JsValue::from_str(&serde_json::to_string(&input).expect("can't serialize worker message"))
+    }
+
+    fn decode<O>(input: JsValue) -> O
+    where
+        O: for<'de> Deserialize<'de>,
+    {
+        let input = input.as_string().expect("worker message was not a string");
+        serde_json::from_str(&input).expect("can't deserialize worker message")
+    }
+}
+
+/// The language the generated configuration is written in
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+pub enum OutputFormat {
+    Yaml,
+    Json,
+}
+
+impl OutputFormat {
+    pub fn serialize<T: Serialize>(self, value: &T) -> Result<String, EditorError> {
+        Ok(match self {
+            OutputFormat::Yaml => serde_yaml::to_string(value)?,
+            OutputFormat::Json => serde_json::to_string_pretty(value)?,
+        })
+    }
+}
+
+/// A configuration to convert, numbered so that answers to earlier requests can be
+/// told apart from the answer to the latest
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConversionRequest {
+    pub generation: u32,
+    pub config: ConfigurationEditor,
+    pub format: OutputFormat,
+    /// Write the editor data alongside the generated configuration, so it can be
+    /// opened in the editor again
+    pub with_editor_info: bool,
+}
+
+/// A converted configuration, along with the text it's written out as
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct Converted {
+    pub config: FinalConfiguration,
+    pub text: String,
+}
+
+/// Converts configurations to YAML or JSON, answering each request with the
+/// generation it was sent with so stale results can be told apart from current ones
+pub struct ConversionWorker;
+
+impl Worker for ConversionWorker {
+    type Message = ();
+    type Input = ConversionRequest;
+    type Output = (u32, Result<Converted, String>);
+
+    fn create(_scope: &WorkerScope<Self>) -> Self {
+        Self
+    }
+
+    fn update(&mut self, _scope: &WorkerScope<Self>, _msg: Self::Message) {}
+
+    fn received(&mut self, scope: &WorkerScope<Self>, request: Self::Input, id: HandlerId) {
+        let result = convert_editor_to_final(&request.config)
+            .map_err(EditorError::Conversion)
+            .and_then(|(final_config, _)| {
+                let text = if request.with_editor_info {
+                    request
+                        .format
+                        .serialize(&Configuration::new(request.config, final_config.clone()))?
+                } else {
+                    request.format.serialize(&final_config)?
+                };
+
+                Ok(Converted {
+                    config: final_config,
+                    text,
+                })
+            })
+            .map_err(|err| err.to_string());
+
+        scope.respond(id, (request.generation, result));
+    }
+}